@@ -14,7 +14,7 @@ describe('exampleOracleRequest', () => {
   it('should create a request in the correct data account', async () => {
     const clientProgramId = solanaTestHelper.programs[CLIET_PROGRAM_KEY];
     const oracleProgramId = solanaTestHelper.programs[ORACLE_PROGRAM_KEY];
-    const oracleDataAccount = await createDataAccountForProgram(
+    const oracleId = await createDataAccountForProgram(
       solanaTestHelper.connection,
       solanaTestHelper.accounts[0],
       oracleProgramId,
@@ -26,12 +26,12 @@ describe('exampleOracleRequest', () => {
       payerAccount,
       clientProgramId,
       oracleProgramId,
-      oracleDataAccount.publicKey,
+      oracleId,
     );
     expect(resp).toBeTruthy();
 
     const accountInfo = await solanaTestHelper.connection.getAccountInfo(
-      oracleDataAccount.publicKey,
+      oracleId,
     );
 
     // eslint-disable-next-line @typescript-eslint/no-non-null-assertion
@@ -43,7 +43,7 @@ describe('exampleOracleRequest', () => {
 
   it('should create a request by calling to the ttp oracle program directly', async () => {
     const oracleProgramId = solanaTestHelper.programs[ORACLE_PROGRAM_KEY];
-    const oracleDataAccount = await createDataAccountForProgram(
+    const oracleId = await createDataAccountForProgram(
       solanaTestHelper.connection,
       solanaTestHelper.accounts[0],
       oracleProgramId,
@@ -59,14 +59,14 @@ describe('exampleOracleRequest', () => {
       solanaTestHelper.connection,
       payerAccount,
       oracleProgramId,
-      oracleDataAccount.publicKey,
+      oracleId,
       dataBuffer,
     );
 
     expect(resp).toBeTruthy();
 
     const accountInfo = await solanaTestHelper.connection.getAccountInfo(
-      oracleDataAccount.publicKey,
+      oracleId,
     );
 
     // eslint-disable-next-line @typescript-eslint/no-non-null-assertion
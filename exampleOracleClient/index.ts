@@ -3,6 +3,8 @@ import {
   Connection,
   PublicKey,
   sendAndConfirmTransaction,
+  SystemProgram,
+  SYSVAR_CLOCK_PUBKEY,
   Transaction,
   TransactionInstruction,
 } from '@solana/web3.js';
@@ -15,7 +17,12 @@ export const sendTransactionDirectlyToOracleProgram = async (
   data: Buffer,
 ): Promise<string> => {
   const createRequestTxInstruction = new TransactionInstruction({
-    keys: [{ pubkey: oracleId, isSigner: false, isWritable: true }],
+    keys: [
+      { pubkey: oracleId, isSigner: false, isWritable: true },
+      { pubkey: payerAccount.publicKey, isSigner: true, isWritable: true },
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+      { pubkey: SYSVAR_CLOCK_PUBKEY, isSigner: false, isWritable: false },
+    ],
     programId: oracleProgramId,
     data,
   });
@@ -42,6 +49,9 @@ export const sendTransactionToCreateRequest = async (
     keys: [
       { pubkey: oracleProgramId, isSigner: false, isWritable: false },
       { pubkey: oracleId, isSigner: false, isWritable: true },
+      { pubkey: payerAccount.publicKey, isSigner: true, isWritable: true },
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+      { pubkey: SYSVAR_CLOCK_PUBKEY, isSigner: false, isWritable: false },
     ],
     programId,
     // creating request is a u8 of 0
@@ -14,10 +14,19 @@ pub mod processor;
 entrypoint!(process_instruction);
 
 /**
- * Takes 2 accounts
+ * 0 - Make a request, takes 5 accounts
  * 
  * 0. Oracle Program - program Id of the oracle program
  * 1. [writable] Oracle - Account for the oracle to make the request
+ * 2. [writable, signer] Payer - pays the oracle's request fee
+ * 3. System Program
+ * 4. Clock sysvar
+ *
+ * 1 - Read a feed, takes 3 accounts
+ *
+ * 0. Oracle Program - program Id of the oracle program
+ * 1. Feed - Account of the feed to read
+ * 2. Clock sysvar
  */
 fn process_instruction(
     program_id: &Pubkey,
@@ -29,6 +38,9 @@ fn process_instruction(
     0 => {
       processor::process_add_request(program_id, accounts, &[])
     },
+    1 => {
+      processor::process_read_feed(program_id, accounts, &instruction_data[1..])
+    },
     CALLBACK_DETERMINANT => {
       processor::process_handle_response(program_id, accounts, &instruction_data[1..])
     },
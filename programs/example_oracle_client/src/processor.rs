@@ -2,13 +2,17 @@ use std::format;
 use solana_program::{
     account_info::{ next_account_info, AccountInfo, },
     program::invoke,
+    program_error::ProgramError,
+    program_pack::Pack,
     entrypoint::ProgramResult,
     pubkey::Pubkey,
     info
 };
 use solana_bpf_ttp_oracle::{
+    feed::read_feed,
     instruction::create_request,
-    request::{ GetArgs, GetParams, JsonParseArgs, Request, Task },
+    oracle_account::OracleHeader,
+    request::{ GetArgs, GetParams, JsonParseArgs, OptimisticResponse, Request, Task },
 };
 use generic_array::GenericArray;
 use arrayref::array_ref;
@@ -26,6 +30,24 @@ pub fn process_handle_response(
   Ok(())
 }
 
+/// Feed values older than this are rejected
+const MAX_FEED_AGE_SLOTS: u64 = 150;
+
+pub fn process_read_feed(
+  _program_id: &Pubkey,
+  accounts: &[AccountInfo],
+  _instruction_data: &[u8],
+) -> ProgramResult {
+  let accounts_iter = &mut accounts.iter();
+  let oracle_program_account = next_account_info(accounts_iter)?;
+  let feed_account = next_account_info(accounts_iter)?;
+  let clock_account = next_account_info(accounts_iter)?;
+
+  let feed_value = read_feed(oracle_program_account.key, feed_account, clock_account, MAX_FEED_AGE_SLOTS)?;
+  info!(&format!("Oracle feed price = {} (round {})", feed_value.value, feed_value.round));
+  Ok(())
+}
+
 pub fn process_add_request(
   program_id: &Pubkey,
   accounts: &[AccountInfo],
@@ -34,16 +56,29 @@ pub fn process_add_request(
   let accounts_iter = &mut accounts.iter();
   let oracle_program_account = next_account_info(accounts_iter)?;
   let oracle_account = next_account_info(accounts_iter)?;
+  let payer_account = next_account_info(accounts_iter)?;
+  let system_program_account = next_account_info(accounts_iter)?;
+  let clock_account = next_account_info(accounts_iter)?;
   
-  let request = create_example_request(program_id);
+  let mut request = create_example_request(program_id);
+  // offer exactly the Oracle's price for the Request's tasks
+  let header = OracleHeader::unpack(&oracle_account.data.borrow()[0..OracleHeader::LEN])?;
+  request.fee = header.fee_for(&request.tasks).ok_or(ProgramError::InvalidArgument)?;
   
   let ix = create_request(
       oracle_program_account.key, 
       oracle_account.key, 
+      payer_account.key,
       request,
   )?;
 
-  invoke(&ix, &[oracle_program_account.clone(), oracle_account.clone()])
+  invoke(&ix, &[
+    oracle_program_account.clone(),
+    oracle_account.clone(),
+    payer_account.clone(),
+    system_program_account.clone(),
+    clock_account.clone(),
+  ])
 }
 
 fn create_example_request(_program_id: &Pubkey) -> Request {
@@ -55,9 +90,7 @@ fn create_example_request(_program_id: &Pubkey) -> Request {
     let params = GetParams {
       get: *GenericArray::from_slice(url_bytes)
     };
-    let args = GetArgs {
-      params: params
-    };
+    let args = GetArgs::new(params);
     let get_task = Task::HttpGet(args);
     let json_parse_task = Task::JsonParse(json_args);
     let uint_128_task = Task::Uint32;
@@ -66,6 +99,14 @@ fn create_example_request(_program_id: &Pubkey) -> Request {
       tasks: [get_task, json_parse_task, uint_128_task],
       call_back_program: *_program_id,
       index: 0,
+      requester: Pubkey::default(),
+      fee: 0,
+      created_slot: 0,
+      id: 0,
+      tip: 0,
+      max_answer_age_slots: 0,
+      job_id: 0,
+      optimistic: OptimisticResponse::default(),
     }
 }
 
@@ -73,16 +114,18 @@ fn create_example_request(_program_id: &Pubkey) -> Request {
 mod tests {
     use super::*;
     use solana_program::{
-      clock::Epoch,
+      clock::{ Clock, Epoch },
       instruction::Instruction,
-      program_error::ProgramError,
-      program_pack::Pack,
       program_stubs,
+      system_program,
+      sysvar::{ self, Sysvar },
     };
   use solana_bpf_ttp_oracle::{ 
-    oracle_account::OracleAccount,
+    answer_cache::AnswerCache,
+    fee_schedule::FeeSchedule,
+    oracle_account::{ OracleAccount, OracleHeader },
     processor::Processor,
-    request::Request,
+    request::{ Request, ALL_TASK_KINDS, REQUEST_QUEUE_SIZE },
   };
 
     // test program id for ttp-oralce program
@@ -134,17 +177,56 @@ mod tests {
       setup_syscall_stubs();
       let oracle_program_owner = Pubkey::default();
       let oracle_id = Pubkey::default();
+      let payer_id = Pubkey::new_unique();
+      let system_program_id = system_program::id();
+      let clock_id = sysvar::clock::id();
+      let sysvar_owner = sysvar::id();
       let mut lamports1 = 0;
       let mut lamports2 = 0;
+      let mut lamports3 = 0;
+      let mut lamports4 = 0;
+      let mut lamports5 = 0;
       let mut oracle_data_buffer = vec![0; OracleAccount::LEN];
+      let header = OracleHeader {
+        is_initialized: true,
+        admin: Pubkey::new_unique(),
+        node_authority: Pubkey::new_unique(),
+        request_fee: 0,
+        request_expiry_slots: 300,
+        fee_mint: Pubkey::default(),
+        fee_vault: Pubkey::default(),
+        vault_authority_bump: 0,
+        fee_schedule: FeeSchedule::default(),
+        min_stake: 0,
+        unstake_cooldown_slots: 0,
+        dispute_bond: 0,
+        pending_admin: Pubkey::default(),
+        max_url_len: 34,
+        allowed_task_kinds: ALL_TASK_KINDS,
+        quorum: 1,
+        is_paused: false,
+        max_requests_per_requester: 0,
+        capacity: REQUEST_QUEUE_SIZE as u16,
+        next_request_id: 0,
+        answer_cache: AnswerCache::default(),
+        supported_secrets: Default::default(),
+      };
+      OracleHeader::pack(header, &mut oracle_data_buffer[0..OracleHeader::LEN]).unwrap();
+      let mut clock_data_buffer = vec![0; Clock::size_of()];
       
       let oracle_account = AccountInfo::new(&oracle_id, false, true, &mut lamports1, &mut oracle_data_buffer, &TTP_ORACLE_PROGRAM_ID, false, Epoch::default());
       let oracle_program_account = AccountInfo::new(&TTP_ORACLE_PROGRAM_ID, false, false, &mut lamports2, &mut [], &oracle_program_owner, true, Epoch::default());
-      let accounts = vec![oracle_program_account, oracle_account];
+      let payer_account = AccountInfo::new(&payer_id, true, true, &mut lamports3, &mut [], &system_program_id, false, Epoch::default());
+      let system_program_account = AccountInfo::new(&system_program_id, false, false, &mut lamports4, &mut [], &oracle_program_owner, true, Epoch::default());
+      let mut clock_account = AccountInfo::new(&clock_id, false, false, &mut lamports5, &mut clock_data_buffer, &sysvar_owner, false, Epoch::default());
+      Clock { slot: 7, ..Clock::default() }.to_account_info(&mut clock_account).unwrap();
+      let accounts = vec![oracle_program_account, oracle_account, payer_account, system_program_account, clock_account];
       
       let ret = process_add_request(&CLIENT_PROGRAM_ID, &accounts, &[]);
       assert!(ret.is_ok());
-      let request = create_example_request(&CLIENT_PROGRAM_ID);
+      let mut request = create_example_request(&CLIENT_PROGRAM_ID);
+      request.requester = payer_id;
+      request.created_slot = 7;
       let mut expected_request = [0; Request::LEN];
       request.pack_into_slice(&mut expected_request);
 
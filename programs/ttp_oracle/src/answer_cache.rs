@@ -0,0 +1,147 @@
+use crate::response::{ ResponseData, RESPONSE_DATA_LEN };
+use solana_program::{
+  clock::Slot,
+  hash::HASH_BYTES,
+  program_error::ProgramError,
+  program_pack::{ Pack, Sealed },
+};
+use arrayref::{ array_mut_ref, array_ref, array_refs, mut_array_refs };
+
+pub const ANSWER_CACHE_SIZE: usize = 4;
+const ANSWER_SLOT_SIZE: usize = 8;
+
+/// Answer delivered for a Request's Tasks. An all zero hash marks an unused
+/// entry.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CachedAnswer {
+  // `Request::task_hash` of the Tasks that produced the answer
+  pub task_hash: [u8; HASH_BYTES],
+  pub data: ResponseData,
+  // Slot the node produced the answer at
+  pub slot: Slot,
+}
+
+impl CachedAnswer {
+  pub fn is_empty(&self) -> bool {
+    self.task_hash == [0u8; HASH_BYTES]
+  }
+}
+
+impl Sealed for CachedAnswer {}
+impl Pack for CachedAnswer {
+  const LEN: usize = HASH_BYTES + RESPONSE_DATA_LEN + ANSWER_SLOT_SIZE;
+
+  fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+    let src = array_ref![src, 0, CachedAnswer::LEN];
+    let (task_hash, data, slot) = array_refs![src, HASH_BYTES, RESPONSE_DATA_LEN, ANSWER_SLOT_SIZE];
+    Ok(CachedAnswer {
+      task_hash: *task_hash,
+      data: *data,
+      slot: u64::from_le_bytes(*slot),
+    })
+  }
+
+  fn pack_into_slice(&self, dst: &mut [u8]) {
+    let dst = array_mut_ref![dst, 0, CachedAnswer::LEN];
+    let (task_hash, data, slot) = mut_array_refs![dst, HASH_BYTES, RESPONSE_DATA_LEN, ANSWER_SLOT_SIZE];
+    *task_hash = self.task_hash;
+    *data = self.data;
+    *slot = self.slot.to_le_bytes();
+  }
+}
+
+/// Latest answers the Oracle delivered, so Requests for the same Tasks can
+/// be served without the node going off-chain again.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AnswerCache {
+  pub answers: [CachedAnswer; ANSWER_CACHE_SIZE],
+}
+
+impl AnswerCache {
+  /// The answer for `task_hash` if it was produced at or after `min_slot`
+  pub fn lookup(&self, task_hash: &[u8; HASH_BYTES], min_slot: Slot) -> Option<ResponseData> {
+    self.answers.iter()
+      .find(|answer| !answer.is_empty() && answer.task_hash == *task_hash)
+      .filter(|answer| answer.slot >= min_slot)
+      .map(|answer| answer.data)
+  }
+
+  /// Store an answer in place of the one for the same Tasks, or else of the
+  /// oldest one
+  pub fn insert(&mut self, task_hash: [u8; HASH_BYTES], data: ResponseData, slot: Slot) {
+    let entry = match self.answers.iter().position(|answer| answer.task_hash == task_hash) {
+      Some(position) => &mut self.answers[position],
+      None => self.answers.iter_mut().min_by_key(|answer| answer.slot).unwrap(),
+    };
+    // a late answer does not replace a fresher one
+    if entry.task_hash == task_hash && entry.slot > slot {
+      return;
+    }
+    *entry = CachedAnswer { task_hash, data, slot };
+  }
+}
+
+impl Sealed for AnswerCache {}
+impl Pack for AnswerCache {
+  const LEN: usize = CachedAnswer::LEN * ANSWER_CACHE_SIZE;
+
+  fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+    let src = array_ref![src, 0, AnswerCache::LEN];
+    let mut cache = AnswerCache::default();
+    for (i, answer) in cache.answers.iter_mut().enumerate() {
+      *answer = CachedAnswer::unpack_from_slice(array_ref![src, i * CachedAnswer::LEN, CachedAnswer::LEN])?;
+    }
+    Ok(cache)
+  }
+
+  fn pack_into_slice(&self, dst: &mut [u8]) {
+    let dst = array_mut_ref![dst, 0, AnswerCache::LEN];
+    for (i, answer) in self.answers.iter().enumerate() {
+      answer.pack_into_slice(array_mut_ref![dst, i * CachedAnswer::LEN, CachedAnswer::LEN]);
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_pack_unpack_answer_cache() {
+    let mut cache = AnswerCache::default();
+    cache.insert([1u8; HASH_BYTES], 7u32.to_le_bytes(), 42);
+    let mut packed = [0u8; AnswerCache::LEN];
+    cache.pack_into_slice(&mut packed);
+    assert_eq!(AnswerCache::unpack_from_slice(&packed).unwrap(), cache);
+  }
+
+  #[test]
+  fn test_answer_cache_lookup() {
+    let mut cache = AnswerCache::default();
+    assert_eq!(cache.lookup(&[0u8; HASH_BYTES], 0), None);
+
+    cache.insert([1u8; HASH_BYTES], 7u32.to_le_bytes(), 42);
+    assert_eq!(cache.lookup(&[1u8; HASH_BYTES], 40), Some(7u32.to_le_bytes()));
+    // too old for the Request
+    assert_eq!(cache.lookup(&[1u8; HASH_BYTES], 43), None);
+    assert_eq!(cache.lookup(&[2u8; HASH_BYTES], 0), None);
+
+    // a newer answer for the same Tasks replaces the older one, a late one does not
+    cache.insert([1u8; HASH_BYTES], 8u32.to_le_bytes(), 50);
+    cache.insert([1u8; HASH_BYTES], 9u32.to_le_bytes(), 45);
+    assert_eq!(cache.lookup(&[1u8; HASH_BYTES], 43), Some(8u32.to_le_bytes()));
+    assert_eq!(cache.answers.iter().filter(|answer| !answer.is_empty()).count(), 1);
+  }
+
+  #[test]
+  fn test_answer_cache_evicts_oldest() {
+    let mut cache = AnswerCache::default();
+    for i in 0..ANSWER_CACHE_SIZE as u8 {
+      cache.insert([i + 1; HASH_BYTES], [i; RESPONSE_DATA_LEN], 10 + i as u64);
+    }
+    cache.insert([9u8; HASH_BYTES], [9u8; RESPONSE_DATA_LEN], 100);
+    assert_eq!(cache.lookup(&[1u8; HASH_BYTES], 0), None);
+    assert_eq!(cache.lookup(&[2u8; HASH_BYTES], 0), Some([1u8; RESPONSE_DATA_LEN]));
+    assert_eq!(cache.lookup(&[9u8; HASH_BYTES], 0), Some([9u8; RESPONSE_DATA_LEN]));
+  }
+}
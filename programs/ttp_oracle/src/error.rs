@@ -0,0 +1,101 @@
+use num_derive::FromPrimitive;
+use solana_program::{
+  decode_error::DecodeError,
+  program_error::ProgramError,
+};
+use thiserror::Error;
+
+/// Errors that may be returned by the oracle program.
+#[derive(Clone, Debug, Eq, Error, FromPrimitive, PartialEq)]
+pub enum OracleError {
+  /// The signer does not match the authority stored on the account
+  #[error("Incorrect authority")]
+  IncorrectAuthority,
+  /// The new feed value neither deviates enough nor is the heartbeat due
+  #[error("Feed update not triggered")]
+  FeedUpdateNotTriggered,
+  /// The stored value is older than the consumer accepts
+  #[error("Stale value")]
+  StaleValue,
+  /// Every slot in the RequestQueue is taken
+  #[error("Request queue full")]
+  RequestQueueFull,
+  /// The RequestQueue slot is out of range or holds no Request
+  #[error("Invalid request index")]
+  InvalidRequestIndex,
+  /// The callback program does not match the one stored on the Request
+  #[error("Incorrect callback program")]
+  IncorrectCallbackProgram,
+  /// The Request can not be cancelled before it expires
+  #[error("Request not expired")]
+  RequestNotExpired,
+  /// The token account is not the Oracle's fee vault
+  #[error("Incorrect fee vault")]
+  IncorrectFeeVault,
+  /// The fee offered for the Request is below the Oracle's price
+  #[error("Insufficient fee")]
+  InsufficientFee,
+  /// The stake is below the Oracle's minimum
+  #[error("Insufficient stake")]
+  InsufficientStake,
+  /// The node must deregister and wait out the cooldown before unstaking
+  #[error("Stake cooldown not elapsed")]
+  StakeCooldownNotElapsed,
+  /// The Request's answer is not in the state the instruction applies to
+  #[error("Invalid response status")]
+  InvalidResponseStatus,
+  /// The proposed answer can no longer be disputed
+  #[error("Dispute window closed")]
+  DisputeWindowClosed,
+  /// The proposed answer can still be disputed
+  #[error("Dispute window open")]
+  DisputeWindowOpen,
+  /// The Task kind is disabled, its url is longer than the Oracle allows or
+  /// it sends a header that is not allowed
+  #[error("Task not allowed")]
+  TaskNotAllowed,
+  /// The admin paused the Oracle
+  #[error("Oracle paused")]
+  OraclePaused,
+  /// The Feed's circuit breaker tripped, the authority must reset it
+  #[error("Feed tripped")]
+  FeedTripped,
+  /// The Oracle still holds Requests
+  #[error("Requests pending")]
+  RequestsPending,
+  /// The requester already holds as many queue slots as the Oracle allows
+  #[error("Requester limit reached")]
+  RequesterLimitReached,
+  /// The answer cache holds no answer for the Request's Tasks young enough
+  #[error("No cached answer")]
+  NoCachedAnswer,
+  /// A JobSpec template is malformed or refers to an undeclared parameter
+  #[error("Invalid job template")]
+  InvalidJobTemplate,
+  /// A Request's arguments do not match the JobSpec's parameters, hold a
+  /// character that is not allowed, or make a Task too long
+  #[error("Invalid job argument")]
+  InvalidJobArgument,
+  /// A Task refers to a secret the Oracle's node does not hold
+  #[error("Unknown secret")]
+  UnknownSecret,
+  /// The node is not registered with the Oracle's minimum stake
+  #[error("Inactive node")]
+  InactiveNode,
+  /// The node is the Oracle's node authority or proposed answers are still
+  /// pending
+  #[error("Node busy")]
+  NodeBusy,
+}
+
+impl From<OracleError> for ProgramError {
+  fn from(e: OracleError) -> Self {
+    ProgramError::Custom(e as u32)
+  }
+}
+
+impl<T> DecodeError<T> for OracleError {
+  fn type_of() -> &'static str {
+    "OracleError"
+  }
+}
@@ -0,0 +1,177 @@
+use crate::request::{ Task, TASK_KIND_COUNT };
+use solana_program::{
+  program_error::ProgramError,
+  program_pack::{ Pack, Sealed },
+};
+use arrayref::{ array_mut_ref, array_ref, array_refs, mut_array_refs };
+
+pub const MAX_HOST_FEES: usize = 4;
+pub const MAX_HOST_LEN: usize = 32;
+const TASK_FEE_SIZE: usize = 8;
+
+/// Price of HttpGet and HttpPost tasks against a single host. An all zero host marks
+/// an unused entry.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct HostFee {
+  // Zero padded host name, e.g. "ftx.us"
+  pub host: [u8; MAX_HOST_LEN],
+  pub fee: u64,
+}
+
+impl HostFee {
+  pub fn new(host: &[u8], fee: u64) -> Result<Self, ProgramError> {
+    if host.is_empty() || host.len() > MAX_HOST_LEN {
+      return Err(ProgramError::InvalidArgument);
+    }
+    let mut padded_host = [0u8; MAX_HOST_LEN];
+    padded_host[..host.len()].copy_from_slice(host);
+    Ok(HostFee { host: padded_host, fee })
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.host == [0u8; MAX_HOST_LEN]
+  }
+
+  fn matches(&self, host: &[u8]) -> bool {
+    let len = self.host.iter().position(|b| *b == 0).unwrap_or(MAX_HOST_LEN);
+    !self.is_empty() && &self.host[..len] == host
+  }
+}
+
+impl Sealed for HostFee {}
+impl Pack for HostFee {
+  const LEN: usize = MAX_HOST_LEN + TASK_FEE_SIZE;
+
+  fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+    let src = array_ref![src, 0, HostFee::LEN];
+    let (host, fee) = array_refs![src, MAX_HOST_LEN, TASK_FEE_SIZE];
+    Ok(HostFee {
+      host: *host,
+      fee: u64::from_le_bytes(*fee),
+    })
+  }
+
+  fn pack_into_slice(&self, dst: &mut [u8]) {
+    let dst = array_mut_ref![dst, 0, HostFee::LEN];
+    let (host, fee) = mut_array_refs![dst, MAX_HOST_LEN, TASK_FEE_SIZE];
+    *host = self.host;
+    *fee = self.fee.to_le_bytes();
+  }
+}
+
+/// Price of each Task in a Request, charged on top of the Oracle's base
+/// request fee. `task_fees` is indexed by `Task::kind`, HTTP tasks
+/// against a host listed in `host_fees` are charged that host's fee instead.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FeeSchedule {
+  pub task_fees: [u64; TASK_KIND_COUNT],
+  pub host_fees: [HostFee; MAX_HOST_FEES],
+}
+
+impl FeeSchedule {
+  /// Fee for running a single Task
+  pub fn task_fee(&self, task: &Task) -> u64 {
+    if let Some(host) = task.host() {
+      if let Some(host_fee) = self.host_fees.iter().find(|host_fee| host_fee.matches(host)) {
+        return host_fee.fee;
+      }
+    }
+    self.task_fees[task.kind()]
+  }
+
+  /// Fee for running every Task in `tasks`, None on overflow
+  pub fn tasks_fee(&self, tasks: &[Task]) -> Option<u64> {
+    tasks.iter().try_fold(0u64, |total, task| total.checked_add(self.task_fee(task)))
+  }
+}
+
+impl Sealed for FeeSchedule {}
+impl Pack for FeeSchedule {
+  const LEN: usize = TASK_FEE_SIZE * TASK_KIND_COUNT + HostFee::LEN * MAX_HOST_FEES;
+
+  fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+    let src = array_ref![src, 0, FeeSchedule::LEN];
+    let (task_fees_src, host_fees_src) = array_refs![src, TASK_FEE_SIZE * TASK_KIND_COUNT, HostFee::LEN * MAX_HOST_FEES];
+    let mut schedule = FeeSchedule::default();
+    for (i, task_fee) in schedule.task_fees.iter_mut().enumerate() {
+      *task_fee = u64::from_le_bytes(*array_ref![task_fees_src, i * TASK_FEE_SIZE, TASK_FEE_SIZE]);
+    }
+    for (i, host_fee) in schedule.host_fees.iter_mut().enumerate() {
+      *host_fee = HostFee::unpack_from_slice(array_ref![host_fees_src, i * HostFee::LEN, HostFee::LEN])?;
+    }
+    Ok(schedule)
+  }
+
+  fn pack_into_slice(&self, dst: &mut [u8]) {
+    let dst = array_mut_ref![dst, 0, FeeSchedule::LEN];
+    let (task_fees_dst, host_fees_dst) = mut_array_refs![dst, TASK_FEE_SIZE * TASK_KIND_COUNT, HostFee::LEN * MAX_HOST_FEES];
+    for (i, task_fee) in self.task_fees.iter().enumerate() {
+      array_mut_ref![task_fees_dst, i * TASK_FEE_SIZE, TASK_FEE_SIZE].copy_from_slice(&task_fee.to_le_bytes());
+    }
+    for (i, host_fee) in self.host_fees.iter().enumerate() {
+      host_fee.pack_into_slice(array_mut_ref![host_fees_dst, i * HostFee::LEN, HostFee::LEN]);
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::request::{ ContentType, GetArgs, GetParams, JsonParseArgs };
+  use generic_array::GenericArray;
+
+  fn get_task(url: &[u8; 34]) -> Task {
+    Task::HttpGet(GetArgs::new(GetParams { get: *GenericArray::from_slice(url) }))
+  }
+
+  fn create_sample_schedule() -> FeeSchedule {
+    let mut schedule = FeeSchedule {
+      task_fees: [1_000, 10, 1, 2_000],
+      ..FeeSchedule::default()
+    };
+    schedule.host_fees[1] = HostFee::new(b"ftx.us", 3_000).unwrap();
+    schedule
+  }
+
+  #[test]
+  fn test_pack_unpack_fee_schedule() {
+    let schedule = create_sample_schedule();
+    let mut buffer = [0u8; FeeSchedule::LEN];
+    schedule.pack_into_slice(&mut buffer);
+    assert_eq!(buffer[0..8], 1_000u64.to_le_bytes());
+    assert_eq!(FeeSchedule::unpack_from_slice(&buffer).unwrap(), schedule);
+  }
+
+  #[test]
+  fn test_tasks_fee() {
+    let schedule = create_sample_schedule();
+    let tasks = [
+      get_task(b"https://ftx.us/api/markets/BTC/USD"),
+      Task::JsonParse(JsonParseArgs { path: *b"result.price" }),
+      Task::Uint32,
+    ];
+    assert_eq!(schedule.tasks_fee(&tasks), Some(3_011));
+
+    let tasks = [
+      get_task(b"https://ftx.com/api/markets/BTC/US"),
+      Task::Uint32,
+      Task::Uint32,
+    ];
+    assert_eq!(schedule.tasks_fee(&tasks), Some(1_002));
+
+    // host fees apply to HttpPost tasks as well
+    let post_tasks = [
+      Task::http_post(b"https://ftx.us/graphql", b"{}", ContentType::Json).unwrap(),
+      Task::http_post(b"https://ftx.com/graphql", b"{}", ContentType::Json).unwrap(),
+      Task::Uint32,
+    ];
+    assert_eq!(schedule.tasks_fee(&post_tasks), Some(5_001));
+
+    let schedule = FeeSchedule {
+      task_fees: [u64::MAX, 0, 1, 0],
+      ..FeeSchedule::default()
+    };
+    assert_eq!(schedule.tasks_fee(&tasks), None);
+    assert_eq!(HostFee::new(&[b'a'; MAX_HOST_LEN + 1], 1), Err(ProgramError::InvalidArgument));
+  }
+}
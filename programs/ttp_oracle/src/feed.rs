@@ -0,0 +1,334 @@
+use crate::{
+  PUBLIC_KEY_LEN,
+  error::OracleError,
+  request::{ Task, TASK_ARRAY_SIZE },
+};
+use solana_program::{
+  account_info::AccountInfo,
+  clock::{ Clock, Slot },
+  program_error::ProgramError,
+  program_pack::{ IsInitialized, Pack, Sealed },
+  pubkey::Pubkey,
+  sysvar::Sysvar,
+};
+use arrayref::{ array_mut_ref, array_ref, array_refs, mut_array_refs };
+
+/// Basis points in 100%
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+/// A value the node keeps up to date on-chain instead of answering
+/// individual Requests. The node runs `tasks` off-chain and only submits
+/// a new value when it deviates from the stored one by more than
+/// `deviation_threshold_bps` or when `heartbeat_slots` have elapsed
+/// since the last update. A value moving more than `max_change_bps` away
+/// from the stored one trips the Feed's circuit breaker instead, which stops
+/// updates and reads until the authority resets the Feed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Feed {
+  pub is_initialized: bool,
+  // The node allowed to push updates
+  pub authority: Pubkey,
+  pub tasks: [Task; TASK_ARRAY_SIZE],
+  pub deviation_threshold_bps: u16,
+  pub heartbeat_slots: u64,
+  pub value: u32,
+  // Number of accepted updates, 0 until the first value is stored
+  pub round: u64,
+  pub last_update_slot: Slot,
+  // Largest move between consecutive values, in bps, 0 disables the breaker
+  pub max_change_bps: u16,
+  // Set when an update moved more than `max_change_bps`
+  pub is_tripped: bool,
+}
+
+impl Feed {
+  /// Check whether `new_value` submitted at `slot` should replace the
+  /// stored value. The first update is always accepted.
+  pub fn should_update(&self, new_value: u32, slot: Slot) -> bool {
+    if self.round == 0 {
+      return true;
+    }
+    if slot.saturating_sub(self.last_update_slot) >= self.heartbeat_slots {
+      return true;
+    }
+    self.deviation_exceeds_threshold(new_value)
+  }
+
+  /// Check whether `new_value` moves more than `deviation_threshold_bps`
+  /// away from the stored value.
+  pub fn deviation_exceeds_threshold(&self, new_value: u32) -> bool {
+    self.change_exceeds(new_value, self.deviation_threshold_bps)
+  }
+
+  /// Check whether `new_value` should trip the circuit breaker. The first
+  /// value has nothing to compare against and never trips it.
+  pub fn trips_breaker(&self, new_value: u32) -> bool {
+    self.round > 0 && self.max_change_bps > 0 && self.change_exceeds(new_value, self.max_change_bps)
+  }
+
+  fn change_exceeds(&self, new_value: u32, bps: u16) -> bool {
+    let old_value = self.value as u64;
+    let new_value = new_value as u64;
+    let diff = new_value.max(old_value) - new_value.min(old_value);
+    diff * BPS_DENOMINATOR > bps as u64 * old_value
+  }
+}
+
+/// The latest value of a Feed as seen by a consumer program
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FeedValue {
+  pub value: u32,
+  pub round: u64,
+  pub last_update_slot: Slot,
+}
+
+/**
+ * Read the latest value of a Feed from a consumer program.
+ *
+ * Verifies the Feed is owned by the oracle program and initialized, and
+ * that the value was updated within `max_age_slots` of the slot in the
+ * Clock sysvar. A Feed that has never been updated is considered stale,
+ * a Feed with a tripped circuit breaker can not be read.
+ */
+pub fn read_feed(
+  oracle_program_id: &Pubkey,
+  feed_info: &AccountInfo,
+  clock_info: &AccountInfo,
+  max_age_slots: u64,
+) -> Result<FeedValue, ProgramError> {
+  if feed_info.owner != oracle_program_id {
+    return Err(ProgramError::IncorrectProgramId);
+  }
+  let feed = Feed::unpack(&feed_info.data.borrow())?;
+  let clock = Clock::from_account_info(clock_info)?;
+
+  if feed.is_tripped {
+    return Err(OracleError::FeedTripped.into());
+  }
+  if feed.round == 0 || clock.slot.saturating_sub(feed.last_update_slot) > max_age_slots {
+    return Err(OracleError::StaleValue.into());
+  }
+
+  Ok(FeedValue {
+    value: feed.value,
+    round: feed.round,
+    last_update_slot: feed.last_update_slot,
+  })
+}
+
+impl Sealed for Feed {}
+impl IsInitialized for Feed {
+  fn is_initialized(&self) -> bool {
+    self.is_initialized
+  }
+}
+impl Pack for Feed {
+  const LEN: usize = 1 + PUBLIC_KEY_LEN + Task::LEN * TASK_ARRAY_SIZE + 2 + 8 + 4 + 8 + 8 + 2 + 1;
+
+  fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+    let src = array_ref![src, 0, Feed::LEN];
+    let (
+      is_initialized,
+      authority,
+      task_1,
+      task_2,
+      task_3,
+      deviation_threshold_bps,
+      heartbeat_slots,
+      value,
+      round,
+      last_update_slot,
+      max_change_bps,
+      is_tripped,
+    ) = array_refs![src, 1, PUBLIC_KEY_LEN, Task::LEN, Task::LEN, Task::LEN, 2, 8, 4, 8, 8, 2, 1];
+    let is_initialized = match is_initialized {
+      [0] => false,
+      [1] => true,
+      _ => return Err(ProgramError::InvalidAccountData),
+    };
+    let is_tripped = match is_tripped {
+      [0] => false,
+      [1] => true,
+      _ => return Err(ProgramError::InvalidAccountData),
+    };
+    Ok(Feed {
+      is_initialized,
+      authority: Pubkey::new(authority),
+      tasks: [
+        Task::unpack_from_slice(task_1)?,
+        Task::unpack_from_slice(task_2)?,
+        Task::unpack_from_slice(task_3)?,
+      ],
+      deviation_threshold_bps: u16::from_le_bytes(*deviation_threshold_bps),
+      heartbeat_slots: u64::from_le_bytes(*heartbeat_slots),
+      value: u32::from_le_bytes(*value),
+      round: u64::from_le_bytes(*round),
+      last_update_slot: u64::from_le_bytes(*last_update_slot),
+      max_change_bps: u16::from_le_bytes(*max_change_bps),
+      is_tripped,
+    })
+  }
+
+  fn pack_into_slice(&self, dst: &mut [u8]) {
+    let dst = array_mut_ref![dst, 0, Feed::LEN];
+    let (
+      is_initialized,
+      authority,
+      task_1,
+      task_2,
+      task_3,
+      deviation_threshold_bps,
+      heartbeat_slots,
+      value,
+      round,
+      last_update_slot,
+      max_change_bps,
+      is_tripped,
+    ) = mut_array_refs![dst, 1, PUBLIC_KEY_LEN, Task::LEN, Task::LEN, Task::LEN, 2, 8, 4, 8, 8, 2, 1];
+    is_initialized[0] = self.is_initialized as u8;
+    *authority = self.authority.to_bytes();
+    self.tasks[0].pack_into_slice(task_1);
+    self.tasks[1].pack_into_slice(task_2);
+    self.tasks[2].pack_into_slice(task_3);
+    *deviation_threshold_bps = self.deviation_threshold_bps.to_le_bytes();
+    *heartbeat_slots = self.heartbeat_slots.to_le_bytes();
+    *value = self.value.to_le_bytes();
+    *round = self.round.to_le_bytes();
+    *last_update_slot = self.last_update_slot.to_le_bytes();
+    *max_change_bps = self.max_change_bps.to_le_bytes();
+    is_tripped[0] = self.is_tripped as u8;
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::request::{ GetArgs, GetParams, JsonParseArgs };
+  use generic_array::GenericArray;
+  use solana_program::{ clock::Epoch, sysvar };
+
+  fn create_sample_feed() -> Feed {
+    let url_bytes = b"https://ftx.us/api/markets/BTC/USD";
+    let path_bytes = b"result.price";
+    let params = GetParams {
+      get: *GenericArray::from_slice(url_bytes)
+    };
+    Feed {
+      is_initialized: true,
+      authority: Pubkey::new(&[5u8; PUBLIC_KEY_LEN]),
+      tasks: [
+        Task::HttpGet(GetArgs::new(params)),
+        Task::JsonParse(JsonParseArgs { path: *path_bytes }),
+        Task::Uint32,
+      ],
+      deviation_threshold_bps: 50,
+      heartbeat_slots: 100,
+      value: 10_000,
+      round: 1,
+      last_update_slot: 1_000,
+      max_change_bps: 1_000,
+      is_tripped: false,
+    }
+  }
+
+  #[test]
+  fn test_pack_unpack_feed() {
+    let feed = create_sample_feed();
+    let mut feed_buffer = [0u8; Feed::LEN];
+    Feed::pack(feed.clone(), &mut feed_buffer).unwrap();
+    assert_eq!(feed_buffer[0], 1);
+
+    let deserialized_feed = Feed::unpack(&feed_buffer).unwrap();
+    assert_eq!(deserialized_feed, feed);
+
+    let empty_buffer = [0u8; Feed::LEN];
+    assert_eq!(Feed::unpack(&empty_buffer), Err(ProgramError::UninitializedAccount));
+    assert!(!Feed::unpack_unchecked(&empty_buffer).unwrap().is_initialized);
+  }
+
+  #[test]
+  fn test_feed_should_update() {
+    let feed = create_sample_feed();
+    // within the threshold and before the heartbeat
+    assert!(!feed.should_update(10_050, 1_050));
+    assert!(!feed.should_update(9_950, 1_050));
+    // deviation above 50 bps
+    assert!(feed.should_update(10_051, 1_050));
+    assert!(feed.should_update(9_949, 1_050));
+    // heartbeat elapsed
+    assert!(feed.should_update(10_000, 1_100));
+
+    let mut new_feed = create_sample_feed();
+    new_feed.round = 0;
+    assert!(new_feed.should_update(10_000, 1_000));
+  }
+
+  #[test]
+  fn test_feed_trips_breaker() {
+    let feed = create_sample_feed();
+    // moves up to 10% are fine
+    assert!(!feed.trips_breaker(11_000));
+    assert!(!feed.trips_breaker(9_000));
+    assert!(feed.trips_breaker(11_001));
+    assert!(feed.trips_breaker(8_999));
+
+    let new_feed = Feed { round: 0, ..create_sample_feed() };
+    assert!(!new_feed.trips_breaker(20_000));
+    let disabled_feed = Feed { max_change_bps: 0, ..create_sample_feed() };
+    assert!(!disabled_feed.trips_breaker(20_000));
+  }
+
+  #[test]
+  fn test_read_feed() {
+    let oracle_program_id = Pubkey::new_unique();
+    let feed_id = Pubkey::new_unique();
+    let clock_id = sysvar::clock::id();
+    let sysvar_owner = sysvar::id();
+    let mut feed_lamports = 0;
+    let mut clock_lamports = 0;
+    let mut feed_data = vec![0u8; Feed::LEN];
+    Feed::pack(create_sample_feed(), &mut feed_data).unwrap();
+    let mut clock_data = vec![0u8; Clock::size_of()];
+    let clock = Clock { slot: 1_010, ..Clock::default() };
+    let mut clock_info = AccountInfo::new(&clock_id, false, false, &mut clock_lamports, &mut clock_data, &sysvar_owner, false, Epoch::default());
+    clock.to_account_info(&mut clock_info).unwrap();
+    let feed_info = AccountInfo::new(&feed_id, false, false, &mut feed_lamports, &mut feed_data, &oracle_program_id, false, Epoch::default());
+
+    let feed_value = read_feed(&oracle_program_id, &feed_info, &clock_info, 10).unwrap();
+    assert_eq!(feed_value, FeedValue {
+      value: 10_000,
+      round: 1,
+      last_update_slot: 1_000,
+    });
+
+    assert_eq!(
+      read_feed(&oracle_program_id, &feed_info, &clock_info, 9),
+      Err(OracleError::StaleValue.into())
+    );
+    assert_eq!(
+      read_feed(&Pubkey::new_unique(), &feed_info, &clock_info, 10),
+      Err(ProgramError::IncorrectProgramId)
+    );
+
+    let tripped_feed = Feed { is_tripped: true, ..create_sample_feed() };
+    Feed::pack(tripped_feed, &mut feed_info.data.borrow_mut()).unwrap();
+    assert_eq!(
+      read_feed(&oracle_program_id, &feed_info, &clock_info, 10),
+      Err(OracleError::FeedTripped.into())
+    );
+
+    let mut never_updated_feed = create_sample_feed();
+    never_updated_feed.round = 0;
+    Feed::pack(never_updated_feed, &mut feed_info.data.borrow_mut()).unwrap();
+    assert_eq!(
+      read_feed(&oracle_program_id, &feed_info, &clock_info, 10),
+      Err(OracleError::StaleValue.into())
+    );
+
+    feed_info.data.borrow_mut().copy_from_slice(&[0u8; Feed::LEN]);
+    assert_eq!(
+      read_feed(&oracle_program_id, &feed_info, &clock_info, 10),
+      Err(ProgramError::UninitializedAccount)
+    );
+  }
+}
@@ -3,12 +3,19 @@ use solana_program::{
   program_error::ProgramError,
   pubkey::Pubkey,
   program_pack::{ Pack, Sealed },
+  system_program,
+  sysvar,
 };
 use arrayref::{ array_ref, array_refs, array_mut_ref, mut_array_refs };
 use crate::{
-  oracle_account::OracleAccount,
-  request::Request,
-  response::Response,
+  PUBLIC_KEY_LEN,
+  fee_schedule::FeeSchedule,
+  job_spec::{ find_job_address, JobParams, JOB_NAME_LEN, JOB_PARAM_LEN, MAX_JOB_PARAMS },
+  node::find_node_address,
+  oracle_account::{ find_oracle_address, OracleSecret, OracleSecrets, MAX_ORACLE_SECRETS, ORACLE_SEED_LEN },
+  request::{ Request, RequestIndex, ResponseStatus, Task, TASK_ARRAY_SIZE },
+  response::{ Response, ResponseData, RESPONSE_DATA_LEN },
+  token::{ find_vault_authority, spl_token },
 };
 
 #[repr(C, u16)]
@@ -16,94 +23,1876 @@ use crate::{
 pub enum OracleInstruction {
   /**
    * 0. [writable] the oracle to create request for
+   * 1. [writable, signer] the payer of the Oracle's request fee
+   * 2. the System program
+   * 3. the Clock sysvar
+   *
+   * Oracles that charge fees in an SPL Token also take
+   * 4. [writable] the payer's token account for the fee mint
+   * 5. [writable] the Oracle's fee vault
+   * 6. the SPL Token program
    */
   CreateRequest {
     // The request to be made by the oracle
     request: Request
   },
   /**
+   * Answer a Request. Requests with a dispute window only record the
+   * answer as a proposal, delivered by FinalizeResponse or ResolveDispute.
+   *
    * 0. [writable] The Account id for the oracle data
    * 1. The program id that should receive the callback
+   * 2. [writable, signer] the Oracle's node authority, receives the Request fee and tip
+   * 3. the Clock sysvar
+   * 4. the node account of the node authority, registered with the minimum stake
+   *    when the Oracle requires one
+   *
+   * Oracles that charge fees in an SPL Token also take
+   * 5. [writable] the Oracle's fee vault
+   * 6. [writable] the node's token account for the fee mint
+   * 7. the vault authority program address
+   * 8. the SPL Token program
+   *
+   * Pending Requests running the same Tasks get the same answer when the
+   * program receiving their callback follows, after any token accounts
+   * 5+. the program receiving the callback of an identical Request
    */
-  HandleResponse(Response)
+  HandleResponse(Response),
+  /**
+   * 0. [writable] the uninitialized Feed account
+   * 1. [signer] the node authority allowed to update the Feed
+   */
+  InitializeFeed {
+    // The tasks the node runs to produce each new value
+    tasks: [Task; TASK_ARRAY_SIZE],
+    // Minimum move from the stored value, in bps, that triggers an update
+    deviation_threshold_bps: u16,
+    // Slots after which an update is accepted regardless of deviation
+    heartbeat_slots: u64,
+    // Largest move between consecutive values, in bps, before the circuit
+    // breaker trips. 0 disables the breaker
+    max_change_bps: u16,
+  },
+  /**
+   * A value moving more than the Feed's `max_change_bps` is not stored and
+   * trips the circuit breaker instead
+   *
+   * 0. [writable] the Feed account
+   * 1. [signer] the Feed's node authority
+   * 2. the Clock sysvar
+   */
+  UpdateFeed {
+    value: u32
+  },
+  /**
+   * 0. [writable] the uninitialized Oracle account
+   * 1. [signer] the admin of the Oracle
+   * 2. the fee vault, a token account for `fee_mint` owned by the vault
+   *    authority. Only when `fee_mint` is set
+   */
+  InitializeOracle {
+    // The node that answers Requests and collects their fees
+    node_authority: Pubkey,
+    // Lamports, or tokens of `fee_mint`, charged for every Request
+    request_fee: u64,
+    // Slots after which an unanswered Request may be cancelled
+    request_expiry_slots: u64,
+    // SPL Token mint fees are paid in, the default Pubkey for lamports
+    fee_mint: Pubkey,
+  },
+  /**
+   * Refund an expired Request's fee and remove it from the queue
+   *
+   * 0. [writable] the Oracle account
+   * 1. [writable, signer] the requester that paid for the Request
+   * 2. the Clock sysvar
+   *
+   * Oracles that charge fees in an SPL Token also take
+   * 3. [writable] the Oracle's fee vault
+   * 4. [writable] the requester's token account for the fee mint
+   * 5. the vault authority program address
+   * 6. the SPL Token program
+   */
+  CancelRequest {
+    request_queue_index: RequestIndex
+  },
+  /**
+   * Replace the per Task prices charged on top of the base request fee
+   *
+   * 0. [writable] the Oracle account
+   * 1. [signer] the admin of the Oracle
+   */
+  SetFeeSchedule {
+    fee_schedule: FeeSchedule
+  },
+  /**
+   * Register an operator by locking `stake` lamports in its node account
+   *
+   * 0. the Oracle account
+   * 1. [writable] the node account, the program address from `find_node_address`
+   * 2. [writable, signer] the node authority, pays the stake and rent
+   * 3. the System program
+   * 4. the Rent sysvar
+   */
+  RegisterNode {
+    stake: u64
+  },
+  /**
+   * Stop operating and start the unstake cooldown. Fails while the node is
+   * the Oracle's node authority or proposed answers are pending.
+   *
+   * 0. the Oracle account
+   * 1. [writable] the node account
+   * 2. [signer] the node authority
+   * 3. the Clock sysvar
+   */
+  DeregisterNode,
+  /**
+   * Withdraw the stake and close the node account once the cooldown elapsed,
+   * under the same conditions as DeregisterNode. Nodes of a closed Oracle
+   * may withdraw right away.
+   *
+   * 0. the Oracle account
+   * 1. [writable] the node account
+   * 2. [writable, signer] the node authority, receives the stake
+   * 3. the Clock sysvar
+   */
+  Unstake,
+  /**
+   * Take up to `amount` lamports of a node's stake for a wrong or missing answer
+   *
+   * 0. the Oracle account
+   * 1. [writable] the node account
+   * 2. [signer] the admin of the Oracle
+   * 3. [writable] the account receiving the slashed stake
+   */
+  SlashNode {
+    amount: u64
+  },
+  /**
+   * Update the staking and dispute parameters
+   *
+   * 0. [writable] the Oracle account
+   * 1. [signer] the admin of the Oracle
+   */
+  SetStakeConfig {
+    // Lamports a node must lock to register
+    min_stake: u64,
+    // Slots between deregistering and unstaking
+    unstake_cooldown_slots: u64,
+    // Lamports posted to dispute a proposed answer
+    dispute_bond: u64,
+  },
+  /**
+   * Challenge a proposed answer within its dispute window by posting the
+   * Oracle's dispute bond
+   *
+   * 0. [writable] the Oracle account
+   * 1. [writable, signer] the disputer, pays the bond
+   * 2. the System program
+   * 3. the Clock sysvar
+   */
+  Dispute {
+    request_queue_index: RequestIndex
+  },
+  /**
+   * Deliver an undisputed answer once its dispute window closed. Anyone may
+   * finalize, the Request fee is paid to the node authority.
+   *
+   * 0. [writable] the Oracle account
+   * 1. The program id that should receive the callback
+   * 2. [writable] the Oracle's node authority
+   * 3. the Clock sysvar
+   *
+   * Oracles that charge fees in an SPL Token also take the accounts listed
+   * for HandleResponse
+   */
+  FinalizeResponse {
+    request_queue_index: RequestIndex
+  },
+  /**
+   * Settle a dispute. When `upheld` the disputer gets the bond back plus the
+   * node's slashed stake, the requester is refunded and `data` is delivered
+   * instead of the proposed answer. Nothing is slashed if the node account
+   * was already closed. Otherwise the node authority takes the bond and the
+   * fee and the proposed answer is delivered.
+   *
+   * 0. [writable] the Oracle account
+   * 1. The program id that should receive the callback
+   * 2. [signer] the admin of the Oracle
+   * 3. [writable] the Oracle's node authority
+   * 4. [writable] the disputer
+   * 5. [writable] the requester
+   * 6. [writable] the node account of the node authority
+   *
+   * Oracles that charge fees in an SPL Token also take the accounts listed
+   * for HandleResponse, paying the requester's token account when `upheld`
+   * and the node authority's otherwise
+   */
+  ResolveDispute {
+    request_queue_index: RequestIndex,
+    upheld: bool,
+    // The correct answer, only used when `upheld`
+    data: ResponseData,
+  },
+  /**
+   * Update the base request fee, the expiry and which Tasks Requests may run
+   *
+   * 0. [writable] the Oracle account
+   * 1. [signer] the admin of the Oracle
+   */
+  SetConfig {
+    request_fee: u64,
+    request_expiry_slots: u64,
+    // Longest url an HTTP Task may request, at most `Task::MAX_URL_LEN`.
+    // HttpGet urls never exceed the 34 bytes of GetParams.
+    max_url_len: u8,
+    // Bitmask of the `Task::kind`s Requests may run
+    allowed_task_kinds: u8,
+    // Number of node answers required to settle a Request, must be 1 until
+    // answers are aggregated
+    quorum: u8,
+  },
+  /**
+   * Replace the node that answers Requests
+   *
+   * 0. [writable] the Oracle account
+   * 1. [signer] the admin of the Oracle
+   */
+  SetNodeAuthority {
+    node_authority: Pubkey
+  },
+  /**
+   * Propose a new admin, who takes over by signing AcceptAdmin
+   *
+   * 0. [writable] the Oracle account
+   * 1. [signer] the admin of the Oracle
+   */
+  TransferAdmin {
+    new_admin: Pubkey
+  },
+  /**
+   * 0. [writable] the Oracle account
+   * 1. [signer] the admin proposed by TransferAdmin
+   */
+  AcceptAdmin,
+  /**
+   * Stop the Oracle taking Requests and Responses
+   *
+   * 0. [writable] the Oracle account
+   * 1. [signer] the admin of the Oracle
+   */
+  Pause,
+  /**
+   * 0. [writable] the Oracle account
+   * 1. [signer] the admin of the Oracle
+   */
+  Unpause,
+  /**
+   * Clear a tripped circuit breaker and store `value`, checked off-chain by
+   * the authority, as the Feed's new value
+   *
+   * 0. [writable] the Feed account
+   * 1. [signer] the Feed's node authority
+   * 2. the Clock sysvar
+   */
+  ResetFeed {
+    value: u32
+  },
+  /**
+   * Close the Oracle account and reclaim its lamports. Refuses while
+   * Requests are pending unless `force` is set, in which case every pending
+   * Request is refunded.
+   *
+   * 0. [writable] the Oracle account
+   * 1. [signer] the admin of the Oracle
+   * 2. [writable] the account receiving the Oracle's lamports
+   *
+   * Followed by, for every pending Request in queue order
+   * 0. [writable] the requester, receives the fee
+   * 1..4. Oracles that charge fees in an SPL Token also take the payout
+   *    accounts listed for CancelRequest
+   * then [writable] the disputer, only for a disputed Request
+   */
+  CloseOracle {
+    force: bool
+  },
+  /**
+   * Create an Oracle account with room for `capacity` Requests at the
+   * program address from `find_oracle_address` and initialize it like
+   * InitializeOracle
+   *
+   * 0. [writable] the Oracle account, the program address of the admin and `seed`
+   * 1. [writable, signer] the admin of the Oracle, pays the rent
+   * 2. the System program
+   * 3. the Rent sysvar
+   * 4. the fee vault, only when `fee_mint` is set
+   */
+  CreateOracle {
+    seed: [u8; ORACLE_SEED_LEN],
+    node_authority: Pubkey,
+    request_fee: u64,
+    request_expiry_slots: u64,
+    fee_mint: Pubkey,
+    capacity: u16,
+  },
+  /**
+   * Limit how many queue slots a single requester may hold at once, so
+   * one client cannot fill the shared queue. 0 removes the limit
+   *
+   * 0. [writable] the Oracle account
+   * 1. [signer] the admin of the Oracle
+   */
+  SetRequesterLimit {
+    max_requests_per_requester: u8,
+  },
+  /**
+   * Change how many Request slots the Oracle uses, between the last slot
+   * holding a Request and the slots the account's data length fits
+   *
+   * 0. [writable] the Oracle account
+   * 1. [signer] the admin of the Oracle
+   */
+  ResizeOracle {
+    capacity: u16,
+  },
+  /**
+   * Answer several Requests at once, one callback per Response. When
+   * `atomic` is set any Response that can not be answered fails the whole
+   * instruction, otherwise it is skipped and the others still go through.
+   * A failing callback always fails the instruction.
+   *
+   * 0. [writable] The Account id for the oracle data
+   * 1. [writable, signer] the Oracle's node authority, receives the Request fees and tips
+   * 2. the Clock sysvar
+   * 3. the node account of the node authority, registered with the minimum stake
+   *    when the Oracle requires one
+   * 4. the program receiving the callback of each of the N Responses, in order
+   *
+   * Oracles that charge fees in an SPL Token also take
+   * 4+N. [writable] the Oracle's fee vault
+   * 5+N. [writable] the node's token account for the fee mint
+   * 6+N. the vault authority program address
+   * 7+N. the SPL Token program
+   */
+  HandleResponses {
+    responses: Vec<Response>,
+    atomic: bool,
+  },
+  /**
+   * Create several Requests at once, paying for all of them. Either every
+   * Request is queued or none is, and their ids are logged in order.
+   * Takes the same accounts as CreateRequest.
+   */
+  CreateRequests {
+    requests: Vec<Request>,
+  },
+  /**
+   * Answer a Request from the Oracle's answer cache, without the node
+   * running its Tasks, when the cache holds an answer the requester accepts.
+   * Takes the same accounts as HandleResponse.
+   */
+  ServeCachedResponse {
+    request_queue_index: RequestIndex,
+  },
+  /**
+   * Store a named task pipeline Requests can refer to by `job_id`. Its
+   * urls and json paths may hold `{param}` placeholders for the declared
+   * parameters, see `JobSpec`.
+   *
+   * 0. the Oracle account
+   * 1. [writable] the JobSpec account, the program address from `find_job_address`
+   * 2. [writable, signer] the admin of the Oracle, pays the rent
+   * 3. the System program
+   * 4. the Rent sysvar
+   */
+  RegisterJob {
+    // Any id but 0
+    job_id: u64,
+    name: [u8; JOB_NAME_LEN],
+    tasks: [Task; TASK_ARRAY_SIZE],
+    // Zero padded names of the template parameters
+    params: JobParams,
+  },
+  /**
+   * Create a Request running the Tasks of a registered JobSpec, with `args`
+   * substituted for its template parameters. Takes the
+   * accounts of CreateRequest with the JobSpec account right after the
+   * Clock sysvar
+   * 4. the JobSpec account
+   */
+  CreateJobRequest {
+    job_id: u64,
+    // The program receiving the answer
+    call_back_program: Pubkey,
+    fee: u64,
+    tip: u64,
+    dispute_window_slots: u64,
+    max_answer_age_slots: u64,
+    // Zero padded argument of each parameter, in the JobSpec's order
+    args: JobParams,
+  },
+  /**
+   * Advertise the secrets the Oracle's node holds, which Tasks refer to
+   * with a `secret:<name>` header value. Each secret may only be sent to
+   * its host, Requests referring to any other secret or sending one to
+   * another host are rejected.
+   *
+   * 0. [writable] the Oracle account
+   * 1. [signer] the admin of the Oracle
+   */
+  SetSecrets {
+    // Secrets with the host each is sent to, all zero for an unused entry
+    secrets: OracleSecrets,
+  },
+}
+
+// Most Responses a HandleResponses instruction carries
+pub const MAX_BATCHED_RESPONSES: usize = 16;
+// Most Requests a CreateRequests instruction carries. A transaction sent
+// directly only fits 2, more can be created through cross program invocation
+pub const MAX_BATCHED_REQUESTS: usize = 8;
+impl Sealed for OracleInstruction {}
+impl Pack for OracleInstruction {
+  // Request is the largest instruction payload
+  const LEN: usize = Request::LEN + 2;
+
+  fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+    let src = array_ref![src, 0, OracleInstruction::LEN];
+    let (tag, _) = array_refs![src, 2, Request::LEN];
+    OracleInstruction::decode(*tag, src)
+  }
+
+  // CreateRequests can outgrow LEN, `dst` must hold `packed_len` bytes
+  fn pack_into_slice(&self, dst: &mut [u8]) {
+    let (
+      tag_dst,
+      data_dest,
+    ) = dst.split_at_mut(2);
+    self.encode(tag_dst, data_dest)
+  }
+}
+
+impl OracleInstruction {
+  /// Bytes needed to pack the instruction, at least `LEN`
+  pub fn packed_len(&self) -> usize {
+    match self {
+      OracleInstruction::CreateRequests { requests } =>
+        OracleInstruction::LEN.max(2 + 1 + requests.len() * Request::LEN),
+      _ => OracleInstruction::LEN,
+    }
+  }
+
+  fn decode(tag: [u8; 2], data: &[u8]) -> Result<Self, ProgramError> {
+    match u16::from_le_bytes(tag) {
+      0 => { 
+        let ix_data = array_ref![data, 2, Request::LEN];
+        Ok(OracleInstruction::CreateRequest {
+            request: Request::unpack_from_slice(ix_data)?
+        })
+      },
+      1 => {
+        let ix_data = array_ref![data, 2, Response::LEN];
+        Ok(OracleInstruction::HandleResponse(
+          Response::unpack_from_slice(ix_data)?
+        ))
+      },
+      2 => {
+        let ix_data = array_ref![data, 2, Task::LEN * TASK_ARRAY_SIZE + 2 + 8 + 2];
+        let (task_1, task_2, task_3, deviation_threshold_bps, heartbeat_slots, max_change_bps) =
+          array_refs![ix_data, Task::LEN, Task::LEN, Task::LEN, 2, 8, 2];
+        Ok(OracleInstruction::InitializeFeed {
+          tasks: [
+            Task::unpack_from_slice(task_1)?,
+            Task::unpack_from_slice(task_2)?,
+            Task::unpack_from_slice(task_3)?,
+          ],
+          deviation_threshold_bps: u16::from_le_bytes(*deviation_threshold_bps),
+          heartbeat_slots: u64::from_le_bytes(*heartbeat_slots),
+          max_change_bps: u16::from_le_bytes(*max_change_bps),
+        })
+      },
+      3 => {
+        let value = array_ref![data, 2, 4];
+        Ok(OracleInstruction::UpdateFeed {
+          value: u32::from_le_bytes(*value)
+        })
+      },
+      4 => {
+        let ix_data = array_ref![data, 2, PUBLIC_KEY_LEN + 8 + 8 + PUBLIC_KEY_LEN];
+        let (node_authority, request_fee, request_expiry_slots, fee_mint) =
+          array_refs![ix_data, PUBLIC_KEY_LEN, 8, 8, PUBLIC_KEY_LEN];
+        Ok(OracleInstruction::InitializeOracle {
+          node_authority: Pubkey::new(node_authority),
+          request_fee: u64::from_le_bytes(*request_fee),
+          request_expiry_slots: u64::from_le_bytes(*request_expiry_slots),
+          fee_mint: Pubkey::new(fee_mint),
+        })
+      },
+      5 => {
+        let request_queue_index = array_ref![data, 2, 1];
+        Ok(OracleInstruction::CancelRequest {
+          request_queue_index: u8::from_le_bytes(*request_queue_index)
+        })
+      },
+      6 => {
+        let ix_data = array_ref![data, 2, FeeSchedule::LEN];
+        Ok(OracleInstruction::SetFeeSchedule {
+          fee_schedule: FeeSchedule::unpack_from_slice(ix_data)?
+        })
+      },
+      7 => {
+        let stake = array_ref![data, 2, 8];
+        Ok(OracleInstruction::RegisterNode {
+          stake: u64::from_le_bytes(*stake)
+        })
+      },
+      8 => Ok(OracleInstruction::DeregisterNode),
+      9 => Ok(OracleInstruction::Unstake),
+      10 => {
+        let amount = array_ref![data, 2, 8];
+        Ok(OracleInstruction::SlashNode {
+          amount: u64::from_le_bytes(*amount)
+        })
+      },
+      11 => {
+        let ix_data = array_ref![data, 2, 8 + 8 + 8];
+        let (min_stake, unstake_cooldown_slots, dispute_bond) = array_refs![ix_data, 8, 8, 8];
+        Ok(OracleInstruction::SetStakeConfig {
+          min_stake: u64::from_le_bytes(*min_stake),
+          unstake_cooldown_slots: u64::from_le_bytes(*unstake_cooldown_slots),
+          dispute_bond: u64::from_le_bytes(*dispute_bond),
+        })
+      },
+      12 => {
+        let request_queue_index = array_ref![data, 2, 1];
+        Ok(OracleInstruction::Dispute {
+          request_queue_index: u8::from_le_bytes(*request_queue_index)
+        })
+      },
+      13 => {
+        let request_queue_index = array_ref![data, 2, 1];
+        Ok(OracleInstruction::FinalizeResponse {
+          request_queue_index: u8::from_le_bytes(*request_queue_index)
+        })
+      },
+      14 => {
+        let ix_data = array_ref![data, 2, 1 + 1 + RESPONSE_DATA_LEN];
+        let (request_queue_index, upheld, response_data) = array_refs![ix_data, 1, 1, RESPONSE_DATA_LEN];
+        let upheld = match upheld {
+          [0] => false,
+          [1] => true,
+          _ => return Err(ProgramError::InvalidInstructionData),
+        };
+        Ok(OracleInstruction::ResolveDispute {
+          request_queue_index: u8::from_le_bytes(*request_queue_index),
+          upheld,
+          data: *response_data,
+        })
+      },
+      15 => {
+        let ix_data = array_ref![data, 2, 8 + 8 + 1 + 1 + 1];
+        let (request_fee, request_expiry_slots, max_url_len, allowed_task_kinds, quorum) =
+          array_refs![ix_data, 8, 8, 1, 1, 1];
+        Ok(OracleInstruction::SetConfig {
+          request_fee: u64::from_le_bytes(*request_fee),
+          request_expiry_slots: u64::from_le_bytes(*request_expiry_slots),
+          max_url_len: max_url_len[0],
+          allowed_task_kinds: allowed_task_kinds[0],
+          quorum: quorum[0],
+        })
+      },
+      16 => {
+        let node_authority = array_ref![data, 2, PUBLIC_KEY_LEN];
+        Ok(OracleInstruction::SetNodeAuthority {
+          node_authority: Pubkey::new(node_authority)
+        })
+      },
+      17 => {
+        let new_admin = array_ref![data, 2, PUBLIC_KEY_LEN];
+        Ok(OracleInstruction::TransferAdmin {
+          new_admin: Pubkey::new(new_admin)
+        })
+      },
+      18 => Ok(OracleInstruction::AcceptAdmin),
+      19 => Ok(OracleInstruction::Pause),
+      20 => Ok(OracleInstruction::Unpause),
+      21 => {
+        let value = array_ref![data, 2, 4];
+        Ok(OracleInstruction::ResetFeed {
+          value: u32::from_le_bytes(*value)
+        })
+      },
+      22 => {
+        let force = match array_ref![data, 2, 1] {
+          [0] => false,
+          [1] => true,
+          _ => return Err(ProgramError::InvalidInstructionData),
+        };
+        Ok(OracleInstruction::CloseOracle { force })
+      },
+      23 => {
+        let ix_data = array_ref![data, 2, ORACLE_SEED_LEN + PUBLIC_KEY_LEN + 8 + 8 + PUBLIC_KEY_LEN + 2];
+        let (seed, node_authority, request_fee, request_expiry_slots, fee_mint, capacity) =
+          array_refs![ix_data, ORACLE_SEED_LEN, PUBLIC_KEY_LEN, 8, 8, PUBLIC_KEY_LEN, 2];
+        Ok(OracleInstruction::CreateOracle {
+          seed: *seed,
+          node_authority: Pubkey::new(node_authority),
+          request_fee: u64::from_le_bytes(*request_fee),
+          request_expiry_slots: u64::from_le_bytes(*request_expiry_slots),
+          fee_mint: Pubkey::new(fee_mint),
+          capacity: u16::from_le_bytes(*capacity),
+        })
+      },
+      24 => {
+        let max_requests_per_requester = array_ref![data, 2, 1];
+        Ok(OracleInstruction::SetRequesterLimit {
+          max_requests_per_requester: max_requests_per_requester[0]
+        })
+      },
+      25 => {
+        let capacity = array_ref![data, 2, 2];
+        Ok(OracleInstruction::ResizeOracle {
+          capacity: u16::from_le_bytes(*capacity)
+        })
+      },
+      26 => {
+        let atomic = match data.get(2) {
+          Some(0) => false,
+          Some(1) => true,
+          _ => return Err(ProgramError::InvalidInstructionData),
+        };
+        let count = *data.get(3).ok_or(ProgramError::InvalidInstructionData)? as usize;
+        if count > MAX_BATCHED_RESPONSES || data.len() < 4 + count * Response::LEN {
+          return Err(ProgramError::InvalidInstructionData);
+        }
+        let responses = data[4..4 + count * Response::LEN]
+          .chunks_exact(Response::LEN)
+          .map(Response::unpack_from_slice)
+          .collect::<Result<_, _>>()?;
+        Ok(OracleInstruction::HandleResponses { responses, atomic })
+      },
+      27 => {
+        let count = *data.get(2).ok_or(ProgramError::InvalidInstructionData)? as usize;
+        if count == 0 || count > MAX_BATCHED_REQUESTS || data.len() < 3 + count * Request::LEN {
+          return Err(ProgramError::InvalidInstructionData);
+        }
+        let requests = data[3..3 + count * Request::LEN]
+          .chunks_exact(Request::LEN)
+          .map(Request::unpack_from_slice)
+          .collect::<Result<_, _>>()?;
+        Ok(OracleInstruction::CreateRequests { requests })
+      },
+      28 => {
+        let request_queue_index = array_ref![data, 2, 1];
+        Ok(OracleInstruction::ServeCachedResponse {
+          request_queue_index: request_queue_index[0]
+        })
+      },
+      29 => {
+        let ix_data = array_ref![data, 2, 8 + JOB_NAME_LEN + Task::LEN * TASK_ARRAY_SIZE + JOB_PARAM_LEN * MAX_JOB_PARAMS];
+        let (job_id, name, task_1, task_2, task_3, param_1, param_2) =
+          array_refs![ix_data, 8, JOB_NAME_LEN, Task::LEN, Task::LEN, Task::LEN, JOB_PARAM_LEN, JOB_PARAM_LEN];
+        Ok(OracleInstruction::RegisterJob {
+          job_id: u64::from_le_bytes(*job_id),
+          name: *name,
+          tasks: [
+            Task::unpack_from_slice(task_1)?,
+            Task::unpack_from_slice(task_2)?,
+            Task::unpack_from_slice(task_3)?,
+          ],
+          params: [*param_1, *param_2],
+        })
+      },
+      30 => {
+        let ix_data = array_ref![data, 2, 8 + PUBLIC_KEY_LEN + 8 + 8 + 8 + 8 + JOB_PARAM_LEN * MAX_JOB_PARAMS];
+        let (job_id, call_back_program, fee, tip, dispute_window_slots, max_answer_age_slots, arg_1, arg_2) =
+          array_refs![ix_data, 8, PUBLIC_KEY_LEN, 8, 8, 8, 8, JOB_PARAM_LEN, JOB_PARAM_LEN];
+        Ok(OracleInstruction::CreateJobRequest {
+          job_id: u64::from_le_bytes(*job_id),
+          call_back_program: Pubkey::new(call_back_program),
+          fee: u64::from_le_bytes(*fee),
+          tip: u64::from_le_bytes(*tip),
+          dispute_window_slots: u64::from_le_bytes(*dispute_window_slots),
+          max_answer_age_slots: u64::from_le_bytes(*max_answer_age_slots),
+          args: [*arg_1, *arg_2],
+        })
+      },
+      31 => {
+        let secrets_src = array_ref![data, 2, OracleSecret::LEN * MAX_ORACLE_SECRETS];
+        let mut secrets = OracleSecrets::default();
+        for (i, secret) in secrets.iter_mut().enumerate() {
+          *secret = OracleSecret::unpack_from_slice(array_ref![secrets_src, i * OracleSecret::LEN, OracleSecret::LEN])?;
+        }
+        Ok(OracleInstruction::SetSecrets { secrets })
+      },
+      _ => Err(ProgramError::InvalidInstructionData),
+    }
+  }
+
+  fn encode(&self, kind: &mut [u8], data:&mut [u8])  {
+    match self {
+      OracleInstruction::CreateRequest { request } => {
+        let tag: u16 = 0;
+        kind.copy_from_slice(&tag.to_le_bytes()[0..2]);
+        request.pack_into_slice(data);
+      },
+      OracleInstruction::HandleResponse(response) => {
+        let tag: u16 = 1;
+        kind.copy_from_slice(&tag.to_le_bytes()[0..2]);
+        response.pack_into_slice(data);
+      },
+      OracleInstruction::InitializeFeed { tasks, deviation_threshold_bps, heartbeat_slots, max_change_bps } => {
+        let tag: u16 = 2;
+        kind.copy_from_slice(&tag.to_le_bytes()[0..2]);
+        let data = array_mut_ref![data, 0, Task::LEN * TASK_ARRAY_SIZE + 2 + 8 + 2];
+        let (task_1, task_2, task_3, deviation_threshold_bps_dst, heartbeat_slots_dst, max_change_bps_dst) =
+          mut_array_refs![data, Task::LEN, Task::LEN, Task::LEN, 2, 8, 2];
+        tasks[0].pack_into_slice(task_1);
+        tasks[1].pack_into_slice(task_2);
+        tasks[2].pack_into_slice(task_3);
+        *deviation_threshold_bps_dst = deviation_threshold_bps.to_le_bytes();
+        *heartbeat_slots_dst = heartbeat_slots.to_le_bytes();
+        *max_change_bps_dst = max_change_bps.to_le_bytes();
+      },
+      OracleInstruction::UpdateFeed { value } => {
+        let tag: u16 = 3;
+        kind.copy_from_slice(&tag.to_le_bytes()[0..2]);
+        data[0..4].copy_from_slice(&value.to_le_bytes());
+      },
+      OracleInstruction::InitializeOracle { node_authority, request_fee, request_expiry_slots, fee_mint } => {
+        let tag: u16 = 4;
+        kind.copy_from_slice(&tag.to_le_bytes()[0..2]);
+        let data = array_mut_ref![data, 0, PUBLIC_KEY_LEN + 8 + 8 + PUBLIC_KEY_LEN];
+        let (node_authority_dst, request_fee_dst, request_expiry_slots_dst, fee_mint_dst) =
+          mut_array_refs![data, PUBLIC_KEY_LEN, 8, 8, PUBLIC_KEY_LEN];
+        *node_authority_dst = node_authority.to_bytes();
+        *request_fee_dst = request_fee.to_le_bytes();
+        *request_expiry_slots_dst = request_expiry_slots.to_le_bytes();
+        *fee_mint_dst = fee_mint.to_bytes();
+      },
+      OracleInstruction::CancelRequest { request_queue_index } => {
+        let tag: u16 = 5;
+        kind.copy_from_slice(&tag.to_le_bytes()[0..2]);
+        data[0] = *request_queue_index;
+      },
+      OracleInstruction::SetFeeSchedule { fee_schedule } => {
+        let tag: u16 = 6;
+        kind.copy_from_slice(&tag.to_le_bytes()[0..2]);
+        fee_schedule.pack_into_slice(&mut data[0..FeeSchedule::LEN]);
+      },
+      OracleInstruction::RegisterNode { stake } => {
+        let tag: u16 = 7;
+        kind.copy_from_slice(&tag.to_le_bytes()[0..2]);
+        data[0..8].copy_from_slice(&stake.to_le_bytes());
+      },
+      OracleInstruction::DeregisterNode => {
+        let tag: u16 = 8;
+        kind.copy_from_slice(&tag.to_le_bytes()[0..2]);
+      },
+      OracleInstruction::Unstake => {
+        let tag: u16 = 9;
+        kind.copy_from_slice(&tag.to_le_bytes()[0..2]);
+      },
+      OracleInstruction::SlashNode { amount } => {
+        let tag: u16 = 10;
+        kind.copy_from_slice(&tag.to_le_bytes()[0..2]);
+        data[0..8].copy_from_slice(&amount.to_le_bytes());
+      },
+      OracleInstruction::SetStakeConfig { min_stake, unstake_cooldown_slots, dispute_bond } => {
+        let tag: u16 = 11;
+        kind.copy_from_slice(&tag.to_le_bytes()[0..2]);
+        let data = array_mut_ref![data, 0, 8 + 8 + 8];
+        let (min_stake_dst, unstake_cooldown_slots_dst, dispute_bond_dst) = mut_array_refs![data, 8, 8, 8];
+        *min_stake_dst = min_stake.to_le_bytes();
+        *unstake_cooldown_slots_dst = unstake_cooldown_slots.to_le_bytes();
+        *dispute_bond_dst = dispute_bond.to_le_bytes();
+      },
+      OracleInstruction::Dispute { request_queue_index } => {
+        let tag: u16 = 12;
+        kind.copy_from_slice(&tag.to_le_bytes()[0..2]);
+        data[0] = *request_queue_index;
+      },
+      OracleInstruction::FinalizeResponse { request_queue_index } => {
+        let tag: u16 = 13;
+        kind.copy_from_slice(&tag.to_le_bytes()[0..2]);
+        data[0] = *request_queue_index;
+      },
+      OracleInstruction::ResolveDispute { request_queue_index, upheld, data: response_data } => {
+        let tag: u16 = 14;
+        kind.copy_from_slice(&tag.to_le_bytes()[0..2]);
+        let data = array_mut_ref![data, 0, 1 + 1 + RESPONSE_DATA_LEN];
+        let (request_queue_index_dst, upheld_dst, response_data_dst) = mut_array_refs![data, 1, 1, RESPONSE_DATA_LEN];
+        request_queue_index_dst[0] = *request_queue_index;
+        upheld_dst[0] = *upheld as u8;
+        *response_data_dst = *response_data;
+      },
+      OracleInstruction::SetConfig { request_fee, request_expiry_slots, max_url_len, allowed_task_kinds, quorum } => {
+        let tag: u16 = 15;
+        kind.copy_from_slice(&tag.to_le_bytes()[0..2]);
+        let data = array_mut_ref![data, 0, 8 + 8 + 1 + 1 + 1];
+        let (request_fee_dst, request_expiry_slots_dst, max_url_len_dst, allowed_task_kinds_dst, quorum_dst) =
+          mut_array_refs![data, 8, 8, 1, 1, 1];
+        *request_fee_dst = request_fee.to_le_bytes();
+        *request_expiry_slots_dst = request_expiry_slots.to_le_bytes();
+        max_url_len_dst[0] = *max_url_len;
+        allowed_task_kinds_dst[0] = *allowed_task_kinds;
+        quorum_dst[0] = *quorum;
+      },
+      OracleInstruction::SetNodeAuthority { node_authority } => {
+        let tag: u16 = 16;
+        kind.copy_from_slice(&tag.to_le_bytes()[0..2]);
+        data[0..PUBLIC_KEY_LEN].copy_from_slice(node_authority.as_ref());
+      },
+      OracleInstruction::TransferAdmin { new_admin } => {
+        let tag: u16 = 17;
+        kind.copy_from_slice(&tag.to_le_bytes()[0..2]);
+        data[0..PUBLIC_KEY_LEN].copy_from_slice(new_admin.as_ref());
+      },
+      OracleInstruction::AcceptAdmin => {
+        let tag: u16 = 18;
+        kind.copy_from_slice(&tag.to_le_bytes()[0..2]);
+      },
+      OracleInstruction::Pause => {
+        let tag: u16 = 19;
+        kind.copy_from_slice(&tag.to_le_bytes()[0..2]);
+      },
+      OracleInstruction::Unpause => {
+        let tag: u16 = 20;
+        kind.copy_from_slice(&tag.to_le_bytes()[0..2]);
+      },
+      OracleInstruction::ResetFeed { value } => {
+        let tag: u16 = 21;
+        kind.copy_from_slice(&tag.to_le_bytes()[0..2]);
+        data[0..4].copy_from_slice(&value.to_le_bytes());
+      },
+      OracleInstruction::CloseOracle { force } => {
+        let tag: u16 = 22;
+        kind.copy_from_slice(&tag.to_le_bytes()[0..2]);
+        data[0] = *force as u8;
+      },
+      OracleInstruction::CreateOracle { seed, node_authority, request_fee, request_expiry_slots, fee_mint, capacity } => {
+        let tag: u16 = 23;
+        kind.copy_from_slice(&tag.to_le_bytes()[0..2]);
+        let data = array_mut_ref![data, 0, ORACLE_SEED_LEN + PUBLIC_KEY_LEN + 8 + 8 + PUBLIC_KEY_LEN + 2];
+        let (seed_dst, node_authority_dst, request_fee_dst, request_expiry_slots_dst, fee_mint_dst, capacity_dst) =
+          mut_array_refs![data, ORACLE_SEED_LEN, PUBLIC_KEY_LEN, 8, 8, PUBLIC_KEY_LEN, 2];
+        *seed_dst = *seed;
+        *node_authority_dst = node_authority.to_bytes();
+        *request_fee_dst = request_fee.to_le_bytes();
+        *request_expiry_slots_dst = request_expiry_slots.to_le_bytes();
+        *fee_mint_dst = fee_mint.to_bytes();
+        *capacity_dst = capacity.to_le_bytes();
+      },
+      OracleInstruction::SetRequesterLimit { max_requests_per_requester } => {
+        let tag: u16 = 24;
+        kind.copy_from_slice(&tag.to_le_bytes()[0..2]);
+        data[0] = *max_requests_per_requester;
+      },
+      OracleInstruction::ResizeOracle { capacity } => {
+        let tag: u16 = 25;
+        kind.copy_from_slice(&tag.to_le_bytes()[0..2]);
+        data[0..2].copy_from_slice(&capacity.to_le_bytes());
+      },
+      OracleInstruction::HandleResponses { responses, atomic } => {
+        let tag: u16 = 26;
+        kind.copy_from_slice(&tag.to_le_bytes()[0..2]);
+        data[0] = *atomic as u8;
+        data[1] = responses.len() as u8;
+        for (response, dst) in responses.iter().zip(data[2..].chunks_exact_mut(Response::LEN)) {
+          response.pack_into_slice(dst);
+        }
+      },
+      OracleInstruction::CreateRequests { requests } => {
+        let tag: u16 = 27;
+        kind.copy_from_slice(&tag.to_le_bytes()[0..2]);
+        data[0] = requests.len() as u8;
+        for (request, dst) in requests.iter().zip(data[1..].chunks_exact_mut(Request::LEN)) {
+          request.pack_into_slice(dst);
+        }
+      },
+      OracleInstruction::ServeCachedResponse { request_queue_index } => {
+        let tag: u16 = 28;
+        kind.copy_from_slice(&tag.to_le_bytes()[0..2]);
+        data[0] = *request_queue_index;
+      },
+      OracleInstruction::RegisterJob { job_id, name, tasks, params } => {
+        let tag: u16 = 29;
+        kind.copy_from_slice(&tag.to_le_bytes()[0..2]);
+        let data = array_mut_ref![data, 0, 8 + JOB_NAME_LEN + Task::LEN * TASK_ARRAY_SIZE + JOB_PARAM_LEN * MAX_JOB_PARAMS];
+        let (job_id_dst, name_dst, task_1, task_2, task_3, param_1, param_2) =
+          mut_array_refs![data, 8, JOB_NAME_LEN, Task::LEN, Task::LEN, Task::LEN, JOB_PARAM_LEN, JOB_PARAM_LEN];
+        *job_id_dst = job_id.to_le_bytes();
+        *name_dst = *name;
+        tasks[0].pack_into_slice(task_1);
+        tasks[1].pack_into_slice(task_2);
+        tasks[2].pack_into_slice(task_3);
+        *param_1 = params[0];
+        *param_2 = params[1];
+      },
+      OracleInstruction::CreateJobRequest {
+        job_id,
+        call_back_program,
+        fee,
+        tip,
+        dispute_window_slots,
+        max_answer_age_slots,
+        args,
+      } => {
+        let tag: u16 = 30;
+        kind.copy_from_slice(&tag.to_le_bytes()[0..2]);
+        let data = array_mut_ref![data, 0, 8 + PUBLIC_KEY_LEN + 8 + 8 + 8 + 8 + JOB_PARAM_LEN * MAX_JOB_PARAMS];
+        let (job_id_dst, call_back_program_dst, fee_dst, tip_dst, dispute_window_slots_dst, max_answer_age_slots_dst, arg_1, arg_2) =
+          mut_array_refs![data, 8, PUBLIC_KEY_LEN, 8, 8, 8, 8, JOB_PARAM_LEN, JOB_PARAM_LEN];
+        *job_id_dst = job_id.to_le_bytes();
+        *call_back_program_dst = call_back_program.to_bytes();
+        *fee_dst = fee.to_le_bytes();
+        *tip_dst = tip.to_le_bytes();
+        *dispute_window_slots_dst = dispute_window_slots.to_le_bytes();
+        *max_answer_age_slots_dst = max_answer_age_slots.to_le_bytes();
+        *arg_1 = args[0];
+        *arg_2 = args[1];
+      },
+      OracleInstruction::SetSecrets { secrets } => {
+        let tag: u16 = 31;
+        kind.copy_from_slice(&tag.to_le_bytes()[0..2]);
+        for (secret, dst) in secrets.iter().zip(data.chunks_exact_mut(OracleSecret::LEN)) {
+          secret.pack_into_slice(dst);
+        }
+      },
+      // TODO propogate error here?
+    }
+  }
+
+  pub fn unpack(instruction_data: &[u8]) -> Result<Self, ProgramError> {
+    // Missing the u32 that determines the insutrction data
+    if instruction_data.len() < 2 {
+      return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let tag = array_ref![instruction_data, 0, 2];
+
+    OracleInstruction::decode(*tag, instruction_data)
+  }
+}
+/// Generate the Instruction for CreateRequest.
+/// Used for clients and cross program invocation
+pub fn create_request(
+  oracle_program_id: &Pubkey,
+  oracle_id: &Pubkey,
+  payer: &Pubkey,
+  request: Request
+) -> Result<Instruction, ProgramError> {
+  let accounts = vec![
+    AccountMeta::new(*oracle_id, false),
+    AccountMeta::new(*payer, true),
+    AccountMeta::new_readonly(system_program::id(), false),
+    AccountMeta::new_readonly(sysvar::clock::id(), false),
+  ];
+  let mut data  = [0u8; OracleInstruction::LEN];
+  OracleInstruction::CreateRequest { request }.pack_into_slice(&mut data);
+  let data = data.to_vec();
+  Ok(Instruction {
+    program_id: *oracle_program_id,
+    accounts,
+    data,
+  })
+}
+
+/// Generate the Instruction for CreateRequest on an Oracle that charges
+/// its fee in an SPL Token
+pub fn create_request_with_token_fee(
+  oracle_program_id: &Pubkey,
+  oracle_id: &Pubkey,
+  payer: &Pubkey,
+  payer_token_account: &Pubkey,
+  fee_vault: &Pubkey,
+  request: Request
+) -> Result<Instruction, ProgramError> {
+  let mut instruction = create_request(oracle_program_id, oracle_id, payer, request)?;
+  instruction.accounts.extend(vec![
+    AccountMeta::new(*payer_token_account, false),
+    AccountMeta::new(*fee_vault, false),
+    AccountMeta::new_readonly(spl_token::id(), false),
+  ]);
+  Ok(instruction)
+}
+
+/// Generate the Instruction for RegisterJob.
+pub fn register_job(
+  oracle_program_id: &Pubkey,
+  oracle_id: &Pubkey,
+  admin: &Pubkey,
+  job_id: u64,
+  name: [u8; JOB_NAME_LEN],
+  tasks: [Task; TASK_ARRAY_SIZE],
+  params: JobParams,
+) -> Result<Instruction, ProgramError> {
+  let (job_address, _) = find_job_address(oracle_program_id, oracle_id, job_id);
+  let accounts = vec![
+    AccountMeta::new_readonly(*oracle_id, false),
+    AccountMeta::new(job_address, false),
+    AccountMeta::new(*admin, true),
+    AccountMeta::new_readonly(system_program::id(), false),
+    AccountMeta::new_readonly(sysvar::rent::id(), false),
+  ];
+  let mut data = [0u8; OracleInstruction::LEN];
+  OracleInstruction::RegisterJob { job_id, name, tasks, params }.pack_into_slice(&mut data);
+  let data = data.to_vec();
+  Ok(Instruction {
+    program_id: *oracle_program_id,
+    accounts,
+    data,
+  })
+}
+
+/// Generate the Instruction for CreateJobRequest.
+/// Used for clients asking for a registered JobSpec
+#[allow(clippy::too_many_arguments)]
+pub fn create_job_request(
+  oracle_program_id: &Pubkey,
+  oracle_id: &Pubkey,
+  payer: &Pubkey,
+  job_id: u64,
+  call_back_program: &Pubkey,
+  fee: u64,
+  tip: u64,
+  dispute_window_slots: u64,
+  max_answer_age_slots: u64,
+  args: JobParams,
+) -> Result<Instruction, ProgramError> {
+  let (job_address, _) = find_job_address(oracle_program_id, oracle_id, job_id);
+  let accounts = vec![
+    AccountMeta::new(*oracle_id, false),
+    AccountMeta::new(*payer, true),
+    AccountMeta::new_readonly(system_program::id(), false),
+    AccountMeta::new_readonly(sysvar::clock::id(), false),
+    AccountMeta::new_readonly(job_address, false),
+  ];
+  let mut data = [0u8; OracleInstruction::LEN];
+  OracleInstruction::CreateJobRequest {
+    job_id,
+    call_back_program: *call_back_program,
+    fee,
+    tip,
+    dispute_window_slots,
+    max_answer_age_slots,
+    args,
+  }.pack_into_slice(&mut data);
+  let data = data.to_vec();
+  Ok(Instruction {
+    program_id: *oracle_program_id,
+    accounts,
+    data,
+  })
+}
+
+/// Generate the Instruction for CreateRequests.
+/// Used for clients needing several answers at once
+pub fn create_requests(
+  oracle_program_id: &Pubkey,
+  oracle_id: &Pubkey,
+  payer: &Pubkey,
+  requests: Vec<Request>
+) -> Result<Instruction, ProgramError> {
+  if requests.is_empty() || requests.len() > MAX_BATCHED_REQUESTS {
+    return Err(ProgramError::InvalidArgument);
+  }
+  let accounts = vec![
+    AccountMeta::new(*oracle_id, false),
+    AccountMeta::new(*payer, true),
+    AccountMeta::new_readonly(system_program::id(), false),
+    AccountMeta::new_readonly(sysvar::clock::id(), false),
+  ];
+  let instruction = OracleInstruction::CreateRequests { requests };
+  let mut data = vec![0u8; instruction.packed_len()];
+  instruction.pack_into_slice(&mut data);
+  Ok(Instruction {
+    program_id: *oracle_program_id,
+    accounts,
+    data,
+  })
+}
+
+/// Generate the Instruction for CreateRequests on an Oracle that charges
+/// its fee in an SPL Token
+pub fn create_requests_with_token_fee(
+  oracle_program_id: &Pubkey,
+  oracle_id: &Pubkey,
+  payer: &Pubkey,
+  payer_token_account: &Pubkey,
+  fee_vault: &Pubkey,
+  requests: Vec<Request>
+) -> Result<Instruction, ProgramError> {
+  let mut instruction = create_requests(oracle_program_id, oracle_id, payer, requests)?;
+  instruction.accounts.extend(vec![
+    AccountMeta::new(*payer_token_account, false),
+    AccountMeta::new(*fee_vault, false),
+    AccountMeta::new_readonly(spl_token::id(), false),
+  ]);
+  Ok(instruction)
+}
+
+/// Generate the Instruction for HandleResponse.
+/// Used by the node to answer a Request
+pub fn handle_response(
+  oracle_program_id: &Pubkey,
+  oracle_id: &Pubkey,
+  call_back_program: &Pubkey,
+  node_authority: &Pubkey,
+  response: Response,
+) -> Result<Instruction, ProgramError> {
+  let (node_id, _) = find_node_address(oracle_program_id, oracle_id, node_authority);
+  let accounts = vec![
+    AccountMeta::new(*oracle_id, false),
+    AccountMeta::new_readonly(*call_back_program, false),
+    AccountMeta::new(*node_authority, true),
+    AccountMeta::new_readonly(sysvar::clock::id(), false),
+    AccountMeta::new_readonly(node_id, false),
+  ];
+  let mut data = [0u8; OracleInstruction::LEN];
+  OracleInstruction::HandleResponse(response).pack_into_slice(&mut data);
+  let data = data.to_vec();
+  Ok(Instruction {
+    program_id: *oracle_program_id,
+    accounts,
+    data,
+  })
+}
+
+/// Generate the Instruction for HandleResponse on an Oracle that charges
+/// its fee in an SPL Token
+pub fn handle_response_with_token_fee(
+  oracle_program_id: &Pubkey,
+  oracle_id: &Pubkey,
+  call_back_program: &Pubkey,
+  node_authority: &Pubkey,
+  fee_vault: &Pubkey,
+  node_token_account: &Pubkey,
+  response: Response,
+) -> Result<Instruction, ProgramError> {
+  let mut instruction = handle_response(oracle_program_id, oracle_id, call_back_program, node_authority, response)?;
+  instruction.accounts.extend(token_fee_payout_accounts(oracle_program_id, oracle_id, fee_vault, node_token_account));
+  Ok(instruction)
+}
+
+/// Generate the Instruction for HandleResponse that also answers the
+/// pending Requests for the same Tasks made by `duplicate_call_back_programs`
+pub fn handle_response_with_duplicates(
+  oracle_program_id: &Pubkey,
+  oracle_id: &Pubkey,
+  call_back_program: &Pubkey,
+  node_authority: &Pubkey,
+  response: Response,
+  duplicate_call_back_programs: &[Pubkey],
+) -> Result<Instruction, ProgramError> {
+  let mut instruction = handle_response(oracle_program_id, oracle_id, call_back_program, node_authority, response)?;
+  for program in duplicate_call_back_programs {
+    instruction.accounts.push(AccountMeta::new_readonly(*program, false));
+  }
+  Ok(instruction)
+}
+
+/// Generate the Instruction for ServeCachedResponse.
+/// Used by the node to answer a Request from the answer cache
+pub fn serve_cached_response(
+  oracle_program_id: &Pubkey,
+  oracle_id: &Pubkey,
+  call_back_program: &Pubkey,
+  node_authority: &Pubkey,
+  request_queue_index: RequestIndex,
+) -> Result<Instruction, ProgramError> {
+  let (node_id, _) = find_node_address(oracle_program_id, oracle_id, node_authority);
+  let accounts = vec![
+    AccountMeta::new(*oracle_id, false),
+    AccountMeta::new_readonly(*call_back_program, false),
+    AccountMeta::new(*node_authority, true),
+    AccountMeta::new_readonly(sysvar::clock::id(), false),
+    AccountMeta::new_readonly(node_id, false),
+  ];
+  let mut data = [0u8; OracleInstruction::LEN];
+  OracleInstruction::ServeCachedResponse { request_queue_index }.pack_into_slice(&mut data);
+  let data = data.to_vec();
+  Ok(Instruction {
+    program_id: *oracle_program_id,
+    accounts,
+    data,
+  })
+}
+
+/// Generate the Instruction for HandleResponses, each Response paired with
+/// the program receiving its callback
+pub fn handle_responses(
+  oracle_program_id: &Pubkey,
+  oracle_id: &Pubkey,
+  node_authority: &Pubkey,
+  responses: Vec<(Pubkey, Response)>,
+  atomic: bool,
+) -> Result<Instruction, ProgramError> {
+  if responses.len() > MAX_BATCHED_RESPONSES {
+    return Err(ProgramError::InvalidArgument);
+  }
+  let (node_id, _) = find_node_address(oracle_program_id, oracle_id, node_authority);
+  let mut accounts = vec![
+    AccountMeta::new(*oracle_id, false),
+    AccountMeta::new(*node_authority, true),
+    AccountMeta::new_readonly(sysvar::clock::id(), false),
+    AccountMeta::new_readonly(node_id, false),
+  ];
+  let mut batch = Vec::with_capacity(responses.len());
+  for (call_back_program, response) in responses {
+    accounts.push(AccountMeta::new_readonly(call_back_program, false));
+    batch.push(response);
+  }
+  let mut data = [0u8; OracleInstruction::LEN];
+  OracleInstruction::HandleResponses { responses: batch, atomic }.pack_into_slice(&mut data);
+  let data = data.to_vec();
+  Ok(Instruction {
+    program_id: *oracle_program_id,
+    accounts,
+    data,
+  })
+}
+
+/// Generate the Instruction for HandleResponses on an Oracle that charges
+/// its fee in an SPL Token
+pub fn handle_responses_with_token_fee(
+  oracle_program_id: &Pubkey,
+  oracle_id: &Pubkey,
+  node_authority: &Pubkey,
+  fee_vault: &Pubkey,
+  node_token_account: &Pubkey,
+  responses: Vec<(Pubkey, Response)>,
+  atomic: bool,
+) -> Result<Instruction, ProgramError> {
+  let mut instruction = handle_responses(oracle_program_id, oracle_id, node_authority, responses, atomic)?;
+  instruction.accounts.extend(token_fee_payout_accounts(oracle_program_id, oracle_id, fee_vault, node_token_account));
+  Ok(instruction)
+}
+
+/// Generate the Instruction for CancelRequest.
+/// Used by the requester to reclaim the fee of an expired Request
+pub fn cancel_request(
+  oracle_program_id: &Pubkey,
+  oracle_id: &Pubkey,
+  requester: &Pubkey,
+  request_queue_index: RequestIndex,
+) -> Result<Instruction, ProgramError> {
+  let accounts = vec![
+    AccountMeta::new(*oracle_id, false),
+    AccountMeta::new(*requester, true),
+    AccountMeta::new_readonly(sysvar::clock::id(), false),
+  ];
+  let mut data = [0u8; OracleInstruction::LEN];
+  OracleInstruction::CancelRequest { request_queue_index }.pack_into_slice(&mut data);
+  let data = data.to_vec();
+  Ok(Instruction {
+    program_id: *oracle_program_id,
+    accounts,
+    data,
+  })
+}
+
+/// Generate the Instruction for CancelRequest on an Oracle that charges
+/// its fee in an SPL Token
+pub fn cancel_request_with_token_fee(
+  oracle_program_id: &Pubkey,
+  oracle_id: &Pubkey,
+  requester: &Pubkey,
+  requester_token_account: &Pubkey,
+  fee_vault: &Pubkey,
+  request_queue_index: RequestIndex,
+) -> Result<Instruction, ProgramError> {
+  let mut instruction = cancel_request(oracle_program_id, oracle_id, requester, request_queue_index)?;
+  instruction.accounts.extend(token_fee_payout_accounts(oracle_program_id, oracle_id, fee_vault, requester_token_account));
+  Ok(instruction)
+}
+
+/// Accounts needed to pay a token fee out of the Oracle's fee vault
+fn token_fee_payout_accounts(
+  oracle_program_id: &Pubkey,
+  oracle_id: &Pubkey,
+  fee_vault: &Pubkey,
+  recipient_token_account: &Pubkey,
+) -> Vec<AccountMeta> {
+  let (vault_authority, _) = find_vault_authority(oracle_program_id, oracle_id);
+  vec![
+    AccountMeta::new(*fee_vault, false),
+    AccountMeta::new(*recipient_token_account, false),
+    AccountMeta::new_readonly(vault_authority, false),
+    AccountMeta::new_readonly(spl_token::id(), false),
+  ]
+}
+
+/// Generate the Instruction for InitializeOracle.
+pub fn initialize_oracle(
+  oracle_program_id: &Pubkey,
+  oracle_id: &Pubkey,
+  admin: &Pubkey,
+  node_authority: &Pubkey,
+  request_fee: u64,
+  request_expiry_slots: u64,
+) -> Result<Instruction, ProgramError> {
+  let accounts = vec![
+    AccountMeta::new(*oracle_id, false),
+    AccountMeta::new_readonly(*admin, true),
+  ];
+  let mut data = [0u8; OracleInstruction::LEN];
+  OracleInstruction::InitializeOracle {
+    node_authority: *node_authority,
+    request_fee,
+    request_expiry_slots,
+    fee_mint: Pubkey::default(),
+  }.pack_into_slice(&mut data);
+  let data = data.to_vec();
+  Ok(Instruction {
+    program_id: *oracle_program_id,
+    accounts,
+    data,
+  })
+}
+
+/// Generate the Instruction for InitializeOracle with fees paid in an SPL Token.
+/// `fee_vault` must be a token account for `fee_mint` owned by the vault authority
+#[allow(clippy::too_many_arguments)]
+pub fn initialize_oracle_with_token_fee(
+  oracle_program_id: &Pubkey,
+  oracle_id: &Pubkey,
+  admin: &Pubkey,
+  node_authority: &Pubkey,
+  fee_mint: &Pubkey,
+  fee_vault: &Pubkey,
+  request_fee: u64,
+  request_expiry_slots: u64,
+) -> Result<Instruction, ProgramError> {
+  let accounts = vec![
+    AccountMeta::new(*oracle_id, false),
+    AccountMeta::new_readonly(*admin, true),
+    AccountMeta::new_readonly(*fee_vault, false),
+  ];
+  let mut data = [0u8; OracleInstruction::LEN];
+  OracleInstruction::InitializeOracle {
+    node_authority: *node_authority,
+    request_fee,
+    request_expiry_slots,
+    fee_mint: *fee_mint,
+  }.pack_into_slice(&mut data);
+  let data = data.to_vec();
+  Ok(Instruction {
+    program_id: *oracle_program_id,
+    accounts,
+    data,
+  })
+}
+
+/// Generate the Instruction for CreateOracle. `fee_vault` is only needed
+/// when `fee_mint` is set
+#[allow(clippy::too_many_arguments)]
+pub fn create_oracle(
+  oracle_program_id: &Pubkey,
+  admin: &Pubkey,
+  seed: [u8; ORACLE_SEED_LEN],
+  node_authority: &Pubkey,
+  request_fee: u64,
+  request_expiry_slots: u64,
+  fee_mint: &Pubkey,
+  fee_vault: Option<&Pubkey>,
+  capacity: u16,
+) -> Result<Instruction, ProgramError> {
+  let (oracle_id, _) = find_oracle_address(oracle_program_id, admin, &seed);
+  let mut accounts = vec![
+    AccountMeta::new(oracle_id, false),
+    AccountMeta::new(*admin, true),
+    AccountMeta::new_readonly(system_program::id(), false),
+    AccountMeta::new_readonly(sysvar::rent::id(), false),
+  ];
+  if let Some(fee_vault) = fee_vault {
+    accounts.push(AccountMeta::new_readonly(*fee_vault, false));
+  }
+  let mut data = [0u8; OracleInstruction::LEN];
+  OracleInstruction::CreateOracle {
+    seed,
+    node_authority: *node_authority,
+    request_fee,
+    request_expiry_slots,
+    fee_mint: *fee_mint,
+    capacity,
+  }.pack_into_slice(&mut data);
+  let data = data.to_vec();
+  Ok(Instruction {
+    program_id: *oracle_program_id,
+    accounts,
+    data,
+  })
+}
+
+/// Generate the Instruction for SetFeeSchedule.
+pub fn set_fee_schedule(
+  oracle_program_id: &Pubkey,
+  oracle_id: &Pubkey,
+  admin: &Pubkey,
+  fee_schedule: FeeSchedule,
+) -> Result<Instruction, ProgramError> {
+  admin_instruction(oracle_program_id, oracle_id, admin, OracleInstruction::SetFeeSchedule { fee_schedule })
+}
+
+/// Generate the Instruction for RegisterNode.
+pub fn register_node(
+  oracle_program_id: &Pubkey,
+  oracle_id: &Pubkey,
+  node_authority: &Pubkey,
+  stake: u64,
+) -> Result<Instruction, ProgramError> {
+  let (node_id, _) = find_node_address(oracle_program_id, oracle_id, node_authority);
+  let accounts = vec![
+    AccountMeta::new_readonly(*oracle_id, false),
+    AccountMeta::new(node_id, false),
+    AccountMeta::new(*node_authority, true),
+    AccountMeta::new_readonly(system_program::id(), false),
+    AccountMeta::new_readonly(sysvar::rent::id(), false),
+  ];
+  let mut data = [0u8; OracleInstruction::LEN];
+  OracleInstruction::RegisterNode { stake }.pack_into_slice(&mut data);
+  let data = data.to_vec();
+  Ok(Instruction {
+    program_id: *oracle_program_id,
+    accounts,
+    data,
+  })
+}
+
+/// Generate the Instruction for DeregisterNode.
+pub fn deregister_node(
+  oracle_program_id: &Pubkey,
+  oracle_id: &Pubkey,
+  node_authority: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+  let (node_id, _) = find_node_address(oracle_program_id, oracle_id, node_authority);
+  let accounts = vec![
+    AccountMeta::new_readonly(*oracle_id, false),
+    AccountMeta::new(node_id, false),
+    AccountMeta::new_readonly(*node_authority, true),
+    AccountMeta::new_readonly(sysvar::clock::id(), false),
+  ];
+  let mut data = [0u8; OracleInstruction::LEN];
+  OracleInstruction::DeregisterNode.pack_into_slice(&mut data);
+  let data = data.to_vec();
+  Ok(Instruction {
+    program_id: *oracle_program_id,
+    accounts,
+    data,
+  })
+}
+
+/// Generate the Instruction for Unstake.
+pub fn unstake(
+  oracle_program_id: &Pubkey,
+  oracle_id: &Pubkey,
+  node_authority: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+  let (node_id, _) = find_node_address(oracle_program_id, oracle_id, node_authority);
+  let accounts = vec![
+    AccountMeta::new_readonly(*oracle_id, false),
+    AccountMeta::new(node_id, false),
+    AccountMeta::new(*node_authority, true),
+    AccountMeta::new_readonly(sysvar::clock::id(), false),
+  ];
+  let mut data = [0u8; OracleInstruction::LEN];
+  OracleInstruction::Unstake.pack_into_slice(&mut data);
+  let data = data.to_vec();
+  Ok(Instruction {
+    program_id: *oracle_program_id,
+    accounts,
+    data,
+  })
+}
+
+/// Generate the Instruction for SlashNode.
+pub fn slash_node(
+  oracle_program_id: &Pubkey,
+  oracle_id: &Pubkey,
+  node_id: &Pubkey,
+  admin: &Pubkey,
+  destination: &Pubkey,
+  amount: u64,
+) -> Result<Instruction, ProgramError> {
+  let accounts = vec![
+    AccountMeta::new_readonly(*oracle_id, false),
+    AccountMeta::new(*node_id, false),
+    AccountMeta::new_readonly(*admin, true),
+    AccountMeta::new(*destination, false),
+  ];
+  let mut data = [0u8; OracleInstruction::LEN];
+  OracleInstruction::SlashNode { amount }.pack_into_slice(&mut data);
+  let data = data.to_vec();
+  Ok(Instruction {
+    program_id: *oracle_program_id,
+    accounts,
+    data,
+  })
+}
+
+/// Generate the Instruction for SetStakeConfig.
+pub fn set_stake_config(
+  oracle_program_id: &Pubkey,
+  oracle_id: &Pubkey,
+  admin: &Pubkey,
+  min_stake: u64,
+  unstake_cooldown_slots: u64,
+  dispute_bond: u64,
+) -> Result<Instruction, ProgramError> {
+  admin_instruction(oracle_program_id, oracle_id, admin, OracleInstruction::SetStakeConfig {
+    min_stake,
+    unstake_cooldown_slots,
+    dispute_bond,
+  })
+}
+
+/// Generate the Instruction for Dispute.
+pub fn dispute(
+  oracle_program_id: &Pubkey,
+  oracle_id: &Pubkey,
+  disputer: &Pubkey,
+  request_queue_index: RequestIndex,
+) -> Result<Instruction, ProgramError> {
+  let accounts = vec![
+    AccountMeta::new(*oracle_id, false),
+    AccountMeta::new(*disputer, true),
+    AccountMeta::new_readonly(system_program::id(), false),
+    AccountMeta::new_readonly(sysvar::clock::id(), false),
+  ];
+  let mut data = [0u8; OracleInstruction::LEN];
+  OracleInstruction::Dispute { request_queue_index }.pack_into_slice(&mut data);
+  let data = data.to_vec();
+  Ok(Instruction {
+    program_id: *oracle_program_id,
+    accounts,
+    data,
+  })
 }
-impl Sealed for OracleInstruction {}
-impl Pack for OracleInstruction {
-  const LEN: usize = OracleAccount::LEN + 2;
 
-  fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
-    let src = array_ref![src, 0, OracleInstruction::LEN];
-    let (tag, serialized_oracle_account) = array_refs![src, 2, OracleAccount::LEN];
-    return OracleInstruction::decode(*tag, serialized_oracle_account);
-  }
+/// Generate the Instruction for FinalizeResponse.
+pub fn finalize_response(
+  oracle_program_id: &Pubkey,
+  oracle_id: &Pubkey,
+  call_back_program: &Pubkey,
+  node_authority: &Pubkey,
+  request_queue_index: RequestIndex,
+) -> Result<Instruction, ProgramError> {
+  let accounts = vec![
+    AccountMeta::new(*oracle_id, false),
+    AccountMeta::new_readonly(*call_back_program, false),
+    AccountMeta::new(*node_authority, false),
+    AccountMeta::new_readonly(sysvar::clock::id(), false),
+  ];
+  let mut data = [0u8; OracleInstruction::LEN];
+  OracleInstruction::FinalizeResponse { request_queue_index }.pack_into_slice(&mut data);
+  let data = data.to_vec();
+  Ok(Instruction {
+    program_id: *oracle_program_id,
+    accounts,
+    data,
+  })
+}
 
-  fn pack_into_slice(&self, dst: &mut [u8]) {
-    let dst = array_mut_ref![dst, 0, OracleInstruction::LEN];
-    let (
-      tag_dst,
-      data_dest,
-    ) = mut_array_refs![dst, 2, OracleAccount::LEN];
-    self.encode(tag_dst, data_dest)
-  }
+/// Generate the Instruction for FinalizeResponse on an Oracle that charges
+/// its fee in an SPL Token
+pub fn finalize_response_with_token_fee(
+  oracle_program_id: &Pubkey,
+  oracle_id: &Pubkey,
+  call_back_program: &Pubkey,
+  node_authority: &Pubkey,
+  fee_vault: &Pubkey,
+  node_token_account: &Pubkey,
+  request_queue_index: RequestIndex,
+) -> Result<Instruction, ProgramError> {
+  let mut instruction = finalize_response(oracle_program_id, oracle_id, call_back_program, node_authority, request_queue_index)?;
+  instruction.accounts.extend(token_fee_payout_accounts(oracle_program_id, oracle_id, fee_vault, node_token_account));
+  Ok(instruction)
 }
 
-impl OracleInstruction {
+/// Generate the Instruction for ResolveDispute of the disputed `request`.
+pub fn resolve_dispute(
+  oracle_program_id: &Pubkey,
+  oracle_id: &Pubkey,
+  admin: &Pubkey,
+  node_authority: &Pubkey,
+  request: &Request,
+  upheld: bool,
+  data: ResponseData,
+) -> Result<Instruction, ProgramError> {
+  let (node_id, _) = find_node_address(oracle_program_id, oracle_id, node_authority);
+  let accounts = vec![
+    AccountMeta::new(*oracle_id, false),
+    AccountMeta::new_readonly(request.call_back_program, false),
+    AccountMeta::new_readonly(*admin, true),
+    AccountMeta::new(*node_authority, false),
+    AccountMeta::new(request.optimistic.disputer, false),
+    AccountMeta::new(request.requester, false),
+    AccountMeta::new(node_id, false),
+  ];
+  let mut instruction_data = [0u8; OracleInstruction::LEN];
+  OracleInstruction::ResolveDispute {
+    request_queue_index: request.index,
+    upheld,
+    data,
+  }.pack_into_slice(&mut instruction_data);
+  let data = instruction_data.to_vec();
+  Ok(Instruction {
+    program_id: *oracle_program_id,
+    accounts,
+    data,
+  })
+}
 
-  fn decode(tag: [u8; 2], data: &[u8]) -> Result<Self, ProgramError> {
-    match u16::from_le_bytes(tag) {
-      0 => { 
-        let ix_data = array_ref![data, 2, Request::LEN];
-        Ok(OracleInstruction::CreateRequest {
-            request: Request::unpack_from_slice(ix_data)?
-        })
-      },
-      1 => {
-        let ix_data = array_ref![data, 2, Response::LEN];
-        Ok(OracleInstruction::HandleResponse(
-          Response::unpack_from_slice(ix_data)?
-        ))
-      },
-      _ => Err(ProgramError::InvalidInstructionData),
-    }
-  }
+/// Generate the Instruction for ResolveDispute on an Oracle that charges its
+/// fee in an SPL Token. `recipient_token_account` belongs to the requester
+/// when `upheld`, to the node otherwise
+#[allow(clippy::too_many_arguments)]
+pub fn resolve_dispute_with_token_fee(
+  oracle_program_id: &Pubkey,
+  oracle_id: &Pubkey,
+  admin: &Pubkey,
+  node_authority: &Pubkey,
+  request: &Request,
+  upheld: bool,
+  data: ResponseData,
+  fee_vault: &Pubkey,
+  recipient_token_account: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+  let mut instruction = resolve_dispute(oracle_program_id, oracle_id, admin, node_authority, request, upheld, data)?;
+  instruction.accounts.extend(token_fee_payout_accounts(oracle_program_id, oracle_id, fee_vault, recipient_token_account));
+  Ok(instruction)
+}
 
-  fn encode(&self, kind: &mut [u8], data:&mut [u8])  {
-    match self {
-      OracleInstruction::CreateRequest { request } => {
-        let tag: u16 = 0;
-        kind.copy_from_slice(&tag.to_le_bytes()[0..2]);
-        request.pack_into_slice(data);
-      },
-      OracleInstruction::HandleResponse(response) => {
-        let tag: u16 = 1;
-        kind.copy_from_slice(&tag.to_le_bytes()[0..2]);
-        response.pack_into_slice(data);
-      }
-      // TODO propogate error here?
-    }
-  }
+/// Generate the Instruction for SetConfig.
+#[allow(clippy::too_many_arguments)]
+pub fn set_config(
+  oracle_program_id: &Pubkey,
+  oracle_id: &Pubkey,
+  admin: &Pubkey,
+  request_fee: u64,
+  request_expiry_slots: u64,
+  max_url_len: u8,
+  allowed_task_kinds: u8,
+  quorum: u8,
+) -> Result<Instruction, ProgramError> {
+  admin_instruction(oracle_program_id, oracle_id, admin, OracleInstruction::SetConfig {
+    request_fee,
+    request_expiry_slots,
+    max_url_len,
+    allowed_task_kinds,
+    quorum,
+  })
+}
 
-  pub fn unpack(instruction_data: &[u8]) -> Result<Self, ProgramError> {
-    // Missing the u32 that determines the insutrction data
-    if instruction_data.len() < 2 {
-      return Err(ProgramError::InvalidInstructionData);
-    }
+/// Generate the Instruction for SetNodeAuthority.
+pub fn set_node_authority(
+  oracle_program_id: &Pubkey,
+  oracle_id: &Pubkey,
+  admin: &Pubkey,
+  node_authority: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+  admin_instruction(oracle_program_id, oracle_id, admin, OracleInstruction::SetNodeAuthority {
+    node_authority: *node_authority
+  })
+}
 
-    let tag = array_ref![instruction_data, 0, 2];
+/// Generate the Instruction for TransferAdmin.
+pub fn transfer_admin(
+  oracle_program_id: &Pubkey,
+  oracle_id: &Pubkey,
+  admin: &Pubkey,
+  new_admin: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+  admin_instruction(oracle_program_id, oracle_id, admin, OracleInstruction::TransferAdmin {
+    new_admin: *new_admin
+  })
+}
 
-    OracleInstruction::decode(*tag, instruction_data)
+/// Generate the Instruction for AcceptAdmin, signed by the proposed admin.
+pub fn accept_admin(
+  oracle_program_id: &Pubkey,
+  oracle_id: &Pubkey,
+  new_admin: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+  admin_instruction(oracle_program_id, oracle_id, new_admin, OracleInstruction::AcceptAdmin)
+}
+
+/// Generate the Instruction for Pause.
+pub fn pause(oracle_program_id: &Pubkey, oracle_id: &Pubkey, admin: &Pubkey) -> Result<Instruction, ProgramError> {
+  admin_instruction(oracle_program_id, oracle_id, admin, OracleInstruction::Pause)
+}
+
+/// Generate the Instruction for Unpause.
+pub fn unpause(oracle_program_id: &Pubkey, oracle_id: &Pubkey, admin: &Pubkey) -> Result<Instruction, ProgramError> {
+  admin_instruction(oracle_program_id, oracle_id, admin, OracleInstruction::Unpause)
+}
+
+/// Generate the Instruction for SetRequesterLimit.
+pub fn set_requester_limit(
+  oracle_program_id: &Pubkey,
+  oracle_id: &Pubkey,
+  admin: &Pubkey,
+  max_requests_per_requester: u8,
+) -> Result<Instruction, ProgramError> {
+  admin_instruction(oracle_program_id, oracle_id, admin, OracleInstruction::SetRequesterLimit { max_requests_per_requester })
+}
+
+/// Generate the Instruction for SetSecrets.
+pub fn set_secrets(
+  oracle_program_id: &Pubkey,
+  oracle_id: &Pubkey,
+  admin: &Pubkey,
+  secrets: OracleSecrets,
+) -> Result<Instruction, ProgramError> {
+  admin_instruction(oracle_program_id, oracle_id, admin, OracleInstruction::SetSecrets { secrets })
+}
+
+/// Generate the Instruction for ResizeOracle.
+pub fn resize_oracle(oracle_program_id: &Pubkey, oracle_id: &Pubkey, admin: &Pubkey, capacity: u16) -> Result<Instruction, ProgramError> {
+  admin_instruction(oracle_program_id, oracle_id, admin, OracleInstruction::ResizeOracle { capacity })
+}
+
+/// Generate the Instruction for CloseOracle, refunding `pending_requests`
+/// when `force` is set.
+pub fn close_oracle(
+  oracle_program_id: &Pubkey,
+  oracle_id: &Pubkey,
+  admin: &Pubkey,
+  destination: &Pubkey,
+  force: bool,
+  pending_requests: &[Request],
+) -> Result<Instruction, ProgramError> {
+  let mut instruction = admin_instruction(oracle_program_id, oracle_id, admin, OracleInstruction::CloseOracle { force })?;
+  instruction.accounts.push(AccountMeta::new(*destination, false));
+  for request in pending_requests {
+    instruction.accounts.push(AccountMeta::new(request.requester, false));
+    instruction.accounts.extend(disputer_account(request));
   }
+  Ok(instruction)
 }
-/// Generate the Instruction for CreateRequest.
-/// Used for clients and cross program invocation
-pub fn create_request(
+
+/// Generate the Instruction for CloseOracle on an Oracle that charges its
+/// fee in an SPL Token. Each pending Request comes with the requester's
+/// token account for the fee mint.
+#[allow(clippy::too_many_arguments)]
+pub fn close_oracle_with_token_fee(
   oracle_program_id: &Pubkey,
   oracle_id: &Pubkey,
-  request: Request
+  admin: &Pubkey,
+  destination: &Pubkey,
+  force: bool,
+  fee_vault: &Pubkey,
+  pending_requests: &[(Request, Pubkey)],
 ) -> Result<Instruction, ProgramError> {
-  let accounts = vec![AccountMeta::new(*oracle_id, false)];
-  let mut data  = [0u8; OracleInstruction::LEN];
-  OracleInstruction::CreateRequest { request }.pack_into_slice(&mut data);
+  let mut instruction = admin_instruction(oracle_program_id, oracle_id, admin, OracleInstruction::CloseOracle { force })?;
+  instruction.accounts.push(AccountMeta::new(*destination, false));
+  for (request, requester_token_account) in pending_requests {
+    instruction.accounts.push(AccountMeta::new(request.requester, false));
+    instruction.accounts.extend(token_fee_payout_accounts(oracle_program_id, oracle_id, fee_vault, requester_token_account));
+    instruction.accounts.extend(disputer_account(request));
+  }
+  Ok(instruction)
+}
+
+/// The disputer refunded on close, only for a disputed Request
+fn disputer_account(request: &Request) -> Option<AccountMeta> {
+  if request.optimistic.status == ResponseStatus::Disputed {
+    Some(AccountMeta::new(request.optimistic.disputer, false))
+  } else {
+    None
+  }
+}
+
+/// Instruction taking the Oracle account followed by the admin's signature
+fn admin_instruction(
+  oracle_program_id: &Pubkey,
+  oracle_id: &Pubkey,
+  admin: &Pubkey,
+  instruction: OracleInstruction,
+) -> Result<Instruction, ProgramError> {
+  let accounts = vec![
+    AccountMeta::new(*oracle_id, false),
+    AccountMeta::new_readonly(*admin, true),
+  ];
+  let mut data = [0u8; OracleInstruction::LEN];
+  instruction.pack_into_slice(&mut data);
+  let data = data.to_vec();
+  Ok(Instruction {
+    program_id: *oracle_program_id,
+    accounts,
+    data,
+  })
+}
+
+/// Generate the Instruction for InitializeFeed.
+pub fn initialize_feed(
+  oracle_program_id: &Pubkey,
+  feed_id: &Pubkey,
+  authority: &Pubkey,
+  tasks: [Task; TASK_ARRAY_SIZE],
+  deviation_threshold_bps: u16,
+  heartbeat_slots: u64,
+  max_change_bps: u16,
+) -> Result<Instruction, ProgramError> {
+  let accounts = vec![
+    AccountMeta::new(*feed_id, false),
+    AccountMeta::new_readonly(*authority, true),
+  ];
+  let mut data = [0u8; OracleInstruction::LEN];
+  OracleInstruction::InitializeFeed {
+    tasks,
+    deviation_threshold_bps,
+    heartbeat_slots,
+    max_change_bps,
+  }.pack_into_slice(&mut data);
+  let data = data.to_vec();
+  Ok(Instruction {
+    program_id: *oracle_program_id,
+    accounts,
+    data,
+  })
+}
+
+/// Generate the Instruction for UpdateFeed.
+/// Used by the node when a new value deviates enough or the heartbeat is due
+pub fn update_feed(
+  oracle_program_id: &Pubkey,
+  feed_id: &Pubkey,
+  authority: &Pubkey,
+  value: u32,
+) -> Result<Instruction, ProgramError> {
+  let accounts = vec![
+    AccountMeta::new(*feed_id, false),
+    AccountMeta::new_readonly(*authority, true),
+    AccountMeta::new_readonly(sysvar::clock::id(), false),
+  ];
+  let mut data = [0u8; OracleInstruction::LEN];
+  OracleInstruction::UpdateFeed { value }.pack_into_slice(&mut data);
+  let data = data.to_vec();
+  Ok(Instruction {
+    program_id: *oracle_program_id,
+    accounts,
+    data,
+  })
+}
+
+/// Generate the Instruction for ResetFeed.
+/// Used by the node to restart a Feed whose circuit breaker tripped
+pub fn reset_feed(
+  oracle_program_id: &Pubkey,
+  feed_id: &Pubkey,
+  authority: &Pubkey,
+  value: u32,
+) -> Result<Instruction, ProgramError> {
+  let accounts = vec![
+    AccountMeta::new(*feed_id, false),
+    AccountMeta::new_readonly(*authority, true),
+    AccountMeta::new_readonly(sysvar::clock::id(), false),
+  ];
+  let mut data = [0u8; OracleInstruction::LEN];
+  OracleInstruction::ResetFeed { value }.pack_into_slice(&mut data);
   let data = data.to_vec();
   Ok(Instruction {
     program_id: *oracle_program_id,
@@ -115,11 +1904,14 @@ pub fn create_request(
 #[cfg(test)]
 mod tests {
   use super::*;
+  use crate::job_spec::job_param;
   use generic_array::GenericArray;
+  use crate::fee_schedule::HostFee;
   use crate::request::{
     GetArgs,
     GetParams,
     JsonParseArgs,
+    OptimisticResponse,
     Task
   };
 
@@ -132,9 +1924,7 @@ mod tests {
     let params = GetParams {
       get: *GenericArray::from_slice(url_bytes)
     };
-    let args = GetArgs {
-      params: params
-    };
+    let args = GetArgs::new(params);
     let get_task = Task::HttpGet(args);
     let json_parse_task = Task::JsonParse(json_args);
     let uint_128_task = Task::Uint32;
@@ -143,6 +1933,14 @@ mod tests {
       tasks: [get_task, json_parse_task, uint_128_task],
       call_back_program: Pubkey::new_unique(),
       index: 0,
+      requester: Pubkey::default(),
+      fee: 0,
+      created_slot: 0,
+      id: 0,
+      tip: 0,
+      max_answer_age_slots: 0,
+      job_id: 0,
+      optimistic: OptimisticResponse::default(),
     }
   }
 
@@ -164,15 +1962,75 @@ mod tests {
     assert_eq!(res, create_req_instruction);
   }
 
+  #[test]
+  fn test_create_requests_instruction() {
+    let create_reqs_instruction = OracleInstruction::CreateRequests {
+      requests: vec![build_request(), build_request(), build_request()],
+    };
+    let ix = create_requests(&Pubkey::new_unique(), &Pubkey::new_unique(), &Pubkey::new_unique(), vec![build_request(); 3]).unwrap();
+    assert_eq!(ix.data.len(), create_reqs_instruction.packed_len());
+    assert_eq!(ix.data.len(), 3 + 3 * Request::LEN);
+    let mut instruction_data = vec![0u8; create_reqs_instruction.packed_len()];
+    create_reqs_instruction.pack_into_slice(&mut instruction_data);
+    assert_eq!(instruction_data[0..3], [27, 0, 3]);
+    let res = OracleInstruction::unpack(&instruction_data).unwrap();
+    assert_eq!(res, create_reqs_instruction);
+
+    // the count must match the Requests that follow
+    instruction_data[2] = 4;
+    assert_eq!(OracleInstruction::unpack(&instruction_data), Err(ProgramError::InvalidInstructionData));
+    instruction_data[2] = 0;
+    assert_eq!(OracleInstruction::unpack(&instruction_data), Err(ProgramError::InvalidInstructionData));
+    assert_eq!(
+      create_requests(&Pubkey::new_unique(), &Pubkey::new_unique(), &Pubkey::new_unique(), vec![]),
+      Err(ProgramError::InvalidArgument)
+    );
+  }
+
+  #[test]
+  fn test_pack_unpack_feed_instructions() {
+    let request = build_request();
+    let initialize_feed_instruction = OracleInstruction::InitializeFeed {
+      tasks: request.tasks,
+      deviation_threshold_bps: 50,
+      heartbeat_slots: 150,
+      max_change_bps: 1_000,
+    };
+    let mut instruction_data = [0u8; OracleInstruction::LEN];
+    initialize_feed_instruction.pack_into_slice(&mut instruction_data);
+    let res = OracleInstruction::unpack(&instruction_data).unwrap();
+    assert_eq!(res, initialize_feed_instruction);
+
+    let update_feed_instruction = OracleInstruction::UpdateFeed { value: 15439 };
+    let mut instruction_data = [0u8; OracleInstruction::LEN];
+    update_feed_instruction.pack_into_slice(&mut instruction_data);
+    assert_eq!(instruction_data[0..2], [3, 0]);
+    assert_eq!(instruction_data[2..6], 15439u32.to_le_bytes());
+    let res = OracleInstruction::unpack(&instruction_data).unwrap();
+    assert_eq!(res, update_feed_instruction);
+
+    let reset_feed_instruction = OracleInstruction::ResetFeed { value: 15439 };
+    let mut instruction_data = [0u8; OracleInstruction::LEN];
+    reset_feed_instruction.pack_into_slice(&mut instruction_data);
+    assert_eq!(instruction_data[0..2], [21, 0]);
+    let res = OracleInstruction::unpack(&instruction_data).unwrap();
+    assert_eq!(res, reset_feed_instruction);
+  }
+
   #[test]
   fn test_create_request() {
     let oracle_program_id = Pubkey::default();
     let oracle_id = Pubkey::default();
+    let payer = Pubkey::new_unique();
     let call_back_program = Pubkey::new_unique();
     let mut request = build_request();
     request.call_back_program = call_back_program;
-    let account = AccountMeta::new(oracle_id, false);
-    let accounts = vec![account];
+    let accounts = vec![
+      AccountMeta::new(oracle_id, false),
+      AccountMeta::new(payer, true),
+      AccountMeta::new_readonly(system_program::id(), false),
+      AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
     let mut data  = [0u8; OracleInstruction::LEN];
     OracleInstruction::CreateRequest { request }.pack_into_slice(&mut data);
     let data = data.to_vec();
@@ -184,7 +2042,230 @@ mod tests {
 
     let mut external_request = build_request();
     external_request.call_back_program = call_back_program;
-    let ret = create_request(&oracle_program_id, &oracle_id, external_request).unwrap();
+    let ret = create_request(&oracle_program_id, &oracle_id, &payer, external_request).unwrap();
     assert_eq!(ret, instruction);
   }
-}
\ No newline at end of file
+
+  #[test]
+  fn test_pack_unpack_oracle_instructions() {
+    let initialize_oracle_instruction = OracleInstruction::InitializeOracle {
+      node_authority: Pubkey::new_unique(),
+      request_fee: 5_000,
+      request_expiry_slots: 300,
+      fee_mint: Pubkey::new_unique(),
+    };
+    let mut instruction_data = [0u8; OracleInstruction::LEN];
+    initialize_oracle_instruction.pack_into_slice(&mut instruction_data);
+    let res = OracleInstruction::unpack(&instruction_data).unwrap();
+    assert_eq!(res, initialize_oracle_instruction);
+
+    let cancel_request_instruction = OracleInstruction::CancelRequest { request_queue_index: 7 };
+    let mut instruction_data = [0u8; OracleInstruction::LEN];
+    cancel_request_instruction.pack_into_slice(&mut instruction_data);
+    assert_eq!(instruction_data[0..3], [5, 0, 7]);
+    let res = OracleInstruction::unpack(&instruction_data).unwrap();
+    assert_eq!(res, cancel_request_instruction);
+
+    let mut fee_schedule = FeeSchedule {
+      task_fees: [1_000, 10, 1, 2_000],
+      ..FeeSchedule::default()
+    };
+    fee_schedule.host_fees[0] = HostFee::new(b"ftx.us", 3_000).unwrap();
+    let set_fee_schedule_instruction = OracleInstruction::SetFeeSchedule { fee_schedule };
+    let mut instruction_data = [0u8; OracleInstruction::LEN];
+    set_fee_schedule_instruction.pack_into_slice(&mut instruction_data);
+    assert_eq!(instruction_data[0..2], [6, 0]);
+    let res = OracleInstruction::unpack(&instruction_data).unwrap();
+    assert_eq!(res, set_fee_schedule_instruction);
+  }
+
+  #[test]
+  fn test_pack_unpack_node_instructions() {
+    let node_instructions = vec![
+      OracleInstruction::RegisterNode { stake: 1_000_000 },
+      OracleInstruction::DeregisterNode,
+      OracleInstruction::Unstake,
+      OracleInstruction::SlashNode { amount: 500 },
+      OracleInstruction::SetStakeConfig { min_stake: 1_000_000, unstake_cooldown_slots: 1_000, dispute_bond: 100_000 },
+    ];
+    for (tag, node_instruction) in (7u8..).zip(node_instructions) {
+      let mut instruction_data = [0u8; OracleInstruction::LEN];
+      node_instruction.pack_into_slice(&mut instruction_data);
+      assert_eq!(instruction_data[0..2], [tag, 0]);
+      let res = OracleInstruction::unpack(&instruction_data).unwrap();
+      assert_eq!(res, node_instruction);
+    }
+  }
+
+  #[test]
+  fn test_pack_unpack_dispute_instructions() {
+    let dispute_instructions = vec![
+      OracleInstruction::Dispute { request_queue_index: 3 },
+      OracleInstruction::FinalizeResponse { request_queue_index: 3 },
+      OracleInstruction::ResolveDispute { request_queue_index: 3, upheld: true, data: 15439u32.to_le_bytes() },
+    ];
+    for (tag, dispute_instruction) in (12u8..).zip(dispute_instructions) {
+      let mut instruction_data = [0u8; OracleInstruction::LEN];
+      dispute_instruction.pack_into_slice(&mut instruction_data);
+      assert_eq!(instruction_data[0..3], [tag, 0, 3]);
+      let res = OracleInstruction::unpack(&instruction_data).unwrap();
+      assert_eq!(res, dispute_instruction);
+    }
+
+    let mut instruction_data = [0u8; OracleInstruction::LEN];
+    instruction_data[0] = 14;
+    instruction_data[3] = 2;
+    assert_eq!(OracleInstruction::unpack(&instruction_data), Err(ProgramError::InvalidInstructionData));
+  }
+
+  #[test]
+  fn test_pack_unpack_admin_instructions() {
+    let admin_instructions = vec![
+      OracleInstruction::SetConfig {
+        request_fee: 5_000,
+        request_expiry_slots: 300,
+        max_url_len: 30,
+        allowed_task_kinds: 0b101,
+        quorum: 2,
+      },
+      OracleInstruction::SetNodeAuthority { node_authority: Pubkey::new_unique() },
+      OracleInstruction::TransferAdmin { new_admin: Pubkey::new_unique() },
+      OracleInstruction::AcceptAdmin,
+      OracleInstruction::Pause,
+      OracleInstruction::Unpause,
+    ];
+    for (tag, admin_instruction) in (15u8..).zip(admin_instructions) {
+      let mut instruction_data = [0u8; OracleInstruction::LEN];
+      admin_instruction.pack_into_slice(&mut instruction_data);
+      assert_eq!(instruction_data[0..2], [tag, 0]);
+      let res = OracleInstruction::unpack(&instruction_data).unwrap();
+      assert_eq!(res, admin_instruction);
+    }
+
+    let close_oracle_instruction = OracleInstruction::CloseOracle { force: true };
+    let mut instruction_data = [0u8; OracleInstruction::LEN];
+    close_oracle_instruction.pack_into_slice(&mut instruction_data);
+    assert_eq!(instruction_data[0..3], [22, 0, 1]);
+    assert_eq!(OracleInstruction::unpack(&instruction_data).unwrap(), close_oracle_instruction);
+    instruction_data[2] = 2;
+    assert_eq!(OracleInstruction::unpack(&instruction_data), Err(ProgramError::InvalidInstructionData));
+  }
+
+  #[test]
+  fn test_pack_unpack_create_oracle() {
+    let create_oracle_instruction = OracleInstruction::CreateOracle {
+      seed: [9u8; ORACLE_SEED_LEN],
+      node_authority: Pubkey::new_unique(),
+      request_fee: 5_000,
+      request_expiry_slots: 300,
+      fee_mint: Pubkey::new_unique(),
+      capacity: 300,
+    };
+    let mut instruction_data = [0u8; OracleInstruction::LEN];
+    create_oracle_instruction.pack_into_slice(&mut instruction_data);
+    assert_eq!(instruction_data[0..2], [23, 0]);
+    let res = OracleInstruction::unpack(&instruction_data).unwrap();
+    assert_eq!(res, create_oracle_instruction);
+
+    let program_id = Pubkey::new_unique();
+    let admin = Pubkey::new_unique();
+    let ix = create_oracle(&program_id, &admin, [9u8; ORACLE_SEED_LEN], &admin, 5_000, 300, &Pubkey::default(), None, 10).unwrap();
+    assert_eq!(ix.accounts[0].pubkey, find_oracle_address(&program_id, &admin, &[9u8; ORACLE_SEED_LEN]).0);
+    assert_eq!(ix.accounts.len(), 4);
+  }
+
+  #[test]
+  fn test_pack_unpack_set_requester_limit() {
+    let set_requester_limit_instruction = OracleInstruction::SetRequesterLimit { max_requests_per_requester: 3 };
+    let mut instruction_data = [0u8; OracleInstruction::LEN];
+    set_requester_limit_instruction.pack_into_slice(&mut instruction_data);
+    assert_eq!(instruction_data[0..3], [24, 0, 3]);
+    let res = OracleInstruction::unpack(&instruction_data).unwrap();
+    assert_eq!(res, set_requester_limit_instruction);
+
+    let resize_oracle_instruction = OracleInstruction::ResizeOracle { capacity: 300 };
+    let mut instruction_data = [0u8; OracleInstruction::LEN];
+    resize_oracle_instruction.pack_into_slice(&mut instruction_data);
+    assert_eq!(instruction_data[0..4], [25, 0, 44, 1]);
+    let res = OracleInstruction::unpack(&instruction_data).unwrap();
+    assert_eq!(res, resize_oracle_instruction);
+
+    let mut secrets = OracleSecrets::default();
+    secrets[1] = OracleSecret::new(b"coingecko_pro", b"pro-api.coingecko.com").unwrap();
+    let set_secrets_instruction = OracleInstruction::SetSecrets { secrets };
+    let mut instruction_data = [0u8; OracleInstruction::LEN];
+    set_secrets_instruction.pack_into_slice(&mut instruction_data);
+    assert_eq!(instruction_data[0..2], [31, 0]);
+    assert_eq!(instruction_data[2 + OracleSecret::LEN..2 + OracleSecret::LEN + 13], *b"coingecko_pro");
+    let res = OracleInstruction::unpack(&instruction_data).unwrap();
+    assert_eq!(res, set_secrets_instruction);
+  }
+
+  #[test]
+  fn test_pack_unpack_handle_responses() {
+    let handle_responses_instruction = OracleInstruction::HandleResponses {
+      responses: vec![
+        Response { data: 7u32.to_le_bytes(), request_queue_index: 2 },
+        Response { data: 9u32.to_le_bytes(), request_queue_index: 0 },
+      ],
+      atomic: true,
+    };
+    let mut instruction_data = [0u8; OracleInstruction::LEN];
+    handle_responses_instruction.pack_into_slice(&mut instruction_data);
+    assert_eq!(instruction_data[0..4], [26, 0, 1, 2]);
+    let res = OracleInstruction::unpack(&instruction_data).unwrap();
+    assert_eq!(res, handle_responses_instruction);
+
+    // more Responses than a batch may carry
+    instruction_data[3] = MAX_BATCHED_RESPONSES as u8 + 1;
+    assert_eq!(OracleInstruction::unpack(&instruction_data), Err(ProgramError::InvalidInstructionData));
+
+    // the count must match the Responses that follow
+    instruction_data[3] = 2;
+    let short_data = &instruction_data[..4 + Response::LEN];
+    assert_eq!(OracleInstruction::unpack(short_data), Err(ProgramError::InvalidInstructionData));
+    assert_eq!(OracleInstruction::unpack(&instruction_data[..3]), Err(ProgramError::InvalidInstructionData));
+  }
+
+  #[test]
+  fn test_pack_unpack_job_instructions() {
+    let mut name = [0u8; JOB_NAME_LEN];
+    name[..7].copy_from_slice(b"BTC/USD");
+    let register_job_instruction = OracleInstruction::RegisterJob {
+      job_id: 7,
+      name,
+      tasks: build_request().tasks,
+      params: [job_param(b"market"), [0u8; JOB_PARAM_LEN]],
+    };
+    let mut instruction_data = [0u8; OracleInstruction::LEN];
+    register_job_instruction.pack_into_slice(&mut instruction_data);
+    assert_eq!(instruction_data[0..3], [29, 0, 7]);
+    let res = OracleInstruction::unpack(&instruction_data).unwrap();
+    assert_eq!(res, register_job_instruction);
+
+    let create_job_request_instruction = OracleInstruction::CreateJobRequest {
+      job_id: 7,
+      call_back_program: Pubkey::new_unique(),
+      fee: 5_000,
+      tip: 10,
+      dispute_window_slots: 0,
+      max_answer_age_slots: 20,
+      args: [job_param(b"ETH"), [0u8; JOB_PARAM_LEN]],
+    };
+    let mut instruction_data = [0u8; OracleInstruction::LEN];
+    create_job_request_instruction.pack_into_slice(&mut instruction_data);
+    assert_eq!(instruction_data[0..3], [30, 0, 7]);
+    let res = OracleInstruction::unpack(&instruction_data).unwrap();
+    assert_eq!(res, create_job_request_instruction);
+  }
+
+  #[test]
+  fn test_pack_unpack_serve_cached_response() {
+    let serve_cached_response_instruction = OracleInstruction::ServeCachedResponse { request_queue_index: 4 };
+    let mut instruction_data = [0u8; OracleInstruction::LEN];
+    serve_cached_response_instruction.pack_into_slice(&mut instruction_data);
+    assert_eq!(instruction_data[0..3], [28, 0, 4]);
+    let res = OracleInstruction::unpack(&instruction_data).unwrap();
+    assert_eq!(res, serve_cached_response_instruction);
+  }
+}
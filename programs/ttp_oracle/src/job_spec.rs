@@ -0,0 +1,349 @@
+use crate::{
+  error::OracleError,
+  PUBLIC_KEY_LEN,
+  request::{ GetArgs, GetParams, JsonParseArgs, Task, TASK_ARRAY_SIZE },
+};
+use solana_program::{
+  program_error::ProgramError,
+  program_pack::{ IsInitialized, Pack, Sealed },
+  pubkey::Pubkey,
+};
+use arrayref::{ array_mut_ref, array_ref, array_refs, mut_array_refs };
+use generic_array::GenericArray;
+
+/// Seed for the program address of a JobSpec account
+pub const JOB_SEED: &[u8] = b"job";
+/// Size of the zero padded name of a JobSpec
+pub const JOB_NAME_LEN: usize = 32;
+/// Most parameters a JobSpec template declares
+pub const MAX_JOB_PARAMS: usize = 2;
+/// Size of the zero padded name of a parameter, and of its argument
+pub const JOB_PARAM_LEN: usize = 16;
+
+/// Zero padded parameter names, or the arguments a Request binds to them
+pub type JobParams = [[u8; JOB_PARAM_LEN]; MAX_JOB_PARAMS];
+
+/// A task pipeline registered with an Oracle by its admin. Requests refer
+/// to it by `job_id` instead of carrying the Tasks themselves. The account
+/// is a program address derived from the Oracle and the job id.
+///
+/// The url of an HttpGet, the url and body of an HttpPost and the path of a
+/// JsonParse Task are templates:
+/// `{name}` is replaced by the argument the Request passes for the declared
+/// parameter `name`, and `{{` and `}}` stand for literal braces. Headers
+/// are sent as they are.
+#[derive(Clone, Debug, PartialEq)]
+pub struct JobSpec {
+  pub is_initialized: bool,
+  pub oracle: Pubkey,
+  // Never 0, which marks Requests carrying their own Tasks
+  pub job_id: u64,
+  // Zero padded UTF 8 name, e.g. "BTC/USD"
+  pub name: [u8; JOB_NAME_LEN],
+  pub tasks: [Task; TASK_ARRAY_SIZE],
+  // Names the templates may refer to, unused ones are all zero
+  pub params: JobParams,
+}
+
+/// Program address of the JobSpec `job_id` on `oracle_id`
+pub fn find_job_address(oracle_program_id: &Pubkey, oracle_id: &Pubkey, job_id: u64) -> (Pubkey, u8) {
+  Pubkey::find_program_address(&[oracle_id.as_ref(), &job_id.to_le_bytes(), JOB_SEED], oracle_program_id)
+}
+
+/// Zero pad a parameter name or argument. Panics if `value` is longer than
+/// `JOB_PARAM_LEN`.
+pub fn job_param(value: &[u8]) -> [u8; JOB_PARAM_LEN] {
+  let mut param = [0u8; JOB_PARAM_LEN];
+  param[..value.len()].copy_from_slice(value);
+  param
+}
+
+/// The bytes of a zero padded field up to its padding
+fn trim_padding(field: &[u8]) -> &[u8] {
+  &field[..field.iter().position(|b| *b == 0).unwrap_or(field.len())]
+}
+
+/// Parameter names and their arguments may only hold ASCII letters, digits,
+/// `-` and `_`, so an argument can not add url segments, query parameters,
+/// json path steps or placeholders to the template it is substituted into
+fn is_valid_param(field: &[u8]) -> bool {
+  let value = trim_padding(field);
+  !value.is_empty()
+    && value.iter().all(|b| b.is_ascii_alphanumeric() || *b == b'-' || *b == b'_')
+    // the padding must not hide further bytes
+    && field[value.len()..].iter().all(|b| *b == 0)
+}
+
+impl JobSpec {
+  /// Check the parameter names are valid and every template is well formed
+  /// and only refers to declared parameters
+  pub fn validate_template(&self) -> Result<(), OracleError> {
+    for (i, param) in self.params.iter().enumerate() {
+      let declared = *param != [0u8; JOB_PARAM_LEN];
+      if declared && (!is_valid_param(param) || self.params[..i].contains(param)) {
+        return Err(OracleError::InvalidJobTemplate);
+      }
+    }
+    // substituting empty arguments catches every template error
+    let values: [&[u8]; MAX_JOB_PARAMS] = [&[]; MAX_JOB_PARAMS];
+    self.tasks.iter()
+      .try_for_each(|task| self.render_task(task, &values).map(|_| ()))
+      .map_err(|_| OracleError::InvalidJobTemplate)
+  }
+
+  /// The Tasks with `args` substituted for the placeholders. Every declared
+  /// parameter needs a valid argument, the others must be left empty, and
+  /// the result must fit the Task.
+  pub fn render_tasks(&self, args: &JobParams) -> Result<[Task; TASK_ARRAY_SIZE], OracleError> {
+    let mut values: [&[u8]; MAX_JOB_PARAMS] = [&[]; MAX_JOB_PARAMS];
+    for (i, (param, arg)) in self.params.iter().zip(args.iter()).enumerate() {
+      let declared = *param != [0u8; JOB_PARAM_LEN];
+      if (declared && !is_valid_param(arg)) || (!declared && *arg != [0u8; JOB_PARAM_LEN]) {
+        return Err(OracleError::InvalidJobArgument);
+      }
+      values[i] = trim_padding(arg);
+    }
+    Ok([
+      self.render_task(&self.tasks[0], &values)?,
+      self.render_task(&self.tasks[1], &values)?,
+      self.render_task(&self.tasks[2], &values)?,
+    ])
+  }
+
+  fn render_task(&self, task: &Task, values: &[&[u8]; MAX_JOB_PARAMS]) -> Result<Task, OracleError> {
+    match task {
+      Task::HttpGet(args) => {
+        let mut url = [0u8; GetParams::LEN];
+        self.substitute_padded(args.params.get.as_slice(), values, &mut url)?;
+        Task::HttpGet(GetArgs::new(GetParams { get: *GenericArray::from_slice(&url) }))
+          .with_headers(task.headers().to_vec())
+          .map_err(|_| OracleError::InvalidJobArgument)
+      },
+      Task::JsonParse(args) => {
+        let mut path = [0u8; JsonParseArgs::LEN];
+        self.substitute_padded(&args.path, values, &mut path)?;
+        Ok(Task::JsonParse(JsonParseArgs { path }))
+      },
+      Task::Uint32 => Ok(Task::Uint32),
+      Task::HttpPost(args) => Task::http_post(
+        &self.substitute(args.url(), values)?,
+        &self.substitute(args.body(), values)?,
+        args.content_type(),
+      )
+        .and_then(|rendered| rendered.with_headers(task.headers().to_vec()))
+        .map_err(|_| OracleError::InvalidJobArgument),
+    }
+  }
+
+  /// Write the zero padded `template` with its placeholders replaced by
+  /// `values` to the zero padded `dst`
+  fn substitute_padded(&self, template: &[u8], values: &[&[u8]; MAX_JOB_PARAMS], dst: &mut [u8]) -> Result<(), OracleError> {
+    let rendered = self.substitute(trim_padding(template), values)?;
+    if rendered.len() > dst.len() {
+      return Err(OracleError::InvalidJobArgument);
+    }
+    dst[..rendered.len()].copy_from_slice(&rendered);
+    Ok(())
+  }
+
+  /// `template` with its placeholders replaced by `values`
+  fn substitute(&self, template: &[u8], values: &[&[u8]; MAX_JOB_PARAMS]) -> Result<Vec<u8>, OracleError> {
+    let mut rendered: Vec<u8> = Vec::with_capacity(template.len());
+    let mut i = 0;
+    while i < template.len() {
+      match (template[i], template.get(i + 1)) {
+        (b'{', Some(b'{')) | (b'}', Some(b'}')) => {
+          rendered.push(template[i]);
+          i += 2;
+        },
+        (b'{', _) => {
+          let len = template[i + 1..].iter().position(|b| *b == b'}')
+            .ok_or(OracleError::InvalidJobTemplate)?;
+          let name = &template[i + 1..i + 1 + len];
+          let param = self.params.iter()
+            .position(|param| !name.is_empty() && trim_padding(param) == name)
+            .ok_or(OracleError::InvalidJobTemplate)?;
+          rendered.extend_from_slice(values[param]);
+          i += len + 2;
+        },
+        (b'}', _) => return Err(OracleError::InvalidJobTemplate),
+        (b, _) => {
+          rendered.push(b);
+          i += 1;
+        },
+      }
+    }
+    Ok(rendered)
+  }
+}
+
+impl Sealed for JobSpec {}
+impl IsInitialized for JobSpec {
+  fn is_initialized(&self) -> bool {
+    self.is_initialized
+  }
+}
+impl Pack for JobSpec {
+  const LEN: usize = 1 + PUBLIC_KEY_LEN + 8 + JOB_NAME_LEN + Task::LEN * TASK_ARRAY_SIZE + JOB_PARAM_LEN * MAX_JOB_PARAMS;
+
+  fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+    let src = array_ref![src, 0, JobSpec::LEN];
+    let (is_initialized, oracle, job_id, name, task_1, task_2, task_3, param_1, param_2) =
+      array_refs![src, 1, PUBLIC_KEY_LEN, 8, JOB_NAME_LEN, Task::LEN, Task::LEN, Task::LEN, JOB_PARAM_LEN, JOB_PARAM_LEN];
+    let is_initialized = match is_initialized {
+      [0] => false,
+      [1] => true,
+      _ => return Err(ProgramError::InvalidAccountData),
+    };
+    Ok(JobSpec {
+      is_initialized,
+      oracle: Pubkey::new(oracle),
+      job_id: u64::from_le_bytes(*job_id),
+      name: *name,
+      tasks: [
+        Task::unpack_from_slice(task_1)?,
+        Task::unpack_from_slice(task_2)?,
+        Task::unpack_from_slice(task_3)?,
+      ],
+      params: [*param_1, *param_2],
+    })
+  }
+
+  fn pack_into_slice(&self, dst: &mut [u8]) {
+    let dst = array_mut_ref![dst, 0, JobSpec::LEN];
+    let (is_initialized, oracle, job_id, name, task_1, task_2, task_3, param_1, param_2) =
+      mut_array_refs![dst, 1, PUBLIC_KEY_LEN, 8, JOB_NAME_LEN, Task::LEN, Task::LEN, Task::LEN, JOB_PARAM_LEN, JOB_PARAM_LEN];
+    is_initialized[0] = self.is_initialized as u8;
+    *oracle = self.oracle.to_bytes();
+    *job_id = self.job_id.to_le_bytes();
+    *name = self.name;
+    self.tasks[0].pack_into_slice(task_1);
+    self.tasks[1].pack_into_slice(task_2);
+    self.tasks[2].pack_into_slice(task_3);
+    *param_1 = self.params[0];
+    *param_2 = self.params[1];
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::request::ContentType;
+
+  #[test]
+  fn test_pack_unpack_job_spec() {
+    let mut name = [0u8; JOB_NAME_LEN];
+    name[..7].copy_from_slice(b"BTC/USD");
+    let job = JobSpec {
+      is_initialized: true,
+      oracle: Pubkey::new(&[4u8; PUBLIC_KEY_LEN]),
+      job_id: 7,
+      name,
+      tasks: [Task::Uint32, Task::Uint32, Task::Uint32],
+      params: [job_param(b"market"), [0u8; JOB_PARAM_LEN]],
+    };
+    let mut packed = [0u8; JobSpec::LEN];
+    JobSpec::pack(job.clone(), &mut packed).unwrap();
+    assert_eq!(JobSpec::unpack(&packed).unwrap(), job);
+    assert_eq!(JobSpec::unpack(&[0u8; JobSpec::LEN]), Err(ProgramError::UninitializedAccount));
+  }
+
+  fn market_job(url: &[u8], path: &[u8]) -> JobSpec {
+    let mut get = [0u8; GetParams::LEN];
+    get[..url.len()].copy_from_slice(url);
+    let mut json_path = [0u8; JsonParseArgs::LEN];
+    json_path[..path.len()].copy_from_slice(path);
+    JobSpec {
+      is_initialized: true,
+      oracle: Pubkey::new(&[4u8; PUBLIC_KEY_LEN]),
+      job_id: 7,
+      name: [0u8; JOB_NAME_LEN],
+      tasks: [
+        Task::HttpGet(GetArgs::new(GetParams { get: *GenericArray::from_slice(&get) })),
+        Task::JsonParse(JsonParseArgs { path: json_path }),
+        Task::Uint32,
+      ],
+      params: [job_param(b"m"), job_param(b"f")],
+    }
+  }
+
+  #[test]
+  fn test_render_job_template() {
+    let job = market_job(b"https://ftx.us/api/markets/{m}/USD", b"result.{f}");
+    job.validate_template().unwrap();
+    let tasks = job.render_tasks(&[job_param(b"ETH"), job_param(b"price")]).unwrap();
+    assert_eq!(tasks, market_job(b"https://ftx.us/api/markets/ETH/USD", b"result.price").tasks);
+
+    // doubled braces are literal
+    let job = market_job(b"https://x.io/{{{m}}}", b"{{}}");
+    job.validate_template().unwrap();
+    let tasks = job.render_tasks(&[job_param(b"a-b_1"), job_param(b"x")]).unwrap();
+    assert_eq!(tasks, market_job(b"https://x.io/{a-b_1}", b"{}").tasks);
+  }
+
+  #[test]
+  fn test_render_http_post_template() {
+    let mut job = market_job(b"https://x.io", b"");
+    job.tasks[0] = Task::http_post(b"https://x.io/graphql", br#"{{"query":"{{market(name:\"{m}\")}}"}}"#, ContentType::Json).unwrap();
+    job.validate_template().unwrap();
+    let tasks = job.render_tasks(&[job_param(b"BTC"), job_param(b"p")]).unwrap();
+    assert_eq!(
+      tasks[0],
+      Task::http_post(b"https://x.io/graphql", br#"{"query":"{market(name:\"BTC\")}"}"#, ContentType::Json).unwrap()
+    );
+
+    // the rendered body must still fit the Task
+    let long_arg = [b'a'; JOB_PARAM_LEN];
+    job.tasks[0] = Task::http_post(b"https://x.io", &[b'{', b'm', b'}'].repeat(26), ContentType::PlainText).unwrap();
+    assert_eq!(job.render_tasks(&[long_arg, job_param(b"p")]), Err(OracleError::InvalidJobArgument));
+  }
+
+  #[test]
+  fn test_invalid_job_template() {
+    for (url, path) in [
+      (&b"https://x.io/{m"[..], &b""[..]),
+      (b"https://x.io/m}", b""),
+      (b"https://x.io/{}", b""),
+      (b"https://x.io/{market}", b""),
+      (b"https://x.io/", b"{f.x}"),
+    ] {
+      assert_eq!(market_job(url, path).validate_template(), Err(OracleError::InvalidJobTemplate));
+    }
+
+    let mut job = market_job(b"https://x.io/{m}", b"");
+    job.params = [job_param(b"m"), job_param(b"m")];
+    assert_eq!(job.validate_template(), Err(OracleError::InvalidJobTemplate));
+    job.params = [job_param(b"m"), job_param(b"a/b")];
+    assert_eq!(job.validate_template(), Err(OracleError::InvalidJobTemplate));
+    job.params = [job_param(b"m"), [0u8; JOB_PARAM_LEN]];
+    job.validate_template().unwrap();
+  }
+
+  #[test]
+  fn test_invalid_job_arguments() {
+    let job = market_job(b"https://ftx.us/api/markets/{m}/USD", b"result.{f}");
+    let mut hidden = job_param(b"ETH");
+    hidden[5] = b'x';
+    for args in [
+      // characters that would change the url or path structure
+      [job_param(b"ETH/BTC"), job_param(b"price")],
+      [job_param(b"ETH?a=1"), job_param(b"price")],
+      [job_param(b"ETH"), job_param(b"price.x")],
+      [job_param(b"{m}"), job_param(b"price")],
+      // every declared parameter needs an argument
+      [job_param(b"ETH"), [0u8; JOB_PARAM_LEN]],
+      // bytes after the padding
+      [hidden, job_param(b"price")],
+      // the url outgrows its Task
+      [job_param(b"ETHBULL"), job_param(b"price")],
+    ] {
+      assert_eq!(job.render_tasks(&args), Err(OracleError::InvalidJobArgument));
+    }
+
+    // no arguments for undeclared parameters
+    let mut job = market_job(b"https://ftx.us/api/markets/{m}/USD", b"result.price");
+    job.params[1] = [0u8; JOB_PARAM_LEN];
+    assert_eq!(job.render_tasks(&[job_param(b"ETH"), job_param(b"x")]), Err(OracleError::InvalidJobArgument));
+    job.render_tasks(&[job_param(b"ETH"), [0u8; JOB_PARAM_LEN]]).unwrap();
+  }
+}
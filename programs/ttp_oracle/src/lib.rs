@@ -1,8 +1,15 @@
+pub mod answer_cache;
+pub mod error;
+pub mod fee_schedule;
+pub mod feed;
 pub mod instruction;
+pub mod job_spec;
+pub mod node;
 pub mod oracle_account;
 pub mod processor;
 pub mod request;
 pub mod response;
+pub mod token;
 
 const PUBLIC_KEY_LEN: usize = 32;
 
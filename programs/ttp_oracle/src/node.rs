@@ -0,0 +1,102 @@
+use crate::PUBLIC_KEY_LEN;
+use solana_program::{
+  clock::Slot,
+  program_error::ProgramError,
+  program_pack::{ IsInitialized, Pack, Sealed },
+  pubkey::Pubkey,
+};
+use arrayref::{ array_mut_ref, array_ref, array_refs, mut_array_refs };
+
+/// Seed for the program address of a node's registry account
+pub const NODE_SEED: &[u8] = b"node";
+
+/// An operator registered with an Oracle. The account is a program address
+/// derived from the Oracle and the node authority, and holds the node's
+/// stake as lamports on top of its rent exempt balance.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NodeAccount {
+  pub is_initialized: bool,
+  pub oracle: Pubkey,
+  // The operator's signing key
+  pub authority: Pubkey,
+  // Lamports at risk of being slashed
+  pub stake: u64,
+  // Slot the node deregistered at, 0 while the node is registered
+  pub deregistered_slot: Slot,
+}
+
+impl NodeAccount {
+  /// Whether the node is registered with at least `min_stake` at risk
+  pub fn is_active(&self, min_stake: u64) -> bool {
+    self.is_initialized && self.deregistered_slot == 0 && self.stake >= min_stake
+  }
+}
+
+/// Program address of the registry account for `authority` on `oracle_id`
+pub fn find_node_address(oracle_program_id: &Pubkey, oracle_id: &Pubkey, authority: &Pubkey) -> (Pubkey, u8) {
+  Pubkey::find_program_address(&[oracle_id.as_ref(), authority.as_ref(), NODE_SEED], oracle_program_id)
+}
+
+impl Sealed for NodeAccount {}
+impl IsInitialized for NodeAccount {
+  fn is_initialized(&self) -> bool {
+    self.is_initialized
+  }
+}
+impl Pack for NodeAccount {
+  const LEN: usize = 1 + PUBLIC_KEY_LEN + PUBLIC_KEY_LEN + 8 + 8;
+
+  fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+    let src = array_ref![src, 0, NodeAccount::LEN];
+    let (is_initialized, oracle, authority, stake, deregistered_slot) =
+      array_refs![src, 1, PUBLIC_KEY_LEN, PUBLIC_KEY_LEN, 8, 8];
+    let is_initialized = match is_initialized {
+      [0] => false,
+      [1] => true,
+      _ => return Err(ProgramError::InvalidAccountData),
+    };
+    Ok(NodeAccount {
+      is_initialized,
+      oracle: Pubkey::new(oracle),
+      authority: Pubkey::new(authority),
+      stake: u64::from_le_bytes(*stake),
+      deregistered_slot: u64::from_le_bytes(*deregistered_slot),
+    })
+  }
+
+  fn pack_into_slice(&self, dst: &mut [u8]) {
+    let dst = array_mut_ref![dst, 0, NodeAccount::LEN];
+    let (is_initialized, oracle, authority, stake, deregistered_slot) =
+      mut_array_refs![dst, 1, PUBLIC_KEY_LEN, PUBLIC_KEY_LEN, 8, 8];
+    is_initialized[0] = self.is_initialized as u8;
+    *oracle = self.oracle.to_bytes();
+    *authority = self.authority.to_bytes();
+    *stake = self.stake.to_le_bytes();
+    *deregistered_slot = self.deregistered_slot.to_le_bytes();
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_pack_unpack_node_account() {
+    let node = NodeAccount {
+      is_initialized: true,
+      oracle: Pubkey::new_unique(),
+      authority: Pubkey::new_unique(),
+      stake: 1_000_000,
+      deregistered_slot: 0,
+    };
+    let mut buffer = [0u8; NodeAccount::LEN];
+    NodeAccount::pack(node.clone(), &mut buffer).unwrap();
+    assert_eq!(NodeAccount::unpack(&buffer).unwrap(), node);
+    assert!(node.is_active(1_000_000));
+    assert!(!node.is_active(1_000_001));
+
+    let deregistered_node = NodeAccount { deregistered_slot: 10, ..node };
+    assert!(!deregistered_node.is_active(0));
+    assert_eq!(NodeAccount::unpack(&[0u8; NodeAccount::LEN]), Err(ProgramError::UninitializedAccount));
+  }
+}
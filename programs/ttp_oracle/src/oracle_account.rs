@@ -1,35 +1,508 @@
 use crate::{
-  request::{ RequestQueue }
+  PUBLIC_KEY_LEN,
+  answer_cache::AnswerCache,
+  fee_schedule::{ FeeSchedule, MAX_HOST_LEN },
+  request::{
+    is_slot_occupied,
+    set_slot_occupied,
+    Header,
+    Request,
+    RequestQueue,
+    Task,
+    MAX_REQUEST_QUEUE_SIZE,
+    OCCUPANCY_BITMAP_LEN,
+    REQUEST_QUEUE_SIZE,
+  }
 };
 use solana_program::{
   program_error::ProgramError,
   program_pack::{ IsInitialized, Pack, Sealed },
+  pubkey::Pubkey,
 };
+use arrayref::{ array_mut_ref, array_ref, array_refs, mut_array_refs };
+
+/// Seed for the program address of an Oracle account created by CreateOracle
+pub const ORACLE_SEED: &[u8] = b"oracle";
+/// Size of the seed an admin picks to tell its Oracles apart
+pub const ORACLE_SEED_LEN: usize = 32;
+/// Most secrets an Oracle advertises
+pub const MAX_ORACLE_SECRETS: usize = 4;
+/// Size of a zero padded secret name
+pub const SECRET_NAME_LEN: usize = 16;
+
+/// Secrets an Oracle's node holds
+pub type OracleSecrets = [OracleSecret; MAX_ORACLE_SECRETS];
+
+/// Whether `name` is a zero padded secret name, e.g. `coingecko_pro`, or
+/// all zero. Names are ASCII alphanumerics, `-` and `_`.
+pub fn is_valid_secret_name(name: &[u8; SECRET_NAME_LEN]) -> bool {
+  let len = name.iter().position(|b| *b == 0).unwrap_or(SECRET_NAME_LEN);
+  name[..len].iter().all(|b| b.is_ascii_alphanumeric() || *b == b'-' || *b == b'_')
+    && name[len..].iter().all(|b| *b == 0)
+}
+
+/// A secret the Oracle's node holds and the only host Tasks may send it to,
+/// so a Request can't hand the secret to a server of its choosing. An all
+/// zero name marks an unused entry.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct OracleSecret {
+  // Zero padded name, e.g. "coingecko_pro"
+  pub name: [u8; SECRET_NAME_LEN],
+  // Zero padded host name, e.g. "pro-api.coingecko.com"
+  pub host: [u8; MAX_HOST_LEN],
+}
+
+impl OracleSecret {
+  pub fn new(name: &[u8], host: &[u8]) -> Result<Self, ProgramError> {
+    if name.is_empty() || name.len() > SECRET_NAME_LEN || host.is_empty() || host.len() > MAX_HOST_LEN {
+      return Err(ProgramError::InvalidArgument);
+    }
+    let mut secret = OracleSecret::default();
+    secret.name[..name.len()].copy_from_slice(name);
+    secret.host[..host.len()].copy_from_slice(host);
+    Ok(secret)
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.name == [0u8; SECRET_NAME_LEN]
+  }
+
+  /// Whether the entry is unused and all zero, or has a valid name and a
+  /// zero padded host
+  pub fn is_valid(&self) -> bool {
+    let host_len = self.host.iter().position(|b| *b == 0).unwrap_or(MAX_HOST_LEN);
+    if self.is_empty() {
+      return host_len == 0 && self.host == [0u8; MAX_HOST_LEN];
+    }
+    is_valid_secret_name(&self.name) && host_len > 0 && self.host[host_len..].iter().all(|b| *b == 0)
+  }
+
+  /// Whether a Task requesting `host` may refer to the secret `name`
+  fn allows(&self, name: &[u8], host: &[u8]) -> bool {
+    let name_len = self.name.iter().position(|b| *b == 0).unwrap_or(SECRET_NAME_LEN);
+    let host_len = self.host.iter().position(|b| *b == 0).unwrap_or(MAX_HOST_LEN);
+    !self.is_empty() && &self.name[..name_len] == name && &self.host[..host_len] == host
+  }
+}
+
+impl Sealed for OracleSecret {}
+impl Pack for OracleSecret {
+  const LEN: usize = SECRET_NAME_LEN + MAX_HOST_LEN;
+
+  fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+    let src = array_ref![src, 0, OracleSecret::LEN];
+    let (name, host) = array_refs![src, SECRET_NAME_LEN, MAX_HOST_LEN];
+    Ok(OracleSecret {
+      name: *name,
+      host: *host,
+    })
+  }
+
+  fn pack_into_slice(&self, dst: &mut [u8]) {
+    let dst = array_mut_ref![dst, 0, OracleSecret::LEN];
+    let (name, host) = mut_array_refs![dst, SECRET_NAME_LEN, MAX_HOST_LEN];
+    *name = self.name;
+    *host = self.host;
+  }
+}
+
+/// Program address of the Oracle `admin` created with `seed`
+pub fn find_oracle_address(oracle_program_id: &Pubkey, admin: &Pubkey, seed: &[u8; ORACLE_SEED_LEN]) -> (Pubkey, u8) {
+  Pubkey::find_program_address(&[admin.as_ref(), seed, ORACLE_SEED], oracle_program_id)
+}
+
+/// Configuration stored at the start of every Oracle account, ahead of
+/// the RequestQueue.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OracleHeader {
+  pub is_initialized: bool,
+  // The account allowed to configure the Oracle
+  pub admin: Pubkey,
+  // The node that answers Requests and receives their fees
+  pub node_authority: Pubkey,
+  // Base fee of every Request, in lamports or in the smallest unit of
+  // `fee_mint`
+  pub request_fee: u64,
+  // Slots after which an unanswered Request may be cancelled and refunded
+  pub request_expiry_slots: u64,
+  // SPL Token mint fees are paid in, the default Pubkey for lamports
+  pub fee_mint: Pubkey,
+  // Token account holding escrowed token fees, owned by the vault authority
+  pub fee_vault: Pubkey,
+  // Bump seed of the program address that owns `fee_vault`
+  pub vault_authority_bump: u8,
+  // Price of each Task, charged on top of `request_fee`
+  pub fee_schedule: FeeSchedule,
+  // Lamports a node must lock to register as an operator
+  pub min_stake: u64,
+  // Slots a deregistered node waits before its stake can be withdrawn
+  pub unstake_cooldown_slots: u64,
+  // Lamports posted by anyone disputing a proposed answer
+  pub dispute_bond: u64,
+  // Admin proposed by TransferAdmin, the default Pubkey when none is pending
+  pub pending_admin: Pubkey,
+  // Longest URL, in bytes, an HttpGet or HttpPost Task may request
+  pub max_url_len: u8,
+  // Bitmask of the `Task::kind`s Requests may run
+  pub allowed_task_kinds: u8,
+  // Number of node answers required to settle a Request, always 1 until
+  // answers are aggregated
+  pub quorum: u8,
+  // Set by the admin to stop the Oracle taking Requests and Responses
+  pub is_paused: bool,
+  // Queue slots a single requester may hold at once, 0 for no limit
+  pub max_requests_per_requester: u8,
+  // Number of Request slots in use, at most what the account length fits
+  pub capacity: u16,
+  // Id given to the next Request, so nodes can answer in arrival order
+  pub next_request_id: u64,
+  // Latest delivered answers, served to Requests accepting an older answer
+  pub answer_cache: AnswerCache,
+  // Secrets Tasks may refer to with a `secret:<name>` header value, each
+  // only in Tasks requesting its host
+  pub supported_secrets: OracleSecrets,
+}
+
+impl OracleHeader {
+  /// Whether Request fees are paid in an SPL Token instead of lamports
+  pub fn charges_token_fee(&self) -> bool {
+    self.fee_mint != Pubkey::default()
+  }
+
+  /// Whether the Oracle's configuration lets a Request run `task`
+  pub fn allows_task(&self, task: &Task) -> bool {
+    if self.allowed_task_kinds & (1 << task.kind()) == 0 {
+      return false;
+    }
+    task.url_len().map_or(true, |len| len <= self.max_url_len as usize)
+      && task.headers().iter().all(Header::is_allowed)
+  }
+
+  /// Whether the Oracle's node holds every secret `task` refers to, each
+  /// for the host `task` requests
+  pub fn supports_secrets(&self, task: &Task) -> bool {
+    let mut names = task.headers().iter().filter_map(Header::secret_name);
+    match task.host() {
+      Some(host) => names.all(|name| self.supported_secrets.iter().any(|secret| secret.allows(name, host))),
+      // secrets are only sent to the host of an https url
+      None => names.next().is_none(),
+    }
+  }
+
+  /// Minimum fee for a Request running `tasks`, None on overflow
+  pub fn fee_for(&self, tasks: &[Task]) -> Option<u64> {
+    self.fee_schedule.tasks_fee(tasks)?.checked_add(self.request_fee)
+  }
+
+  /// Whether a requester already holding `pending` Requests may add another
+  pub fn allows_another_request(&self, pending: usize) -> bool {
+    self.max_requests_per_requester == 0 || pending < self.max_requests_per_requester as usize
+  }
+}
+
+impl Sealed for OracleHeader {}
+impl IsInitialized for OracleHeader {
+  fn is_initialized(&self) -> bool {
+    self.is_initialized
+  }
+}
+impl Pack for OracleHeader {
+  const LEN: usize = 1 + PUBLIC_KEY_LEN + PUBLIC_KEY_LEN + 8 + 8 + PUBLIC_KEY_LEN + PUBLIC_KEY_LEN + 1
+    + FeeSchedule::LEN + 8 + 8 + 8 + PUBLIC_KEY_LEN + 1 + 1 + 1 + 1 + 1 + 2 + 8 + AnswerCache::LEN
+    + OracleSecret::LEN * MAX_ORACLE_SECRETS;
+
+  fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+    let src = array_ref![src, 0, OracleHeader::LEN];
+    let (
+      is_initialized,
+      admin,
+      node_authority,
+      request_fee,
+      request_expiry_slots,
+      fee_mint,
+      fee_vault,
+      vault_authority_bump,
+      fee_schedule,
+      min_stake,
+      unstake_cooldown_slots,
+      dispute_bond,
+      pending_admin,
+      max_url_len,
+      allowed_task_kinds,
+      quorum,
+      is_paused,
+      max_requests_per_requester,
+      capacity,
+      next_request_id,
+      answer_cache,
+      supported_secrets,
+    ) = array_refs![src, 1, PUBLIC_KEY_LEN, PUBLIC_KEY_LEN, 8, 8, PUBLIC_KEY_LEN, PUBLIC_KEY_LEN, 1, FeeSchedule::LEN, 8, 8, 8,
+      PUBLIC_KEY_LEN, 1, 1, 1, 1, 1, 2, 8, AnswerCache::LEN, OracleSecret::LEN * MAX_ORACLE_SECRETS];
+    let mut secrets = OracleSecrets::default();
+    for (i, secret) in secrets.iter_mut().enumerate() {
+      *secret = OracleSecret::unpack_from_slice(array_ref![supported_secrets, i * OracleSecret::LEN, OracleSecret::LEN])?;
+    }
+    let is_initialized = match is_initialized {
+      [0] => false,
+      [1] => true,
+      _ => return Err(ProgramError::InvalidAccountData),
+    };
+    let is_paused = match is_paused {
+      [0] => false,
+      [1] => true,
+      _ => return Err(ProgramError::InvalidAccountData),
+    };
+    Ok(OracleHeader {
+      is_initialized,
+      admin: Pubkey::new(admin),
+      node_authority: Pubkey::new(node_authority),
+      request_fee: u64::from_le_bytes(*request_fee),
+      request_expiry_slots: u64::from_le_bytes(*request_expiry_slots),
+      fee_mint: Pubkey::new(fee_mint),
+      fee_vault: Pubkey::new(fee_vault),
+      vault_authority_bump: vault_authority_bump[0],
+      fee_schedule: FeeSchedule::unpack_from_slice(fee_schedule)?,
+      min_stake: u64::from_le_bytes(*min_stake),
+      unstake_cooldown_slots: u64::from_le_bytes(*unstake_cooldown_slots),
+      dispute_bond: u64::from_le_bytes(*dispute_bond),
+      pending_admin: Pubkey::new(pending_admin),
+      max_url_len: max_url_len[0],
+      allowed_task_kinds: allowed_task_kinds[0],
+      quorum: quorum[0],
+      is_paused,
+      max_requests_per_requester: max_requests_per_requester[0],
+      capacity: u16::from_le_bytes(*capacity),
+      next_request_id: u64::from_le_bytes(*next_request_id),
+      answer_cache: AnswerCache::unpack_from_slice(answer_cache)?,
+      supported_secrets: secrets,
+    })
+  }
+
+  fn pack_into_slice(&self, dst: &mut [u8]) {
+    let dst = array_mut_ref![dst, 0, OracleHeader::LEN];
+    let (
+      is_initialized,
+      admin,
+      node_authority,
+      request_fee,
+      request_expiry_slots,
+      fee_mint,
+      fee_vault,
+      vault_authority_bump,
+      fee_schedule,
+      min_stake,
+      unstake_cooldown_slots,
+      dispute_bond,
+      pending_admin,
+      max_url_len,
+      allowed_task_kinds,
+      quorum,
+      is_paused,
+      max_requests_per_requester,
+      capacity,
+      next_request_id,
+      answer_cache,
+      supported_secrets,
+    ) = mut_array_refs![dst, 1, PUBLIC_KEY_LEN, PUBLIC_KEY_LEN, 8, 8, PUBLIC_KEY_LEN, PUBLIC_KEY_LEN, 1, FeeSchedule::LEN, 8, 8, 8,
+      PUBLIC_KEY_LEN, 1, 1, 1, 1, 1, 2, 8, AnswerCache::LEN, OracleSecret::LEN * MAX_ORACLE_SECRETS];
+    is_initialized[0] = self.is_initialized as u8;
+    *admin = self.admin.to_bytes();
+    *node_authority = self.node_authority.to_bytes();
+    *request_fee = self.request_fee.to_le_bytes();
+    *request_expiry_slots = self.request_expiry_slots.to_le_bytes();
+    *fee_mint = self.fee_mint.to_bytes();
+    *fee_vault = self.fee_vault.to_bytes();
+    vault_authority_bump[0] = self.vault_authority_bump;
+    self.fee_schedule.pack_into_slice(fee_schedule);
+    *min_stake = self.min_stake.to_le_bytes();
+    *unstake_cooldown_slots = self.unstake_cooldown_slots.to_le_bytes();
+    *dispute_bond = self.dispute_bond.to_le_bytes();
+    *pending_admin = self.pending_admin.to_bytes();
+    max_url_len[0] = self.max_url_len;
+    allowed_task_kinds[0] = self.allowed_task_kinds;
+    quorum[0] = self.quorum;
+    is_paused[0] = self.is_paused as u8;
+    max_requests_per_requester[0] = self.max_requests_per_requester;
+    *capacity = self.capacity.to_le_bytes();
+    *next_request_id = self.next_request_id.to_le_bytes();
+    self.answer_cache.pack_into_slice(answer_cache);
+    for (i, secret) in self.supported_secrets.iter().enumerate() {
+      secret.pack_into_slice(array_mut_ref![supported_secrets, i * OracleSecret::LEN, OracleSecret::LEN]);
+    }
+  }
+}
 
 /// Struct representing the entire data buffer stored for each Oracle.
 #[derive(Debug, PartialEq)]
 pub struct OracleAccount {
+  pub header: OracleHeader,
   pub request_queue: RequestQueue,
 }
 
-impl Sealed for OracleAccount {}
-impl IsInitialized for OracleAccount {
-  fn is_initialized(&self) -> bool {
-      true
+impl OracleAccount {
+  /// Length of an Oracle account with the default capacity
+  pub const LEN: usize = OracleAccount::get_packed_len(REQUEST_QUEUE_SIZE);
+
+  /// Length of an Oracle account holding `capacity` Request slots
+  pub const fn get_packed_len(capacity: usize) -> usize {
+    OracleHeader::LEN + RequestQueue::get_packed_len(capacity)
   }
-}
-impl Pack for OracleAccount {
-  const LEN: usize = RequestQueue::LEN;
 
-  fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+  /// Request slots that fit in an account of `data_len` bytes, None when
+  /// not even one does
+  pub fn capacity_for(data_len: usize) -> Option<usize> {
+    let capacity = data_len.checked_sub(OracleAccount::get_packed_len(0))? / Request::LEN;
+    if capacity == 0 {
+      return None;
+    }
+    Some(capacity.min(MAX_REQUEST_QUEUE_SIZE))
+  }
+
+  /// Byte offset of the Request slot at `index` within the account data
+  pub fn request_offset(index: usize) -> usize {
+    OracleAccount::get_packed_len(index)
+  }
+
+  /// Number of queued Requests made by `requester`
+  pub fn pending_requests_of(&self, requester: &Pubkey) -> usize {
+    self.request_queue.requests.iter().flatten().filter(|request| request.requester == *requester).count()
+  }
+
+  /// Unpack an initialized Oracle account, reading as many Request slots
+  /// as its header's capacity
+  pub fn unpack(src: &[u8]) -> Result<Self, ProgramError> {
+    let oracle_account = Self::unpack_unchecked(src)?;
+    if !oracle_account.is_initialized() {
+      return Err(ProgramError::UninitializedAccount);
+    }
+    Ok(oracle_account)
+  }
+
+  pub fn unpack_unchecked(src: &[u8]) -> Result<Self, ProgramError> {
+    let header = OracleHeader::unpack_unchecked(src.get(0..OracleHeader::LEN).ok_or(ProgramError::InvalidAccountData)?)?;
+    let request_queue = src.get(OracleHeader::LEN..OracleAccount::get_packed_len(header.capacity as usize))
+      .ok_or(ProgramError::InvalidAccountData)?;
     Ok(OracleAccount {
-      request_queue: RequestQueue::unpack(src)?
+      header,
+      request_queue: RequestQueue::unpack_from_slice(request_queue)?
     })
   }
 
-  fn pack_into_slice(&self, dst: &mut [u8]) {
-    let queue = self.request_queue.clone();
-    RequestQueue::pack(queue, dst).unwrap();
+  pub fn pack(src: Self, dst: &mut [u8]) -> Result<(), ProgramError> {
+    let len = OracleAccount::get_packed_len(src.request_queue.requests.len());
+    let dst = dst.get_mut(0..len).ok_or(ProgramError::InvalidAccountData)?;
+    let (header, request_queue) = dst.split_at_mut(OracleHeader::LEN);
+    src.header.pack_into_slice(header);
+    src.request_queue.pack_into_slice(request_queue);
+    Ok(())
+  }
+}
+
+/// Zero-copy view of an Oracle account's data. Single Request slots are
+/// read and written in place, so the cost of an instruction doesn't grow
+/// with the capacity of the queue.
+pub struct OracleAccountView<D> {
+  data: D,
+  header: OracleHeader,
+}
+
+impl<D: AsRef<[u8]>> OracleAccountView<D> {
+  /// View an initialized Oracle account, checking it fits its capacity
+  pub fn new(data: D) -> Result<Self, ProgramError> {
+    let header = OracleHeader::unpack(data.as_ref().get(0..OracleHeader::LEN).ok_or(ProgramError::InvalidAccountData)?)?;
+    if data.as_ref().len() < OracleAccount::get_packed_len(header.capacity as usize) {
+      return Err(ProgramError::InvalidAccountData);
+    }
+    Ok(OracleAccountView { data, header })
+  }
+
+  pub fn header(&self) -> &OracleHeader {
+    &self.header
+  }
+
+  pub fn capacity(&self) -> usize {
+    self.header.capacity as usize
+  }
+
+  fn occupancy_bitmap(&self) -> &[u8; OCCUPANCY_BITMAP_LEN] {
+    array_ref![self.data.as_ref(), OracleHeader::LEN, OCCUPANCY_BITMAP_LEN]
+  }
+
+  /// Whether the slot at `index` holds a Request, only the bitmap is read
+  pub fn is_occupied(&self, index: usize) -> bool {
+    index < self.capacity() && is_slot_occupied(self.occupancy_bitmap(), index)
+  }
+
+  /// Occupancy of the queue, one entry per slot
+  pub fn occupancy(&self) -> impl Iterator<Item = bool> + '_ {
+    (0..self.capacity()).map(move |index| self.is_occupied(index))
+  }
+
+  pub fn empty_slots(&self) -> impl Iterator<Item = usize> + '_ {
+    (0..self.capacity()).filter(move |index| !self.is_occupied(*index))
+  }
+
+  pub fn first_empty_slot(&self) -> Option<usize> {
+    self.empty_slots().next()
+  }
+
+  fn slot(&self, index: usize) -> &[u8; Request::LEN] {
+    array_ref![self.data.as_ref(), OracleAccount::request_offset(index), Request::LEN]
+  }
+
+  /// Unpack only the Request at `index`, None when the slot is empty or out
+  /// of range
+  pub fn request(&self, index: usize) -> Result<Option<Request>, ProgramError> {
+    if !self.is_occupied(index) {
+      return Ok(None);
+    }
+    Request::unpack(self.slot(index)).map(Some)
+  }
+
+  /// Number of queued Requests made by `requester`, reading only their
+  /// requester field
+  pub fn pending_requests_of(&self, requester: &Pubkey) -> usize {
+    (0..self.capacity())
+      .filter(|index| self.is_occupied(*index))
+      .filter(|index| array_ref![self.slot(*index), Request::REQUESTER_OFFSET, PUBLIC_KEY_LEN] == requester.as_ref())
+      .count()
+  }
+}
+
+impl<D: AsRef<[u8]> + AsMut<[u8]>> OracleAccountView<D> {
+  fn set_occupied(&mut self, index: usize, is_occupied: bool) -> Result<(), ProgramError> {
+    if index >= self.capacity() {
+      return Err(ProgramError::InvalidArgument);
+    }
+    let bitmap = array_mut_ref![self.data.as_mut(), OracleHeader::LEN, OCCUPANCY_BITMAP_LEN];
+    set_slot_occupied(bitmap, index, is_occupied);
+    Ok(())
+  }
+
+  pub fn write_header(&mut self, header: OracleHeader) {
+    header.pack_into_slice(&mut self.data.as_mut()[0..OracleHeader::LEN]);
+    self.header = header;
+  }
+
+  /// Write `request` to the slot at its index
+  pub fn write_request(&mut self, request: Request) -> Result<(), ProgramError> {
+    let index = request.index as usize;
+    self.set_occupied(index, true)?;
+    Request::pack(request, array_mut_ref![self.data.as_mut(), OracleAccount::request_offset(index), Request::LEN])
+  }
+
+  /// Mark the slot at `index` empty and zero it out so it can be reused
+  pub fn clear_request(&mut self, index: usize) -> Result<(), ProgramError> {
+    self.set_occupied(index, false)?;
+    array_mut_ref![self.data.as_mut(), OracleAccount::request_offset(index), Request::LEN].copy_from_slice(&[0u8; Request::LEN]);
+    Ok(())
+  }
+}
+
+impl Sealed for OracleAccount {}
+impl IsInitialized for OracleAccount {
+  fn is_initialized(&self) -> bool {
+    self.header.is_initialized
   }
 }
 
@@ -37,16 +510,11 @@ impl Pack for OracleAccount {
 mod tests {
   use super::*;
   use crate::{
-    PUBLIC_KEY_LEN,
-    request::{ GetArgs, GetParams, JsonParseArgs, Request, Task }
+    request::{ ContentType, GetArgs, GetParams, JsonParseArgs, OptimisticResponse, Task, ALL_TASK_KINDS }
   };
-  use arrayref::{array_mut_ref, mut_array_refs};
   use generic_array::{
     GenericArray,
   };
-  use solana_program::{
-    pubkey::Pubkey,
-  };
 
   fn create_sample_request() -> Request {
     let url_bytes = b"https://ftx.us/api/markets/BTC/USD";
@@ -57,9 +525,7 @@ mod tests {
     let params = GetParams {
       get: *GenericArray::from_slice(url_bytes)
     };
-    let args = GetArgs {
-      params: params
-    };
+    let args = GetArgs::new(params);
     let get_task = Task::HttpGet(args);
     let json_parse_task = Task::JsonParse(json_args);
     let uint_128_task = Task::Uint32;
@@ -68,6 +534,49 @@ mod tests {
       tasks: [get_task, json_parse_task, uint_128_task],
       call_back_program: Pubkey::new(&[4u8; PUBLIC_KEY_LEN]),
       index: 0,
+      requester: Pubkey::default(),
+      fee: 0,
+      created_slot: 0,
+      id: 0,
+      tip: 0,
+      max_answer_age_slots: 0,
+      job_id: 0,
+      optimistic: OptimisticResponse::default(),
+    }
+  }
+
+  fn create_sample_header() -> OracleHeader {
+    OracleHeader {
+      is_initialized: true,
+      admin: Pubkey::new(&[5u8; PUBLIC_KEY_LEN]),
+      node_authority: Pubkey::new(&[6u8; PUBLIC_KEY_LEN]),
+      request_fee: 5_000,
+      request_expiry_slots: 300,
+      fee_mint: Pubkey::new(&[7u8; PUBLIC_KEY_LEN]),
+      fee_vault: Pubkey::new(&[8u8; PUBLIC_KEY_LEN]),
+      vault_authority_bump: 254,
+      fee_schedule: FeeSchedule {
+        task_fees: [1_000, 10, 1, 2_000],
+        ..FeeSchedule::default()
+      },
+      min_stake: 1_000_000,
+      unstake_cooldown_slots: 1_000,
+      dispute_bond: 100_000,
+      pending_admin: Pubkey::default(),
+      max_url_len: 34,
+      allowed_task_kinds: ALL_TASK_KINDS,
+      quorum: 1,
+      is_paused: false,
+      max_requests_per_requester: 0,
+      capacity: REQUEST_QUEUE_SIZE as u16,
+      next_request_id: 0,
+      answer_cache: AnswerCache::default(),
+      supported_secrets: [
+        OracleSecret::new(b"coingecko_pro", b"pro-api.coingecko.com").unwrap(),
+        OracleSecret::default(),
+        OracleSecret::default(),
+        OracleSecret::default(),
+      ],
     }
   }
 
@@ -75,14 +584,18 @@ mod tests {
   fn test_oracle_account_pack_unpack() {
     let request = create_sample_request();
     let request_queue = RequestQueue {
-      requests: Box::new([Some(request), None, None, None, None, None, None, None, None, None]),
+      requests: vec![Some(request), None, None, None, None, None, None, None, None, None],
     };
     let oracle_account = OracleAccount {
+      header: create_sample_header(),
       request_queue,
     };
     let mut expected_oracle_account_buffer = [0u8; OracleAccount::LEN];
     let oracle_account_buffer = array_mut_ref![expected_oracle_account_buffer, 0, OracleAccount::LEN];
-    let (first_request, _rest) = mut_array_refs![oracle_account_buffer, Request::LEN, RequestQueue::LEN - Request::LEN];
+    let (header, bitmap, first_request, _rest) =
+      mut_array_refs![oracle_account_buffer, OracleHeader::LEN, OCCUPANCY_BITMAP_LEN, Request::LEN, Request::LEN * (REQUEST_QUEUE_SIZE - 1)];
+    bitmap[0] = 0b1;
+    OracleHeader::pack(create_sample_header(), header).unwrap();
     let request = create_sample_request();
     Request::pack(request, first_request).unwrap();
 
@@ -92,13 +605,168 @@ mod tests {
     
     let request = create_sample_request();
     let request_queue = RequestQueue {
-      requests: Box::new([Some(request), None, None, None, None, None, None, None, None, None]),
+      requests: vec![Some(request), None, None, None, None, None, None, None, None, None],
     };
     let expected_oracle_account = OracleAccount {
+      header: create_sample_header(),
       request_queue,
     };
 
     let oracle_account = OracleAccount::unpack(&oracle_account_buffer).unwrap();
     assert_eq!(oracle_account, expected_oracle_account);
   }
+
+  #[test]
+  fn test_header_fee_for() {
+    let header = create_sample_header();
+    let tasks = create_sample_request().tasks;
+    assert_eq!(header.fee_for(&tasks), Some(5_000 + 1_000 + 10 + 1));
+
+    let mut header = create_sample_header();
+    header.request_fee = u64::MAX;
+    assert_eq!(header.fee_for(&tasks), None);
+  }
+
+  #[test]
+  fn test_header_allows_task() {
+    let tasks = create_sample_request().tasks;
+    let mut header = create_sample_header();
+    assert!(tasks.iter().all(|task| header.allows_task(task)));
+
+    header.max_url_len = 33;
+    assert!(!header.allows_task(&tasks[0]));
+    assert!(header.allows_task(&tasks[1]));
+
+    header.max_url_len = 34;
+    header.allowed_task_kinds = ALL_TASK_KINDS & !(1 << tasks[2].kind());
+    assert!(header.allows_task(&tasks[0]));
+    assert!(!header.allows_task(&tasks[2]));
+
+    // HttpPost urls are held to the same limit, which may exceed what an
+    // HttpGet url can hold
+    let post_task = Task::http_post(b"https://cloudflare-eth.com", b"{}", ContentType::Json).unwrap();
+    assert!(header.allows_task(&post_task));
+    header.max_url_len = 25;
+    assert!(!header.allows_task(&post_task));
+    let url = b"https://mainnet.infura.io/v3/0123456789abcdef0123456789abcdef";
+    let post_task = Task::http_post(url, b"{}", ContentType::Json).unwrap();
+    header.max_url_len = Task::MAX_URL_LEN as u8;
+    assert!(header.allows_task(&post_task));
+
+    // Tasks may not send denied headers
+    header.max_url_len = 34;
+    let get_task = tasks[0].clone().with_headers(vec![Header::new(b"Accept", b"application/json")]).unwrap();
+    assert!(header.allows_task(&get_task));
+    let get_task = tasks[0].clone().with_headers(vec![Header::new(b"Host", b"ftx.com")]).unwrap();
+    assert!(!header.allows_task(&get_task));
+  }
+
+  #[test]
+  fn test_header_supports_secrets() {
+    let header = create_sample_header();
+    let tasks = create_sample_request().tasks;
+    assert!(tasks.iter().all(|task| header.supports_secrets(task)));
+
+    let coingecko_task = Task::HttpGet(GetArgs::new(GetParams {
+      get: *GenericArray::from_slice(b"https://pro-api.coingecko.com/ping"),
+    }));
+    let with_key = |task: &Task, value: &[u8]| task.clone().with_headers(vec![Header::new(b"X-Api-Key", value)]).unwrap();
+    assert!(header.supports_secrets(&with_key(&coingecko_task, b"secret:coingecko_pro")));
+    assert!(!header.supports_secrets(&with_key(&coingecko_task, b"secret:coingecko")));
+    assert!(!header.supports_secrets(&with_key(&coingecko_task, b"secret:coingecko_pro_2")));
+    // plain values are not secret references
+    assert!(header.supports_secrets(&with_key(&tasks[0], b"coingecko_pro")));
+    // a supported secret may not be sent to any other host
+    assert!(!header.supports_secrets(&with_key(&tasks[0], b"secret:coingecko_pro")));
+    let post_task = Task::http_post(b"https://pro-api.coingecko.com.evil.io/", b"{}", ContentType::Json).unwrap();
+    assert!(!header.supports_secrets(&with_key(&post_task, b"secret:coingecko_pro")));
+    // nor to a url an HTTP client reads as another host than the Oracle
+    let post_task = Task::http_post(b"https:/evil.io/://pro-api.coingecko.com/x", b"{}", ContentType::Json).unwrap();
+    assert!(!header.supports_secrets(&with_key(&post_task, b"secret:coingecko_pro")));
+
+    assert!(header.supported_secrets.iter().all(OracleSecret::is_valid));
+    assert!(is_valid_secret_name(&[0u8; SECRET_NAME_LEN]));
+    assert!(!is_valid_secret_name(b"coingecko pro\0\0\0"));
+    assert!(!is_valid_secret_name(b"coingecko\0pro\0\0\0"));
+    let mut secret = OracleSecret::new(b"coingecko_pro", b"pro-api.coingecko.com").unwrap();
+    secret.host = [0u8; MAX_HOST_LEN];
+    assert!(!secret.is_valid());
+    // unused entries have no host either
+    let host = OracleSecret::new(b"ftx", b"ftx.us").unwrap().host;
+    assert!(!OracleSecret { host, ..OracleSecret::default() }.is_valid());
+    assert!(OracleSecret::default().is_valid());
+  }
+
+  #[test]
+  fn test_header_allows_another_request() {
+    let mut header = create_sample_header();
+    assert!(header.allows_another_request(REQUEST_QUEUE_SIZE));
+
+    header.max_requests_per_requester = 2;
+    assert!(header.allows_another_request(1));
+    assert!(!header.allows_another_request(2));
+  }
+
+  #[test]
+  fn test_oracle_account_capacity() {
+    assert_eq!(OracleAccount::capacity_for(OracleAccount::LEN), Some(REQUEST_QUEUE_SIZE));
+    assert_eq!(OracleAccount::capacity_for(OracleAccount::LEN + Request::LEN - 1), Some(REQUEST_QUEUE_SIZE));
+    assert_eq!(OracleAccount::capacity_for(OracleAccount::get_packed_len(1) - 1), None);
+    assert_eq!(OracleAccount::capacity_for(OracleAccount::get_packed_len(1000)), Some(MAX_REQUEST_QUEUE_SIZE));
+
+    // only the slots within the header's capacity are read
+    let mut oracle_account_buffer = vec![0u8; OracleAccount::get_packed_len(3)];
+    let header = OracleHeader { capacity: 2, ..create_sample_header() };
+    OracleAccount::pack(OracleAccount {
+      header: header.clone(),
+      request_queue: RequestQueue { requests: vec![None, Some(create_sample_request())] },
+    }, &mut oracle_account_buffer).unwrap();
+    let oracle_account = OracleAccount::unpack(&oracle_account_buffer).unwrap();
+    assert_eq!(oracle_account.request_queue.requests, vec![None, Some(create_sample_request())]);
+
+    let header = OracleHeader { capacity: 4, ..header };
+    OracleHeader::pack(header, &mut oracle_account_buffer[0..OracleHeader::LEN]).unwrap();
+    assert_eq!(OracleAccount::unpack(&oracle_account_buffer), Err(ProgramError::InvalidAccountData));
+  }
+
+  #[test]
+  fn test_oracle_account_view() {
+    let mut oracle_account_buffer = vec![0u8; OracleAccount::get_packed_len(3)];
+    let mut request = create_sample_request();
+    request.index = 1;
+    OracleAccount::pack(OracleAccount {
+      header: OracleHeader { capacity: 3, ..create_sample_header() },
+      request_queue: RequestQueue { requests: vec![None, Some(request.clone()), None] },
+    }, &mut oracle_account_buffer).unwrap();
+
+    let mut oracle_account = OracleAccountView::new(&mut oracle_account_buffer[..]).unwrap();
+    assert_eq!(oracle_account.occupancy().collect::<Vec<_>>(), vec![false, true, false]);
+    assert_eq!(oracle_account.first_empty_slot(), Some(0));
+    assert_eq!(oracle_account.empty_slots().collect::<Vec<_>>(), vec![0, 2]);
+    assert_eq!(oracle_account.request(1).unwrap(), Some(request.clone()));
+    assert_eq!(oracle_account.request(0).unwrap(), None);
+    assert_eq!(oracle_account.request(3).unwrap(), None);
+    assert_eq!(oracle_account.pending_requests_of(&request.requester), 1);
+    assert_eq!(oracle_account.pending_requests_of(&Pubkey::new(&[9u8; PUBLIC_KEY_LEN])), 0);
+
+    request.index = 0;
+    oracle_account.write_request(request.clone()).unwrap();
+    oracle_account.clear_request(1).unwrap();
+    assert_eq!(oracle_account.occupancy().collect::<Vec<_>>(), vec![true, false, false]);
+    request.index = 3;
+    assert_eq!(oracle_account.write_request(request), Err(ProgramError::InvalidArgument));
+
+    // the view agrees with unpacking the whole account
+    let oracle_data = OracleAccount::unpack(&oracle_account_buffer).unwrap();
+    assert_eq!(oracle_data.request_queue.requests, vec![Some(create_sample_request()), None, None]);
+    assert_eq!(OracleAccountView::new(&oracle_account_buffer[0..OracleAccount::get_packed_len(2)]).err(), Some(ProgramError::InvalidAccountData));
+  }
+
+  #[test]
+  fn test_unpack_uninitialized_oracle_account() {
+    let oracle_account_buffer = [0u8; OracleAccount::LEN];
+    assert_eq!(OracleAccount::unpack(&oracle_account_buffer), Err(ProgramError::UninitializedAccount));
+    let oracle_account = OracleAccount::unpack_unchecked(&oracle_account_buffer).unwrap();
+    assert!(!oracle_account.header.is_initialized);
+  }
 }
@@ -1,71 +1,1500 @@
+use std::format;
 use crate::{
+  answer_cache::AnswerCache,
+  error::OracleError,
+  fee_schedule::FeeSchedule,
+  feed::Feed,
   instruction::{ OracleInstruction },
-  request::{ Request, REQUEST_QUEUE_SIZE },
-  response::Response,
-  oracle_account::OracleAccount
+  job_spec::{ find_job_address, JobParams, JobSpec, JOB_NAME_LEN, JOB_SEED },
+  node::{ find_node_address, NodeAccount, NODE_SEED },
+  request::{
+    GetParams,
+    OptimisticResponse,
+    Request,
+    RequestIndex,
+    ResponseStatus,
+    Task,
+    ALL_TASK_KINDS,
+    MAX_REQUEST_QUEUE_SIZE,
+    TASK_ARRAY_SIZE,
+  },
+  response::{ Response, ResponseData },
+  oracle_account::{
+    find_oracle_address,
+    OracleAccount,
+    OracleAccountView,
+    OracleHeader,
+    OracleSecret,
+    OracleSecrets,
+    ORACLE_SEED,
+    ORACLE_SEED_LEN,
+  },
+  token::{ self, find_vault_authority, spl_token, TokenAccount, VAULT_AUTHORITY_SEED },
 };
 use solana_program::{
   account_info::{ next_account_info, AccountInfo },
+  clock::Clock,
+  hash::HASH_BYTES,
+  rent::Rent,
   entrypoint::ProgramResult,
   instruction::Instruction,
+  program_error::ProgramError,
   program_pack::Pack,
-  program::invoke,
+  program::{ invoke, invoke_signed },
   pubkey::Pubkey,
+  system_instruction,
+  sysvar::Sysvar,
+  info,
 };
 use arrayref::{ array_mut_ref };
 
 pub const CALLBACK_DETERMINANT: u8 = 255;
 pub const CALLBACK_DETERMINANT_SIZE: usize = 1;
+// Accounts Oracles charging an SPL Token take to pay out a fee
+const TOKEN_FEE_ACCOUNTS_LEN: usize = 4;
 
 pub struct Processor {}
 impl Processor {
   /// process instructions
-  pub fn process(_program_id: &Pubkey, accounts: &[AccountInfo], input: &[u8]) -> ProgramResult {
+  pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], input: &[u8]) -> ProgramResult {
     let instruction = OracleInstruction::unpack(input)?;
 
+    // a paused Oracle neither takes new Requests nor delivers answers
+    if let OracleInstruction::CreateRequest { .. }
+      | OracleInstruction::CreateRequests { .. }
+      | OracleInstruction::CreateJobRequest { .. }
+      | OracleInstruction::HandleResponse(_)
+      | OracleInstruction::HandleResponses { .. }
+      | OracleInstruction::ServeCachedResponse { .. }
+      | OracleInstruction::FinalizeResponse { .. } = instruction {
+      Self::check_not_paused(program_id, accounts)?;
+    }
+
     match instruction {
-      OracleInstruction::CreateRequest { request } => Self::process_create_request(accounts, request),
-      OracleInstruction::HandleResponse(response) => Self::process_handle_response(accounts, response),
+      OracleInstruction::CreateRequest { request } => Self::process_create_request(program_id, accounts, request),
+      OracleInstruction::HandleResponse(response) => Self::process_handle_response(program_id, accounts, response),
+      OracleInstruction::InitializeFeed {
+        tasks,
+        deviation_threshold_bps,
+        heartbeat_slots,
+        max_change_bps,
+      } => Self::process_initialize_feed(program_id, accounts, tasks, deviation_threshold_bps, heartbeat_slots, max_change_bps),
+      OracleInstruction::UpdateFeed { value } => Self::process_update_feed(program_id, accounts, value),
+      OracleInstruction::InitializeOracle {
+        node_authority,
+        request_fee,
+        request_expiry_slots,
+        fee_mint,
+      } => Self::process_initialize_oracle(program_id, accounts, node_authority, request_fee, request_expiry_slots, fee_mint),
+      OracleInstruction::CancelRequest { request_queue_index } => {
+        Self::process_cancel_request(program_id, accounts, request_queue_index)
+      },
+      OracleInstruction::SetFeeSchedule { fee_schedule } => {
+        Self::process_set_fee_schedule(program_id, accounts, fee_schedule)
+      },
+      OracleInstruction::RegisterNode { stake } => Self::process_register_node(program_id, accounts, stake),
+      OracleInstruction::DeregisterNode => Self::process_deregister_node(program_id, accounts),
+      OracleInstruction::Unstake => Self::process_unstake(program_id, accounts),
+      OracleInstruction::SlashNode { amount } => Self::process_slash_node(program_id, accounts, amount),
+      OracleInstruction::SetStakeConfig {
+        min_stake,
+        unstake_cooldown_slots,
+        dispute_bond,
+      } => Self::process_set_stake_config(program_id, accounts, min_stake, unstake_cooldown_slots, dispute_bond),
+      OracleInstruction::Dispute { request_queue_index } => Self::process_dispute(program_id, accounts, request_queue_index),
+      OracleInstruction::FinalizeResponse { request_queue_index } => {
+        Self::process_finalize_response(program_id, accounts, request_queue_index)
+      },
+      OracleInstruction::ResolveDispute {
+        request_queue_index,
+        upheld,
+        data,
+      } => Self::process_resolve_dispute(program_id, accounts, request_queue_index, upheld, data),
+      OracleInstruction::SetConfig {
+        request_fee,
+        request_expiry_slots,
+        max_url_len,
+        allowed_task_kinds,
+        quorum,
+      } => Self::process_set_config(program_id, accounts, request_fee, request_expiry_slots, max_url_len, allowed_task_kinds, quorum),
+      OracleInstruction::SetNodeAuthority { node_authority } => {
+        Self::process_set_node_authority(program_id, accounts, node_authority)
+      },
+      OracleInstruction::TransferAdmin { new_admin } => Self::process_transfer_admin(program_id, accounts, new_admin),
+      OracleInstruction::AcceptAdmin => Self::process_accept_admin(program_id, accounts),
+      OracleInstruction::Pause => Self::process_set_paused(program_id, accounts, true),
+      OracleInstruction::Unpause => Self::process_set_paused(program_id, accounts, false),
+      OracleInstruction::ResetFeed { value } => Self::process_reset_feed(program_id, accounts, value),
+      OracleInstruction::CloseOracle { force } => Self::process_close_oracle(program_id, accounts, force),
+      OracleInstruction::CreateOracle {
+        seed,
+        node_authority,
+        request_fee,
+        request_expiry_slots,
+        fee_mint,
+        capacity,
+      } => Self::process_create_oracle(program_id, accounts, seed, node_authority, request_fee, request_expiry_slots, fee_mint, capacity),
+      OracleInstruction::SetRequesterLimit { max_requests_per_requester } =>
+        Self::process_set_requester_limit(program_id, accounts, max_requests_per_requester),
+      OracleInstruction::ResizeOracle { capacity } => Self::process_resize_oracle(program_id, accounts, capacity),
+      OracleInstruction::SetSecrets { secrets } => Self::process_set_secrets(program_id, accounts, secrets),
+      OracleInstruction::HandleResponses { responses, atomic } =>
+        Self::process_handle_responses(program_id, accounts, responses, atomic),
+      OracleInstruction::CreateRequests { requests } => Self::process_create_requests(program_id, accounts, requests),
+      OracleInstruction::ServeCachedResponse { request_queue_index } =>
+        Self::process_serve_cached_response(program_id, accounts, request_queue_index),
+      OracleInstruction::RegisterJob { job_id, name, tasks, params } =>
+        Self::process_register_job(program_id, accounts, job_id, name, tasks, params),
+      OracleInstruction::CreateJobRequest {
+        job_id,
+        call_back_program,
+        fee,
+        tip,
+        dispute_window_slots,
+        max_answer_age_slots,
+        args,
+      } => Self::process_create_job_request(
+        program_id,
+        accounts,
+        job_id,
+        call_back_program,
+        fee,
+        tip,
+        dispute_window_slots,
+        max_answer_age_slots,
+        args,
+      ),
     }
   }
 
   /**
-   * Find and insert Request in the first open slot on the OracleAccount's RequestQueue
+   * Find and insert Request in the first open slot on the OracleAccount's RequestQueue.
+   * The fee offered on the Request must cover the Oracle's price for its tasks.
+   * It is moved from the payer into the Oracle account, or into its fee vault
+   * for token fees, together with the optional tip, where both are held until
+   * the Request is answered or cancelled.
    */
-  pub fn process_create_request(accounts: &[AccountInfo], mut request: Request) -> ProgramResult {
+  pub fn process_create_request(program_id: &Pubkey, accounts: &[AccountInfo], mut request: Request) -> ProgramResult {
+    request.job_id = 0;
+    Self::enqueue_requests(program_id, accounts, vec![request]).map(|_| ())
+  }
+
+  /**
+   * Insert several Requests at once, none of them is created unless the
+   * queue has room for all. The ids assigned to them are logged in order.
+   */
+  pub fn process_create_requests(program_id: &Pubkey, accounts: &[AccountInfo], mut requests: Vec<Request>) -> ProgramResult {
+    for request in requests.iter_mut() {
+      request.job_id = 0;
+    }
+    let ids = Self::enqueue_requests(program_id, accounts, requests)?;
+    info!(&format!("Created Requests {:?}", ids));
+    Ok(())
+  }
+
+  /// Store a task pipeline in a new JobSpec account, paid for by the admin
+  pub fn process_register_job(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    job_id: u64,
+    name: [u8; JOB_NAME_LEN],
+    tasks: [Task; TASK_ARRAY_SIZE],
+    params: JobParams,
+  ) -> ProgramResult {
     let accounts_iter = &mut accounts.iter();
     let oracle_account = next_account_info(accounts_iter)?;
-    
+    let job_account = next_account_info(accounts_iter)?;
+    let admin = next_account_info(accounts_iter)?;
+    let system_program_account = next_account_info(accounts_iter)?;
+    let rent = Rent::from_account_info(next_account_info(accounts_iter)?)?;
+
+    if oracle_account.owner != program_id {
+      return Err(ProgramError::IncorrectProgramId);
+    }
+    if !admin.is_signer {
+      return Err(ProgramError::MissingRequiredSignature);
+    }
+    let header = OracleHeader::unpack(&oracle_account.data.borrow()[0..OracleHeader::LEN])?;
+    if header.admin != *admin.key {
+      return Err(OracleError::IncorrectAuthority.into());
+    }
+    if job_id == 0 {
+      return Err(ProgramError::InvalidArgument);
+    }
+    if !tasks.iter().all(|task| header.allows_task(task)) {
+      return Err(OracleError::TaskNotAllowed.into());
+    }
+    if !tasks.iter().all(|task| header.supports_secrets(task)) {
+      return Err(OracleError::UnknownSecret.into());
+    }
+    let job = JobSpec {
+      is_initialized: true,
+      oracle: *oracle_account.key,
+      job_id,
+      name,
+      tasks,
+      params,
+    };
+    job.validate_template()?;
+    let (job_address, bump) = find_job_address(program_id, oracle_account.key, job_id);
+    if job_address != *job_account.key {
+      return Err(ProgramError::InvalidSeeds);
+    }
+
+    invoke_signed(
+      &system_instruction::create_account(admin.key, job_account.key, rent.minimum_balance(JobSpec::LEN), JobSpec::LEN as u64, program_id),
+      &[admin.clone(), job_account.clone(), system_program_account.clone()],
+      &[&[oracle_account.key.as_ref(), &job_id.to_le_bytes(), JOB_SEED, &[bump]]],
+    )?;
+
+    JobSpec::pack(job, &mut job_account.data.borrow_mut())
+  }
+
+  /// Create a Request running the Tasks of the JobSpec `job_id` with `args`
+  /// substituted for its parameters
+  #[allow(clippy::too_many_arguments)]
+  pub fn process_create_job_request(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    job_id: u64,
+    call_back_program: Pubkey,
+    fee: u64,
+    tip: u64,
+    dispute_window_slots: u64,
+    max_answer_age_slots: u64,
+    args: JobParams,
+  ) -> ProgramResult {
+    let oracle_account = accounts.first().ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let job_account = accounts.get(4).ok_or(ProgramError::NotEnoughAccountKeys)?;
+    if job_account.owner != program_id {
+      return Err(ProgramError::IncorrectProgramId);
+    }
+    let job = JobSpec::unpack(&job_account.data.borrow())?;
+    if job.oracle != *oracle_account.key || job.job_id != job_id {
+      return Err(ProgramError::InvalidArgument);
+    }
+
+    let request = Request {
+      tasks: job.render_tasks(&args)?,
+      call_back_program,
+      index: 0,
+      requester: Pubkey::default(),
+      fee,
+      created_slot: 0,
+      id: 0,
+      tip,
+      max_answer_age_slots,
+      job_id,
+      optimistic: OptimisticResponse {
+        dispute_window_slots,
+        ..OptimisticResponse::default()
+      },
+    };
+    // CreateRequest takes the token accounts right after the Clock sysvar
+    let mut request_accounts = accounts[..4].to_vec();
+    request_accounts.extend(accounts[5..].iter().cloned());
+    Self::enqueue_requests(program_id, &request_accounts, vec![request]).map(|_| ())
+  }
+
+  /// Escrow the fees of `requests` and write them to open slots, returning their ids
+  fn enqueue_requests(program_id: &Pubkey, accounts: &[AccountInfo], requests: Vec<Request>) -> Result<Vec<u64>, ProgramError> {
+    let accounts_iter = &mut accounts.iter();
+    let oracle_account = next_account_info(accounts_iter)?;
+    let payer = next_account_info(accounts_iter)?;
+    let system_program_account = next_account_info(accounts_iter)?;
+    let clock = Clock::from_account_info(next_account_info(accounts_iter)?)?;
+
+    if oracle_account.owner != program_id {
+      return Err(ProgramError::IncorrectProgramId);
+    }
+    if !payer.is_signer {
+      return Err(ProgramError::MissingRequiredSignature);
+    }
+    if requests.is_empty() {
+      return Err(ProgramError::InvalidArgument);
+    }
+
+    let (header, indexes) = {
+      let account_data = oracle_account.data.borrow();
+      let oracle_view = OracleAccountView::new(&account_data[..])?;
+      // find the first empty requests
+      let indexes: Vec<usize> = oracle_view.empty_slots().take(requests.len()).collect();
+      if indexes.len() < requests.len() {
+        return Err(OracleError::RequestQueueFull.into());
+      }
+      let pending = oracle_view.pending_requests_of(payer.key);
+      if !oracle_view.header().allows_another_request(pending + requests.len() - 1) {
+        return Err(OracleError::RequesterLimitReached.into());
+      }
+      (oracle_view.header().clone(), indexes)
+    };
+
+    let mut fee: u64 = 0;
+    for request in &requests {
+      if !request.tasks.iter().all(|task| header.allows_task(task)) {
+        return Err(OracleError::TaskNotAllowed.into());
+      }
+      if !request.tasks.iter().all(|task| header.supports_secrets(task)) {
+        return Err(OracleError::UnknownSecret.into());
+      }
+      let price = header.fee_for(&request.tasks).ok_or(ProgramError::InvalidArgument)?;
+      if request.fee < price {
+        return Err(OracleError::InsufficientFee.into());
+      }
+      fee = fee.checked_add(request.fee)
+        .and_then(|fee| fee.checked_add(request.tip))
+        .ok_or(ProgramError::InvalidArgument)?;
+    }
+    if header.charges_token_fee() {
+      let payer_token_account = next_account_info(accounts_iter)?;
+      let fee_vault = next_account_info(accounts_iter)?;
+      let token_program = next_account_info(accounts_iter)?;
+      if *fee_vault.key != header.fee_vault {
+        return Err(OracleError::IncorrectFeeVault.into());
+      }
+      if !spl_token::check_id(token_program.key) {
+        return Err(ProgramError::IncorrectProgramId);
+      }
+      if fee > 0 {
+        invoke(
+          &token::transfer(payer_token_account.key, fee_vault.key, payer.key, fee),
+          &[payer_token_account.clone(), fee_vault.clone(), payer.clone(), token_program.clone()],
+        )?;
+      }
+    } else if fee > 0 {
+      invoke(
+        &system_instruction::transfer(payer.key, oracle_account.key, fee),
+        &[payer.clone(), oracle_account.clone(), system_program_account.clone()],
+      )?;
+    }
+
+    let mut account_data = oracle_account.data.borrow_mut();
+    let mut oracle_view = OracleAccountView::new(&mut account_data[..])?;
+    let mut next_request_id = header.next_request_id;
+    let mut ids = Vec::with_capacity(requests.len());
+    for (mut request, index) in requests.into_iter().zip(indexes) {
+      request.index = index as u8;
+      request.requester = *payer.key;
+      request.created_slot = clock.slot;
+      request.id = next_request_id;
+      // only the dispute window is chosen by the requester
+      request.optimistic = OptimisticResponse {
+        dispute_window_slots: request.optimistic.dispute_window_slots,
+        ..OptimisticResponse::default()
+      };
+      next_request_id = next_request_id.wrapping_add(1);
+      ids.push(request.id);
+      oracle_view.write_request(request)?;
+    }
+    oracle_view.write_header(OracleHeader { next_request_id, ..header });
+    Ok(ids)
+  }
+
+  /**
+   * Convert the response data into data bufer to be sent to the Caller Program.
+   * The Request's escrowed fee and tip are paid out to the node authority.
+   * Requests in optimistic mode only record the answer as a proposal.
+   * Other pending Requests for the same Tasks are answered along when the
+   * program receiving their callback is passed.
+   */
+  pub fn process_handle_response(program_id: &Pubkey, accounts: &[AccountInfo], response: Response) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let oracle_account = next_account_info(accounts_iter)?;
+    let client_program_account = next_account_info(accounts_iter)?;
+    let node_authority = next_account_info(accounts_iter)?;
+    let clock = Clock::from_account_info(next_account_info(accounts_iter)?)?;
+    let node_account = next_account_info(accounts_iter)?;
+
+    if oracle_account.owner != program_id {
+      return Err(ProgramError::IncorrectProgramId);
+    }
+    if !node_authority.is_signer {
+      return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let (header, request) = Self::unpack_request(oracle_account, response.request_queue_index)?;
+    if header.node_authority != *node_authority.key {
+      return Err(OracleError::IncorrectAuthority.into());
+    }
+    Self::check_active_node(program_id, oracle_account, &header, node_account)?;
+    Self::check_response(&request, client_program_account)?;
+
+    let remaining_accounts = accounts_iter.as_slice();
+    let (token_fee_accounts, duplicate_call_back_accounts) = if header.charges_token_fee() {
+      remaining_accounts.split_at(TOKEN_FEE_ACCOUNTS_LEN.min(remaining_accounts.len()))
+    } else {
+      remaining_accounts.split_at(0)
+    };
+    let is_optimistic = request.optimistic.is_optimistic();
+    let task_hash = request.task_hash();
+    let data = response.data;
+    Self::answer_request(
+      oracle_account,
+      &header,
+      client_program_account,
+      node_authority,
+      &mut token_fee_accounts.iter(),
+      &clock,
+      request,
+      response,
+    )?;
+    if is_optimistic || duplicate_call_back_accounts.is_empty() {
+      return Ok(());
+    }
+
+    for duplicate in Self::pending_duplicates(oracle_account, &task_hash)? {
+      let call_back_account = duplicate_call_back_accounts.iter()
+        .find(|account| *account.key == duplicate.call_back_program);
+      if let Some(call_back_account) = call_back_account {
+        let response = Response { data, request_queue_index: duplicate.index };
+        Self::deliver_response(
+          oracle_account,
+          &header,
+          call_back_account,
+          node_authority,
+          &mut token_fee_accounts.iter(),
+          duplicate.escrow(),
+          response,
+        )?;
+      }
+    }
+    Ok(())
+  }
+
+  /**
+   * Answer a Request with a cached answer for its Tasks no older than the
+   * requester accepts. The node is paid as if it had run the Tasks.
+   */
+  pub fn process_serve_cached_response(program_id: &Pubkey, accounts: &[AccountInfo], request_queue_index: RequestIndex) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let oracle_account = next_account_info(accounts_iter)?;
+    let client_program_account = next_account_info(accounts_iter)?;
+    let node_authority = next_account_info(accounts_iter)?;
+    let clock = Clock::from_account_info(next_account_info(accounts_iter)?)?;
+    let node_account = next_account_info(accounts_iter)?;
+
+    if oracle_account.owner != program_id {
+      return Err(ProgramError::IncorrectProgramId);
+    }
+    if !node_authority.is_signer {
+      return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let (header, request) = Self::unpack_request(oracle_account, request_queue_index)?;
+    if header.node_authority != *node_authority.key {
+      return Err(OracleError::IncorrectAuthority.into());
+    }
+    Self::check_active_node(program_id, oracle_account, &header, node_account)?;
+    Self::check_response(&request, client_program_account)?;
+    // a cached answer can not be disputed
+    if request.optimistic.is_optimistic() {
+      return Err(OracleError::InvalidResponseStatus.into());
+    }
+    if request.max_answer_age_slots == 0 {
+      return Err(OracleError::NoCachedAnswer.into());
+    }
+    let min_slot = clock.slot.saturating_sub(request.max_answer_age_slots);
+    let data = header.answer_cache.lookup(&request.task_hash(), min_slot).ok_or(OracleError::NoCachedAnswer)?;
+
+    let response = Response { data, request_queue_index };
+    Self::deliver_response(oracle_account, &header, client_program_account, node_authority, accounts_iter, request.escrow(), response)
+  }
+
+  /**
+   * Answer a batch of Requests, each Response with its own callback program.
+   * Responses that fail their checks abort the batch when `atomic` is set and
+   * are skipped otherwise. A callback that fails aborts the transaction
+   * either way, as the runtime does not let a program recover from it.
+   */
+  pub fn process_handle_responses(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    responses: Vec<Response>,
+    atomic: bool,
+  ) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let oracle_account = next_account_info(accounts_iter)?;
+    let node_authority = next_account_info(accounts_iter)?;
+    let clock = Clock::from_account_info(next_account_info(accounts_iter)?)?;
+    let node_account = next_account_info(accounts_iter)?;
+    let client_program_accounts = (0..responses.len())
+      .map(|_| next_account_info(accounts_iter))
+      .collect::<Result<Vec<_>, _>>()?;
+    // the token fee accounts are shared by every payout
+    let token_fee_accounts = accounts_iter.as_slice();
+
+    if oracle_account.owner != program_id {
+      return Err(ProgramError::IncorrectProgramId);
+    }
+    if !node_authority.is_signer {
+      return Err(ProgramError::MissingRequiredSignature);
+    }
+    let header = {
+      let account_data = oracle_account.data.borrow();
+      OracleAccountView::new(&account_data[..])?.header().clone()
+    };
+    if header.node_authority != *node_authority.key {
+      return Err(OracleError::IncorrectAuthority.into());
+    }
+    Self::check_active_node(program_id, oracle_account, &header, node_account)?;
+
+    for (response, client_program_account) in responses.into_iter().zip(client_program_accounts) {
+      let index = response.request_queue_index;
+      // slots answered earlier in the batch are already empty
+      let checked = Self::unpack_request(oracle_account, index)
+        .and_then(|(_, request)| Self::check_response(&request, client_program_account).map(|_| request));
+      let request = match checked {
+        Ok(request) => request,
+        Err(error) if !atomic => {
+          info!(&format!("Skipping Response for Request {}: {}", index, error));
+          continue;
+        },
+        Err(error) => return Err(error),
+      };
+      Self::answer_request(
+        oracle_account,
+        &header,
+        client_program_account,
+        node_authority,
+        &mut token_fee_accounts.iter(),
+        &clock,
+        request,
+        response,
+      )?;
+    }
+    Ok(())
+  }
+
+  /// Challenge a proposed answer, escrowing the dispute bond in the Oracle account
+  pub fn process_dispute(program_id: &Pubkey, accounts: &[AccountInfo], request_queue_index: RequestIndex) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let oracle_account = next_account_info(accounts_iter)?;
+    let disputer = next_account_info(accounts_iter)?;
+    let system_program_account = next_account_info(accounts_iter)?;
+    let clock = Clock::from_account_info(next_account_info(accounts_iter)?)?;
+
+    if oracle_account.owner != program_id {
+      return Err(ProgramError::IncorrectProgramId);
+    }
+    if !disputer.is_signer {
+      return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let (header, mut request) = Self::unpack_request(oracle_account, request_queue_index)?;
+    if request.optimistic.status != ResponseStatus::Proposed {
+      return Err(OracleError::InvalidResponseStatus.into());
+    }
+    if !request.optimistic.in_dispute_window(clock.slot) {
+      return Err(OracleError::DisputeWindowClosed.into());
+    }
+
+    if header.dispute_bond > 0 {
+      invoke(
+        &system_instruction::transfer(disputer.key, oracle_account.key, header.dispute_bond),
+        &[disputer.clone(), oracle_account.clone(), system_program_account.clone()],
+      )?;
+    }
+    request.optimistic.status = ResponseStatus::Disputed;
+    request.optimistic.disputer = *disputer.key;
+    request.optimistic.dispute_bond = header.dispute_bond;
+    Self::pack_request(oracle_account, request)
+  }
+
+  /// Deliver a proposed answer nobody disputed before its window closed
+  pub fn process_finalize_response(program_id: &Pubkey, accounts: &[AccountInfo], request_queue_index: RequestIndex) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let oracle_account = next_account_info(accounts_iter)?;
+    let client_program_account = next_account_info(accounts_iter)?;
+    let node_authority = next_account_info(accounts_iter)?;
+    let clock = Clock::from_account_info(next_account_info(accounts_iter)?)?;
+
+    if oracle_account.owner != program_id {
+      return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let (header, request) = Self::unpack_request(oracle_account, request_queue_index)?;
+    if header.node_authority != *node_authority.key {
+      return Err(OracleError::IncorrectAuthority.into());
+    }
+    if request.call_back_program != *client_program_account.key {
+      return Err(OracleError::IncorrectCallbackProgram.into());
+    }
+    if request.optimistic.status != ResponseStatus::Proposed {
+      return Err(OracleError::InvalidResponseStatus.into());
+    }
+    if request.optimistic.in_dispute_window(clock.slot) {
+      return Err(OracleError::DisputeWindowOpen.into());
+    }
+
+    Self::cache_answer(oracle_account, &request, request.optimistic.data, request.optimistic.proposed_slot)?;
+    let response = Response {
+      data: request.optimistic.data,
+      request_queue_index,
+    };
+    Self::deliver_response(oracle_account, &header, client_program_account, node_authority, accounts_iter, request.escrow(), response)
+  }
+
+  /**
+   * Settle a dispute as the admin. An upheld dispute refunds the bond and the
+   * Request fee, slashes the node by the bond in favor of the disputer and
+   * delivers the admin's answer. A rejected dispute pays the bond and the fee
+   * to the node and delivers the proposed answer.
+   */
+  pub fn process_resolve_dispute(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    request_queue_index: RequestIndex,
+    upheld: bool,
+    data: ResponseData,
+  ) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let oracle_account = next_account_info(accounts_iter)?;
+    let client_program_account = next_account_info(accounts_iter)?;
+    let admin = next_account_info(accounts_iter)?;
+    let node_authority = next_account_info(accounts_iter)?;
+    let disputer = next_account_info(accounts_iter)?;
+    let requester = next_account_info(accounts_iter)?;
+    let node_account = next_account_info(accounts_iter)?;
+
+    if oracle_account.owner != program_id {
+      return Err(ProgramError::IncorrectProgramId);
+    }
+    if !admin.is_signer {
+      return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let (header, request) = Self::unpack_request(oracle_account, request_queue_index)?;
+    if header.admin != *admin.key || header.node_authority != *node_authority.key {
+      return Err(OracleError::IncorrectAuthority.into());
+    }
+    if request.call_back_program != *client_program_account.key {
+      return Err(OracleError::IncorrectCallbackProgram.into());
+    }
+    if request.optimistic.status != ResponseStatus::Disputed {
+      return Err(OracleError::InvalidResponseStatus.into());
+    }
+    if request.optimistic.disputer != *disputer.key || request.requester != *requester.key {
+      return Err(ProgramError::InvalidArgument);
+    }
+
+    if header.charges_token_fee() {
+      // the token account follows the fee vault and belongs to whoever is paid
+      let fee_recipient = if upheld { requester } else { node_authority };
+      let recipient_token_account = accounts_iter.clone().nth(1).ok_or(ProgramError::NotEnoughAccountKeys)?;
+      if TokenAccount::unpack(&recipient_token_account.data.borrow())?.owner != *fee_recipient.key {
+        return Err(OracleError::IncorrectAuthority.into());
+      }
+    }
+
+    if upheld {
+      if node_account.lamports() == 0 {
+        // the node already closed its account, the disputer only gets the bond back
+        if *node_account.key != find_node_address(program_id, oracle_account.key, node_authority.key).0 {
+          return Err(ProgramError::InvalidArgument);
+        }
+      } else if Self::unpack_node(program_id, oracle_account, node_account)?.authority != *node_authority.key {
+        return Err(OracleError::IncorrectAuthority.into());
+      } else {
+        Self::slash(program_id, oracle_account, node_account, disputer, request.optimistic.dispute_bond)?;
+      }
+      Self::transfer_lamports(oracle_account, disputer, request.optimistic.dispute_bond)?;
+      let response = Response { data, request_queue_index };
+      Self::deliver_response(oracle_account, &header, client_program_account, requester, accounts_iter, request.escrow(), response)
+    } else {
+      Self::transfer_lamports(oracle_account, node_authority, request.optimistic.dispute_bond)?;
+      let response = Response {
+        data: request.optimistic.data,
+        request_queue_index,
+      };
+      Self::deliver_response(oracle_account, &header, client_program_account, node_authority, accounts_iter, request.escrow(), response)
+    }
+  }
+
+  /**
+   * Write the header of a freshly allocated Oracle account. When fees are
+   * charged in an SPL Token the fee vault must hold `fee_mint` and be owned
+   * by the Oracle's vault authority so only the program can pay out of it.
+   */
+  pub fn process_initialize_oracle(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    node_authority: Pubkey,
+    request_fee: u64,
+    request_expiry_slots: u64,
+    fee_mint: Pubkey,
+  ) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let oracle_account = next_account_info(accounts_iter)?;
+    let admin = next_account_info(accounts_iter)?;
+
+    if oracle_account.owner != program_id {
+      return Err(ProgramError::IncorrectProgramId);
+    }
+    if !admin.is_signer {
+      return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut account_data = oracle_account.data.borrow_mut();
+    // every Request slot the account has room for is used
+    let capacity = OracleAccount::capacity_for(account_data.len()).ok_or(ProgramError::InvalidAccountData)?;
+    let header_data = array_mut_ref![account_data, 0, OracleHeader::LEN];
+    let header = OracleHeader::unpack_unchecked(header_data)?;
+    if header.is_initialized {
+      return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    let mut fee_vault = Pubkey::default();
+    let mut vault_authority_bump = 0;
+    if fee_mint != Pubkey::default() {
+      let fee_vault_account = next_account_info(accounts_iter)?;
+      let (vault_authority, bump) = find_vault_authority(program_id, oracle_account.key);
+      if !spl_token::check_id(fee_vault_account.owner) {
+        return Err(ProgramError::IncorrectProgramId);
+      }
+      let vault = TokenAccount::unpack(&fee_vault_account.data.borrow())?;
+      if vault.mint != fee_mint || vault.owner != vault_authority {
+        return Err(OracleError::IncorrectFeeVault.into());
+      }
+      fee_vault = *fee_vault_account.key;
+      vault_authority_bump = bump;
+    }
+
+    let header = OracleHeader {
+      is_initialized: true,
+      admin: *admin.key,
+      node_authority,
+      request_fee,
+      request_expiry_slots,
+      fee_mint,
+      fee_vault,
+      vault_authority_bump,
+      fee_schedule: FeeSchedule::default(),
+      min_stake: 0,
+      unstake_cooldown_slots: 0,
+      dispute_bond: 0,
+      pending_admin: Pubkey::default(),
+      max_url_len: GetParams::LEN as u8,
+      allowed_task_kinds: ALL_TASK_KINDS,
+      quorum: 1,
+      is_paused: false,
+      max_requests_per_requester: 0,
+      capacity: capacity as u16,
+      next_request_id: 0,
+      answer_cache: AnswerCache::default(),
+      supported_secrets: Default::default(),
+    };
+    OracleHeader::pack(header, header_data)
+  }
+
+  /**
+   * Create the Oracle account at the program address of the admin and
+   * `seed`, sized for `capacity` Requests and funded with its rent exempt
+   * balance, then initialize it.
+   */
+  #[allow(clippy::too_many_arguments)]
+  pub fn process_create_oracle(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    seed: [u8; ORACLE_SEED_LEN],
+    node_authority: Pubkey,
+    request_fee: u64,
+    request_expiry_slots: u64,
+    fee_mint: Pubkey,
+    capacity: u16,
+  ) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let oracle_account = next_account_info(accounts_iter)?;
+    let admin = next_account_info(accounts_iter)?;
+    let system_program_account = next_account_info(accounts_iter)?;
+    let rent = Rent::from_account_info(next_account_info(accounts_iter)?)?;
+
+    if !admin.is_signer {
+      return Err(ProgramError::MissingRequiredSignature);
+    }
+    let (oracle_id, bump) = find_oracle_address(program_id, admin.key, &seed);
+    if oracle_id != *oracle_account.key {
+      return Err(ProgramError::InvalidSeeds);
+    }
+    if capacity == 0 || capacity as usize > MAX_REQUEST_QUEUE_SIZE {
+      return Err(ProgramError::InvalidArgument);
+    }
+    let space = OracleAccount::get_packed_len(capacity as usize);
+
+    invoke_signed(
+      &system_instruction::create_account(
+        admin.key,
+        oracle_account.key,
+        rent.minimum_balance(space),
+        space as u64,
+        program_id,
+      ),
+      &[admin.clone(), oracle_account.clone(), system_program_account.clone()],
+      &[&[admin.key.as_ref(), &seed, ORACLE_SEED, &[bump]]],
+    )?;
+
+    // InitializeOracle takes the fee vault right after the admin
+    let mut initialize_accounts = vec![oracle_account.clone(), admin.clone()];
+    initialize_accounts.extend(accounts_iter.cloned());
+    Self::process_initialize_oracle(program_id, &initialize_accounts, node_authority, request_fee, request_expiry_slots, fee_mint)
+  }
+
+  /// Remove an expired Request from the queue and refund its fee and tip to the requester
+  pub fn process_cancel_request(program_id: &Pubkey, accounts: &[AccountInfo], request_queue_index: RequestIndex) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let oracle_account = next_account_info(accounts_iter)?;
+    let requester = next_account_info(accounts_iter)?;
+    let clock = Clock::from_account_info(next_account_info(accounts_iter)?)?;
+
+    if oracle_account.owner != program_id {
+      return Err(ProgramError::IncorrectProgramId);
+    }
+    if !requester.is_signer {
+      return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let (header, request) = Self::unpack_request(oracle_account, request_queue_index)?;
+    if request.requester != *requester.key {
+      return Err(OracleError::IncorrectAuthority.into());
+    }
+    // a proposed answer is settled by FinalizeResponse or ResolveDispute
+    if request.optimistic.status != ResponseStatus::Unanswered {
+      return Err(OracleError::InvalidResponseStatus.into());
+    }
+    if clock.slot < request.created_slot.saturating_add(header.request_expiry_slots) {
+      return Err(OracleError::RequestNotExpired.into());
+    }
+
+    Self::clear_request(oracle_account, request_queue_index as usize)?;
+    Self::release_fee(oracle_account, &header, requester, accounts_iter, request.escrow())
+  }
+
+  /// Replace the Oracle's per Task prices, only the admin may change them
+  pub fn process_set_fee_schedule(program_id: &Pubkey, accounts: &[AccountInfo], fee_schedule: FeeSchedule) -> ProgramResult {
+    Self::update_header(program_id, accounts, |header| {
+      header.fee_schedule = fee_schedule;
+      Ok(())
+    })
+  }
+
+  /**
+   * Create the node account at its program address, funded with the rent
+   * exempt balance plus `stake`, and record the node as an operator.
+   */
+  pub fn process_register_node(program_id: &Pubkey, accounts: &[AccountInfo], stake: u64) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let oracle_account = next_account_info(accounts_iter)?;
+    let node_account = next_account_info(accounts_iter)?;
+    let node_authority = next_account_info(accounts_iter)?;
+    let system_program_account = next_account_info(accounts_iter)?;
+    let rent = Rent::from_account_info(next_account_info(accounts_iter)?)?;
+
+    if oracle_account.owner != program_id {
+      return Err(ProgramError::IncorrectProgramId);
+    }
+    if !node_authority.is_signer {
+      return Err(ProgramError::MissingRequiredSignature);
+    }
+    let header = OracleHeader::unpack(&oracle_account.data.borrow()[0..OracleHeader::LEN])?;
+    if stake < header.min_stake {
+      return Err(OracleError::InsufficientStake.into());
+    }
+    let (node_id, bump) = find_node_address(program_id, oracle_account.key, node_authority.key);
+    if node_id != *node_account.key {
+      return Err(ProgramError::InvalidSeeds);
+    }
+
+    let lamports = rent.minimum_balance(NodeAccount::LEN).checked_add(stake).ok_or(ProgramError::InvalidArgument)?;
+    invoke_signed(
+      &system_instruction::create_account(node_authority.key, node_account.key, lamports, NodeAccount::LEN as u64, program_id),
+      &[node_authority.clone(), node_account.clone(), system_program_account.clone()],
+      &[&[oracle_account.key.as_ref(), node_authority.key.as_ref(), NODE_SEED, &[bump]]],
+    )?;
+
+    let node = NodeAccount {
+      is_initialized: true,
+      oracle: *oracle_account.key,
+      authority: *node_authority.key,
+      stake,
+      deregistered_slot: 0,
+    };
+    NodeAccount::pack(node, &mut node_account.data.borrow_mut())
+  }
+
+  /// Stop a node from operating and start its unstake cooldown
+  pub fn process_deregister_node(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let oracle_account = next_account_info(accounts_iter)?;
+    let node_account = next_account_info(accounts_iter)?;
+    let node_authority = next_account_info(accounts_iter)?;
+    let clock = Clock::from_account_info(next_account_info(accounts_iter)?)?;
+
+    let mut node = Self::unpack_node(program_id, oracle_account, node_account)?;
+    if !node_authority.is_signer {
+      return Err(ProgramError::MissingRequiredSignature);
+    }
+    if node.authority != *node_authority.key {
+      return Err(OracleError::IncorrectAuthority.into());
+    }
+    if node.deregistered_slot != 0 {
+      return Err(ProgramError::InvalidArgument);
+    }
+    Self::check_node_idle(oracle_account, &node)?;
+
+    // slot 0 marks a registered node
+    node.deregistered_slot = clock.slot.max(1);
+    NodeAccount::pack(node, &mut node_account.data.borrow_mut())
+  }
+
+  /**
+   * Return the stake and rent of a deregistered node once the cooldown
+   * elapsed. A closed Oracle has nothing left to answer or slash for, so its
+   * nodes are returned their stake right away.
+   */
+  pub fn process_unstake(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let oracle_account = next_account_info(accounts_iter)?;
+    let node_account = next_account_info(accounts_iter)?;
+    let node_authority = next_account_info(accounts_iter)?;
+    let clock = Clock::from_account_info(next_account_info(accounts_iter)?)?;
+
+    if node_account.owner != program_id {
+      return Err(ProgramError::IncorrectProgramId);
+    }
+    let node = NodeAccount::unpack(&node_account.data.borrow())?;
+    if node.oracle != *oracle_account.key {
+      return Err(ProgramError::InvalidAccountData);
+    }
+    if !node_authority.is_signer {
+      return Err(ProgramError::MissingRequiredSignature);
+    }
+    if node.authority != *node_authority.key {
+      return Err(OracleError::IncorrectAuthority.into());
+    }
+    if !Self::is_closed(program_id, oracle_account) {
+      let header = OracleHeader::unpack(&oracle_account.data.borrow()[0..OracleHeader::LEN])?;
+      if node.deregistered_slot == 0
+        || clock.slot < node.deregistered_slot.saturating_add(header.unstake_cooldown_slots) {
+        return Err(OracleError::StakeCooldownNotElapsed.into());
+      }
+      Self::check_node_idle(oracle_account, &node)?;
+    }
+
+    node_account.data.borrow_mut().copy_from_slice(&[0u8; NodeAccount::LEN]);
+    Self::transfer_lamports(node_account, node_authority, node_account.lamports())
+  }
+
+  /// Take part of a node's stake, only the admin may slash
+  pub fn process_slash_node(program_id: &Pubkey, accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let oracle_account = next_account_info(accounts_iter)?;
+    let node_account = next_account_info(accounts_iter)?;
+    let admin = next_account_info(accounts_iter)?;
+    let destination = next_account_info(accounts_iter)?;
+
+    if !admin.is_signer {
+      return Err(ProgramError::MissingRequiredSignature);
+    }
+    let header = OracleHeader::unpack(&oracle_account.data.borrow()[0..OracleHeader::LEN])?;
+    if header.admin != *admin.key {
+      return Err(OracleError::IncorrectAuthority.into());
+    }
+
+    Self::slash(program_id, oracle_account, node_account, destination, amount)
+  }
+
+  /// Update the stake a node must lock, the unstake cooldown and the dispute bond
+  pub fn process_set_stake_config(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    min_stake: u64,
+    unstake_cooldown_slots: u64,
+    dispute_bond: u64,
+  ) -> ProgramResult {
+    Self::update_header(program_id, accounts, |header| {
+      header.min_stake = min_stake;
+      header.unstake_cooldown_slots = unstake_cooldown_slots;
+      header.dispute_bond = dispute_bond;
+      Ok(())
+    })
+  }
+
+  /// Update the base request fee, the expiry and which Tasks Requests may run
+  pub fn process_set_config(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    request_fee: u64,
+    request_expiry_slots: u64,
+    max_url_len: u8,
+    allowed_task_kinds: u8,
+    quorum: u8,
+  ) -> ProgramResult {
+    if max_url_len as usize > Task::MAX_URL_LEN || allowed_task_kinds & !ALL_TASK_KINDS != 0 || quorum != 1 {
+      return Err(ProgramError::InvalidArgument);
+    }
+    Self::update_header(program_id, accounts, |header| {
+      header.request_fee = request_fee;
+      header.request_expiry_slots = request_expiry_slots;
+      header.max_url_len = max_url_len;
+      header.allowed_task_kinds = allowed_task_kinds;
+      header.quorum = quorum;
+      Ok(())
+    })
+  }
+
+  /// Hand answering Requests over to another node
+  pub fn process_set_node_authority(program_id: &Pubkey, accounts: &[AccountInfo], node_authority: Pubkey) -> ProgramResult {
+    Self::update_header(program_id, accounts, |header| {
+      header.node_authority = node_authority;
+      Ok(())
+    })
+  }
+
+  /// Propose `new_admin`, who takes over once they sign AcceptAdmin
+  pub fn process_transfer_admin(program_id: &Pubkey, accounts: &[AccountInfo], new_admin: Pubkey) -> ProgramResult {
+    Self::update_header(program_id, accounts, |header| {
+      header.pending_admin = new_admin;
+      Ok(())
+    })
+  }
+
+  /// Complete an admin transfer, signed by the proposed admin
+  pub fn process_accept_admin(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let oracle_account = next_account_info(accounts_iter)?;
+    let pending_admin = next_account_info(accounts_iter)?;
+
+    if oracle_account.owner != program_id {
+      return Err(ProgramError::IncorrectProgramId);
+    }
+    if !pending_admin.is_signer {
+      return Err(ProgramError::MissingRequiredSignature);
+    }
+
     let mut account_data = oracle_account.data.borrow_mut();
-    let oracle_data = OracleAccount::unpack(&account_data)?;
-    for i in 0..REQUEST_QUEUE_SIZE {
-      // loop to find the first empty request
-      if oracle_data.request_queue.requests[i].is_none() {
-        let offset = Request::LEN * i;
-        let dest = array_mut_ref![account_data, offset, Request::LEN];
-        request.index  = i as u8;
-        Request::pack(request, dest)?;
-        break;
+    let header_data = array_mut_ref![account_data, 0, OracleHeader::LEN];
+    let mut header = OracleHeader::unpack(header_data)?;
+    if header.pending_admin == Pubkey::default() || header.pending_admin != *pending_admin.key {
+      return Err(OracleError::IncorrectAuthority.into());
+    }
+
+    header.admin = header.pending_admin;
+    header.pending_admin = Pubkey::default();
+    OracleHeader::pack(header, header_data)
+  }
+
+  /// Pause or unpause the Oracle
+  pub fn process_set_paused(program_id: &Pubkey, accounts: &[AccountInfo], is_paused: bool) -> ProgramResult {
+    Self::update_header(program_id, accounts, |header| {
+      header.is_paused = is_paused;
+      Ok(())
+    })
+  }
+
+  /// Cap the queue slots a single requester may hold
+  pub fn process_set_requester_limit(program_id: &Pubkey, accounts: &[AccountInfo], max_requests_per_requester: u8) -> ProgramResult {
+    Self::update_header(program_id, accounts, |header| {
+      header.max_requests_per_requester = max_requests_per_requester;
+      Ok(())
+    })
+  }
+
+  /// Replace the secrets the Oracle advertises and the hosts they are sent to
+  pub fn process_set_secrets(program_id: &Pubkey, accounts: &[AccountInfo], secrets: OracleSecrets) -> ProgramResult {
+    if !secrets.iter().all(OracleSecret::is_valid) {
+      return Err(ProgramError::InvalidArgument);
+    }
+    Self::update_header(program_id, accounts, |header| {
+      header.supported_secrets = secrets;
+      Ok(())
+    })
+  }
+
+  /**
+   * Change how many Request slots the Oracle uses. Accounts can't be
+   * reallocated, so the capacity can grow up to what the account's data
+   * length fits; slots holding Requests are never dropped.
+   */
+  pub fn process_resize_oracle(program_id: &Pubkey, accounts: &[AccountInfo], capacity: u16) -> ProgramResult {
+    let oracle_account = accounts.first().ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let (max_capacity, slots_in_use) = {
+      let account_data = oracle_account.data.borrow();
+      let oracle_view = OracleAccountView::new(&account_data[..])?;
+      let slots_in_use = (0..oracle_view.capacity()).rposition(|index| oracle_view.is_occupied(index)).map_or(0, |index| index + 1);
+      (OracleAccount::capacity_for(account_data.len()).ok_or(ProgramError::InvalidAccountData)?, slots_in_use)
+    };
+    let capacity_range = slots_in_use.max(1)..=max_capacity;
+    if !capacity_range.contains(&(capacity as usize)) {
+      return Err(ProgramError::InvalidArgument);
+    }
+    Self::update_header(program_id, accounts, |header| {
+      header.capacity = capacity;
+      Ok(())
+    })
+  }
+
+  /**
+   * Close the Oracle account. Pending Requests are only dropped when
+   * `force` is set, their fees and dispute bonds are refunded first. The
+   * account data is zeroed and its lamports sent to the destination. Nodes
+   * withdraw their stake afterwards with Unstake.
+   */
+  pub fn process_close_oracle(program_id: &Pubkey, accounts: &[AccountInfo], force: bool) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let oracle_account = next_account_info(accounts_iter)?;
+    let admin = next_account_info(accounts_iter)?;
+    let destination = next_account_info(accounts_iter)?;
+
+    if oracle_account.owner != program_id {
+      return Err(ProgramError::IncorrectProgramId);
+    }
+    if !admin.is_signer {
+      return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let oracle_data = OracleAccount::unpack(&oracle_account.data.borrow())?;
+    let header = oracle_data.header;
+    if header.admin != *admin.key {
+      return Err(OracleError::IncorrectAuthority.into());
+    }
+    let pending_requests = oracle_data.request_queue.requests.iter().flatten();
+    if !force && pending_requests.clone().next().is_some() {
+      return Err(OracleError::RequestsPending.into());
+    }
+
+    for request in pending_requests {
+      let requester = next_account_info(accounts_iter)?;
+      if *requester.key != request.requester {
+        return Err(OracleError::IncorrectAuthority.into());
+      }
+      Self::release_fee(oracle_account, &header, requester, accounts_iter, request.escrow())?;
+
+      if request.optimistic.status == ResponseStatus::Disputed {
+        let disputer = next_account_info(accounts_iter)?;
+        if *disputer.key != request.optimistic.disputer {
+          return Err(OracleError::IncorrectAuthority.into());
+        }
+        Self::transfer_lamports(oracle_account, disputer, request.optimistic.dispute_bond)?;
       }
     }
 
+    for byte in oracle_account.data.borrow_mut().iter_mut() {
+      *byte = 0;
+    }
+    Self::transfer_lamports(oracle_account, destination, oracle_account.lamports())
+  }
+
+  /// Fail with OraclePaused when the Oracle in the first account is paused
+  fn check_not_paused(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let oracle_account = next_account_info(&mut accounts.iter())?;
+    if oracle_account.owner != program_id {
+      return Err(ProgramError::IncorrectProgramId);
+    }
+    let account_data = oracle_account.data.borrow();
+    let header_data = account_data.get(0..OracleHeader::LEN).ok_or(ProgramError::InvalidAccountData)?;
+    if OracleHeader::unpack(header_data)?.is_paused {
+      return Err(OracleError::OraclePaused.into());
+    }
     Ok(())
   }
 
-  /// Convert the response data into data bufer to be sent to the Caller Program
-  pub fn process_handle_response(accounts: &[AccountInfo], response: Response) -> ProgramResult {
-    // clear the account data
+  /**
+   * Apply `update` to the Oracle's header. Every admin instruction takes
+   * the Oracle account followed by the admin's signature.
+   */
+  fn update_header<F: FnOnce(&mut OracleHeader) -> ProgramResult>(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    update: F,
+  ) -> ProgramResult {
     let accounts_iter = &mut accounts.iter();
     let oracle_account = next_account_info(accounts_iter)?;
+    let admin = next_account_info(accounts_iter)?;
+
+    if oracle_account.owner != program_id {
+      return Err(ProgramError::IncorrectProgramId);
+    }
+    if !admin.is_signer {
+      return Err(ProgramError::MissingRequiredSignature);
+    }
+
     let mut account_data = oracle_account.data.borrow_mut();
+    let header_data = array_mut_ref![account_data, 0, OracleHeader::LEN];
+    let mut header = OracleHeader::unpack(header_data)?;
+    if header.admin != *admin.key {
+      return Err(OracleError::IncorrectAuthority.into());
+    }
+
+    update(&mut header)?;
+    OracleHeader::pack(header, header_data)
+  }
 
+  /// Set up a Feed account that only the node authority may update
+  pub fn process_initialize_feed(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    tasks: [Task; TASK_ARRAY_SIZE],
+    deviation_threshold_bps: u16,
+    heartbeat_slots: u64,
+    max_change_bps: u16,
+  ) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let feed_account = next_account_info(accounts_iter)?;
+    let authority = next_account_info(accounts_iter)?;
+
+    if feed_account.owner != program_id {
+      return Err(ProgramError::IncorrectProgramId);
+    }
+    if !authority.is_signer {
+      return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut feed_data = feed_account.data.borrow_mut();
+    let feed = Feed::unpack_unchecked(&feed_data)?;
+    if feed.is_initialized {
+      return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    let feed = Feed {
+      is_initialized: true,
+      authority: *authority.key,
+      tasks,
+      deviation_threshold_bps,
+      heartbeat_slots,
+      value: 0,
+      round: 0,
+      last_update_slot: 0,
+      max_change_bps,
+      is_tripped: false,
+    };
+    Feed::pack(feed, &mut feed_data)
+  }
+
+  /**
+   * Store a new Feed value. The update is rejected unless it deviates from
+   * the stored value by more than the Feed's threshold or the heartbeat
+   * interval has elapsed. A value moving past the Feed's maximum change
+   * trips the circuit breaker and is not stored.
+   */
+  pub fn process_update_feed(program_id: &Pubkey, accounts: &[AccountInfo], value: u32) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let feed_account = next_account_info(accounts_iter)?;
+    let authority = next_account_info(accounts_iter)?;
+    let clock = Clock::from_account_info(next_account_info(accounts_iter)?)?;
+
+    if feed_account.owner != program_id {
+      return Err(ProgramError::IncorrectProgramId);
+    }
+    if !authority.is_signer {
+      return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut feed_data = feed_account.data.borrow_mut();
+    let mut feed = Feed::unpack(&feed_data)?;
+    if feed.authority != *authority.key {
+      return Err(OracleError::IncorrectAuthority.into());
+    }
+    if feed.is_tripped {
+      return Err(OracleError::FeedTripped.into());
+    }
+    // the trip has to be stored, so it is not reported as an error
+    if feed.trips_breaker(value) {
+      info!(&format!("Feed circuit breaker tripped by {}", value));
+      feed.is_tripped = true;
+      return Feed::pack(feed, &mut feed_data);
+    }
+    if !feed.should_update(value, clock.slot) {
+      return Err(OracleError::FeedUpdateNotTriggered.into());
+    }
+
+    feed.value = value;
+    feed.round += 1;
+    feed.last_update_slot = clock.slot;
+    Feed::pack(feed, &mut feed_data)
+  }
+
+  /// Clear a Feed's circuit breaker and store `value` as its latest value
+  pub fn process_reset_feed(program_id: &Pubkey, accounts: &[AccountInfo], value: u32) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let feed_account = next_account_info(accounts_iter)?;
+    let authority = next_account_info(accounts_iter)?;
+    let clock = Clock::from_account_info(next_account_info(accounts_iter)?)?;
+
+    if feed_account.owner != program_id {
+      return Err(ProgramError::IncorrectProgramId);
+    }
+    if !authority.is_signer {
+      return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut feed_data = feed_account.data.borrow_mut();
+    let mut feed = Feed::unpack(&feed_data)?;
+    if feed.authority != *authority.key {
+      return Err(OracleError::IncorrectAuthority.into());
+    }
+
+    feed.is_tripped = false;
+    feed.value = value;
+    feed.round += 1;
+    feed.last_update_slot = clock.slot;
+    Feed::pack(feed, &mut feed_data)
+  }
+
+  /**
+   * Move up to `amount` lamports of a node's stake to `destination`. Shared
+   * by the admin's SlashNode and dispute outcomes.
+   */
+  pub(crate) fn slash(
+    program_id: &Pubkey,
+    oracle_account: &AccountInfo,
+    node_account: &AccountInfo,
+    destination: &AccountInfo,
+    amount: u64,
+  ) -> ProgramResult {
+    let mut node = Self::unpack_node(program_id, oracle_account, node_account)?;
+    let slashed = amount.min(node.stake);
+    node.stake -= slashed;
+    NodeAccount::pack(node, &mut node_account.data.borrow_mut())?;
+    Self::transfer_lamports(node_account, destination, slashed)
+  }
+
+  /// Whether CloseOracle zeroed the Oracle account, or its lamports were already reclaimed
+  fn is_closed(program_id: &Pubkey, oracle_account: &AccountInfo) -> bool {
+    // is_initialized is the first byte of the header
+    oracle_account.owner != program_id || oracle_account.data.borrow().first() != Some(&1)
+  }
+
+  /// Unpack a node account, checking it belongs to the program and to `oracle_account`
+  fn unpack_node(program_id: &Pubkey, oracle_account: &AccountInfo, node_account: &AccountInfo) -> Result<NodeAccount, ProgramError> {
+    if oracle_account.owner != program_id || node_account.owner != program_id {
+      return Err(ProgramError::IncorrectProgramId);
+    }
+    let node = NodeAccount::unpack(&node_account.data.borrow())?;
+    if node.oracle != *oracle_account.key {
+      return Err(ProgramError::InvalidAccountData);
+    }
+    Ok(node)
+  }
+
+  /**
+   * Fail unless `node_account` registers the Oracle's node authority with at
+   * least the minimum stake. Without a minimum stake the node authority
+   * answers on its own and `node_account` is not checked.
+   */
+  fn check_active_node(program_id: &Pubkey, oracle_account: &AccountInfo, header: &OracleHeader, node_account: &AccountInfo) -> ProgramResult {
+    if header.min_stake == 0 {
+      return Ok(());
+    }
+    let node = Self::unpack_node(program_id, oracle_account, node_account)?;
+    if node.authority != header.node_authority {
+      return Err(OracleError::IncorrectAuthority.into());
+    }
+    if !node.is_active(header.min_stake) {
+      return Err(OracleError::InactiveNode.into());
+    }
+    Ok(())
+  }
+
+  /**
+   * Fail while the node may still be slashed: it answers as the node
+   * authority, or a proposed answer is pending. Requests do not record
+   * which node proposed, so any pending proposal holds every node.
+   */
+  fn check_node_idle(oracle_account: &AccountInfo, node: &NodeAccount) -> ProgramResult {
+    let account_data = oracle_account.data.borrow();
+    let oracle_view = OracleAccountView::new(&account_data[..])?;
+    if oracle_view.header().node_authority == node.authority {
+      return Err(OracleError::NodeBusy.into());
+    }
+    for index in 0..oracle_view.capacity() {
+      if let Some(request) = oracle_view.request(index)? {
+        if request.optimistic.status != ResponseStatus::Unanswered {
+          return Err(OracleError::NodeBusy.into());
+        }
+      }
+    }
+    Ok(())
+  }
+
+  /// Whether the node may answer `request` with a callback to `client_program_account`
+  fn check_response(request: &Request, client_program_account: &AccountInfo) -> ProgramResult {
+    if request.call_back_program != *client_program_account.key {
+      return Err(OracleError::IncorrectCallbackProgram.into());
+    }
+    if request.optimistic.is_optimistic() && request.optimistic.status != ResponseStatus::Unanswered {
+      return Err(OracleError::InvalidResponseStatus.into());
+    }
+    Ok(())
+  }
+
+  /**
+   * Record the node's answer to a checked Request. Requests in optimistic
+   * mode hold it as a proposal, others are delivered right away.
+   */
+  #[allow(clippy::too_many_arguments)]
+  fn answer_request<'a, 'b: 'a, I: Iterator<Item = &'a AccountInfo<'b>>>(
+    oracle_account: &AccountInfo<'b>,
+    header: &OracleHeader,
+    client_program_account: &AccountInfo<'b>,
+    node_authority: &AccountInfo<'b>,
+    accounts_iter: &mut I,
+    clock: &Clock,
+    mut request: Request,
+    response: Response,
+  ) -> ProgramResult {
+    if request.optimistic.is_optimistic() {
+      request.optimistic.status = ResponseStatus::Proposed;
+      request.optimistic.data = response.data;
+      request.optimistic.proposed_slot = clock.slot;
+      return Self::pack_request(oracle_account, request);
+    }
+
+    Self::cache_answer(oracle_account, &request, response.data, clock.slot)?;
+    Self::deliver_response(oracle_account, header, client_program_account, node_authority, accounts_iter, request.escrow(), response)
+  }
+
+  /// Keep a delivered answer in the Oracle's answer cache
+  fn cache_answer(oracle_account: &AccountInfo, request: &Request, data: ResponseData, slot: u64) -> ProgramResult {
+    let mut account_data = oracle_account.data.borrow_mut();
+    let mut oracle_view = OracleAccountView::new(&mut account_data[..])?;
+    let mut header = oracle_view.header().clone();
+    header.answer_cache.insert(request.task_hash(), data, slot);
+    oracle_view.write_header(header);
+    Ok(())
+  }
+
+  /// Unanswered Requests outside optimistic mode running the Tasks hashed to `task_hash`
+  fn pending_duplicates(oracle_account: &AccountInfo, task_hash: &[u8; HASH_BYTES]) -> Result<Vec<Request>, ProgramError> {
+    let account_data = oracle_account.data.borrow();
+    let oracle_view = OracleAccountView::new(&account_data[..])?;
+    let mut duplicates = vec![];
+    for index in 0..oracle_view.capacity() {
+      if let Some(request) = oracle_view.request(index)? {
+        if !request.optimistic.is_optimistic() && request.task_hash() == *task_hash {
+          duplicates.push(request);
+        }
+      }
+    }
+    Ok(duplicates)
+  }
+
+  /**
+   * Remove the answered Request, pay its fee to `fee_recipient` and send the
+   * Response to the Caller Program
+   */
+  fn deliver_response<'a, 'b: 'a, I: Iterator<Item = &'a AccountInfo<'b>>>(
+    oracle_account: &AccountInfo<'b>,
+    header: &OracleHeader,
+    client_program_account: &AccountInfo<'b>,
+    fee_recipient: &AccountInfo<'b>,
+    accounts_iter: &mut I,
+    fee: u64,
+    response: Response,
+  ) -> ProgramResult {
     // delete the Request that the Response is for
-    let offset = response.request_queue_index as usize * Request::LEN;
-    let request_to_delete = array_mut_ref![account_data, offset, Request::LEN];
-    request_to_delete.copy_from_slice(&[0u8; Request::LEN]);
+    Self::clear_request(oracle_account, response.request_queue_index as usize)?;
+    Self::release_fee(oracle_account, header, fee_recipient, accounts_iter, fee)?;
 
     // send a cross program invocation to the second account
-    let client_program_account = next_account_info(accounts_iter)?;
     let data = &mut [0u8; Response::LEN];
     Response::pack(response, data)?;
     let accounts = vec![];
@@ -75,32 +1504,106 @@ impl Processor {
       data: data.to_vec()
     };
 
-    invoke(&ix, &[client_program_account.clone()])
+    invoke(&ix, std::slice::from_ref(client_program_account))
+  }
+
+  /// Unpack the Oracle's header and the Request at `request_queue_index`
+  fn unpack_request(oracle_account: &AccountInfo, request_queue_index: RequestIndex) -> Result<(OracleHeader, Request), ProgramError> {
+    let account_data = oracle_account.data.borrow();
+    let oracle_view = OracleAccountView::new(&account_data[..])?;
+    let request = oracle_view.request(request_queue_index as usize)?.ok_or(OracleError::InvalidRequestIndex)?;
+    Ok((oracle_view.header().clone(), request))
+  }
+
+  /// Write `request` back to its slot
+  fn pack_request(oracle_account: &AccountInfo, request: Request) -> ProgramResult {
+    let mut account_data = oracle_account.data.borrow_mut();
+    OracleAccountView::new(&mut account_data[..])?.write_request(request)
+  }
+
+  /// Zero out the Request slot at `index` so it can be reused
+  fn clear_request(oracle_account: &AccountInfo, index: usize) -> ProgramResult {
+    let mut account_data = oracle_account.data.borrow_mut();
+    OracleAccountView::new(&mut account_data[..])?.clear_request(index)
+  }
+
+  /**
+   * Pay an escrowed Request fee to `recipient`. Token fees are sent from the
+   * fee vault, signed for by the vault authority, to the token account of
+   * `recipient` that follows in `accounts_iter`.
+   */
+  fn release_fee<'a, 'b: 'a, I: Iterator<Item = &'a AccountInfo<'b>>>(
+    oracle_account: &AccountInfo<'b>,
+    header: &OracleHeader,
+    recipient: &AccountInfo<'b>,
+    accounts_iter: &mut I,
+    amount: u64,
+  ) -> ProgramResult {
+    if !header.charges_token_fee() {
+      return Self::transfer_lamports(oracle_account, recipient, amount);
+    }
+
+    let fee_vault = next_account_info(accounts_iter)?;
+    let recipient_token_account = next_account_info(accounts_iter)?;
+    let vault_authority = next_account_info(accounts_iter)?;
+    let token_program = next_account_info(accounts_iter)?;
+    if *fee_vault.key != header.fee_vault {
+      return Err(OracleError::IncorrectFeeVault.into());
+    }
+    if !spl_token::check_id(token_program.key) {
+      return Err(ProgramError::IncorrectProgramId);
+    }
+    if TokenAccount::unpack(&recipient_token_account.data.borrow())?.owner != *recipient.key {
+      return Err(OracleError::IncorrectAuthority.into());
+    }
+    if amount == 0 {
+      return Ok(());
+    }
+
+    invoke_signed(
+      &token::transfer(fee_vault.key, recipient_token_account.key, vault_authority.key, amount),
+      &[fee_vault.clone(), recipient_token_account.clone(), vault_authority.clone(), token_program.clone()],
+      &[&[oracle_account.key.as_ref(), VAULT_AUTHORITY_SEED, &[header.vault_authority_bump]]],
+    )
+  }
+
+  /// Move lamports held in escrow out of an account owned by the program
+  fn transfer_lamports(from: &AccountInfo, to: &AccountInfo, amount: u64) -> ProgramResult {
+    let from_lamports = from.lamports().checked_sub(amount).ok_or(ProgramError::InsufficientFunds)?;
+    let to_lamports = to.lamports().checked_add(amount).ok_or(ProgramError::InvalidArgument)?;
+    **from.lamports.borrow_mut() = from_lamports;
+    **to.lamports.borrow_mut() = to_lamports;
+    Ok(())
   }
 }
 
 #[cfg(test)]
 mod tests {
   use crate::{ 
+    fee_schedule::HostFee,
     instruction::*,
+    job_spec::{ job_param, JOB_PARAM_LEN, MAX_JOB_PARAMS },
     PUBLIC_KEY_LEN,
     request::{ GetArgs,
       GetParams,
+      Header,
       JsonParseArgs,
       Task,
       Request, 
-      RequestQueue 
+      RequestQueue,
+      REQUEST_QUEUE_SIZE,
     },
   };
   use super::*;
   use generic_array::GenericArray;
   use solana_program::{
-    instruction::{ AccountMeta, Instruction },
+    instruction::Instruction,
     program_stubs,
     program_error::ProgramError,
+    system_program,
   };
   use solana_sdk::account::{
-    create_is_signer_account_infos, Account
+    create_account, create_is_signer_account_infos, Account
   };
   use arrayref::{ array_ref };
 
@@ -108,6 +1611,10 @@ mod tests {
   const TTP_ORACLE_PROGRAM_ID: Pubkey = Pubkey::new_from_array([1u8; PUBLIC_KEY_LEN]);
   // test program id for the client program that consumes ttp-oracle
   const CLIENT_PROGRAM_ID: Pubkey = Pubkey::new_from_array([2u8; PUBLIC_KEY_LEN]);
+  const ADMIN: Pubkey = Pubkey::new_from_array([5u8; PUBLIC_KEY_LEN]);
+  const NODE_AUTHORITY: Pubkey = Pubkey::new_from_array([6u8; PUBLIC_KEY_LEN]);
+  const REQUEST_FEE: u64 = 5_000;
+  const REQUEST_EXPIRY_SLOTS: u64 = 300;
 
 
   fn do_process_instruction(
@@ -143,6 +1650,13 @@ mod tests {
             }
           }
 
+          if instruction.program_id == system_program::id() {
+            return invoke_system_transfer(instruction, account_infos);
+          }
+          if instruction.program_id == spl_token::id() {
+            return invoke_token_transfer(instruction, account_infos);
+          }
+
           match instruction.program_id {
               TTP_ORACLE_PROGRAM_ID => Ok(()), 
               CLIENT_PROGRAM_ID => invoke_client(&new_account_infos, &instruction.data),
@@ -159,6 +1673,49 @@ mod tests {
     Ok(())
   }
 
+  // only system transfers and create_account are needed by the processor, both carry
+  // the lamports at bytes 4..12. Accounts are allocated by the test up front
+  fn invoke_system_transfer(instruction: &Instruction, account_infos: &[AccountInfo]) -> ProgramResult {
+    let lamports = u64::from_le_bytes(*array_ref![instruction.data, 4, 8]);
+    let find_account = |key: &Pubkey| account_infos.iter().find(|account_info| account_info.key == key).unwrap();
+    let from = find_account(&instruction.accounts[0].pubkey);
+    let to = find_account(&instruction.accounts[1].pubkey);
+    if from.lamports() < lamports {
+      return Err(ProgramError::InsufficientFunds);
+    }
+    **from.lamports.borrow_mut() -= lamports;
+    **to.lamports.borrow_mut() += lamports;
+    Ok(())
+  }
+
+  // only token Transfers are needed by the processor, balances live at bytes 64..72
+  fn invoke_token_transfer(instruction: &Instruction, account_infos: &[AccountInfo]) -> ProgramResult {
+    let amount = u64::from_le_bytes(*array_ref![instruction.data, 1, 8]);
+    let find_account = |key: &Pubkey| account_infos.iter().find(|account_info| account_info.key == key).unwrap();
+    let source = find_account(&instruction.accounts[0].pubkey);
+    let destination = find_account(&instruction.accounts[1].pubkey);
+    let source_amount = TokenAccount::unpack(&source.data.borrow())?.amount;
+    let destination_amount = TokenAccount::unpack(&destination.data.borrow())?.amount;
+    if source_amount < amount {
+      return Err(ProgramError::InsufficientFunds);
+    }
+    source.data.borrow_mut()[64..72].copy_from_slice(&(source_amount - amount).to_le_bytes());
+    destination.data.borrow_mut()[64..72].copy_from_slice(&(destination_amount + amount).to_le_bytes());
+    Ok(())
+  }
+
+  fn token_account(mint: &Pubkey, owner: &Pubkey, amount: u64) -> Account {
+    let mut account = Account::new(0, token::TOKEN_ACCOUNT_LEN, &spl_token::id());
+    account.data[0..32].copy_from_slice(mint.as_ref());
+    account.data[32..64].copy_from_slice(owner.as_ref());
+    account.data[64..72].copy_from_slice(&amount.to_le_bytes());
+    account
+  }
+
+  fn token_amount(account: &Account) -> u64 {
+    TokenAccount::unpack(&account.data).unwrap().amount
+  }
+
   fn setup_syscall_stubs() {
       use std::sync::Once;
       static ONCE: Once = Once::new();
@@ -178,83 +1735,715 @@ mod tests {
     let params = GetParams {
       get: *GenericArray::from_slice(url_bytes)
     };
-    let args = GetArgs {
-      params: params
-    };
+    let args = GetArgs::new(params);
     let get_task = Task::HttpGet(args);
     let json_parse_task = Task::JsonParse(json_args);
     let uint_128_task = Task::Uint32;
 
-    Request {
-      tasks: [get_task, json_parse_task, uint_128_task],
-      call_back_program: Pubkey::new(&[3u8; PUBLIC_KEY_LEN]),
-      index: 0,
-    }
+    Request {
+      tasks: [get_task, json_parse_task, uint_128_task],
+      call_back_program: Pubkey::new(&[3u8; PUBLIC_KEY_LEN]),
+      index: 0,
+      requester: Pubkey::default(),
+      fee: REQUEST_FEE,
+      created_slot: 0,
+      id: 0,
+      tip: 0,
+      max_answer_age_slots: 0,
+      job_id: 0,
+      optimistic: OptimisticResponse::default(),
+    }
+  }
+
+  fn initialize_oracle_account(program_id: &Pubkey, oracle_id: &Pubkey) -> Account {
+    let mut oracle_account = Account::new(0, OracleAccount::LEN, program_id);
+    let ix = initialize_oracle(program_id, oracle_id, &ADMIN, &NODE_AUTHORITY, REQUEST_FEE, REQUEST_EXPIRY_SLOTS).unwrap();
+    do_process_instruction(ix, vec![&mut oracle_account, &mut Account::default()]).unwrap();
+    oracle_account
+  }
+
+  fn expected_header() -> OracleHeader {
+    OracleHeader {
+      is_initialized: true,
+      admin: ADMIN,
+      node_authority: NODE_AUTHORITY,
+      request_fee: REQUEST_FEE,
+      request_expiry_slots: REQUEST_EXPIRY_SLOTS,
+      fee_mint: Pubkey::default(),
+      fee_vault: Pubkey::default(),
+      vault_authority_bump: 0,
+      fee_schedule: FeeSchedule::default(),
+      min_stake: 0,
+      unstake_cooldown_slots: 0,
+      dispute_bond: 0,
+      pending_admin: Pubkey::default(),
+      max_url_len: 34,
+      allowed_task_kinds: ALL_TASK_KINDS,
+      quorum: 1,
+      is_paused: false,
+      max_requests_per_requester: 0,
+      capacity: REQUEST_QUEUE_SIZE as u16,
+      next_request_id: 0,
+      answer_cache: AnswerCache::default(),
+      supported_secrets: Default::default(),
+    }
+  }
+
+  fn build_escrowed_request(requester: &Pubkey, index: u8, created_slot: u64) -> Request {
+    let mut request = build_request();
+    request.index = index;
+    request.requester = *requester;
+    request.fee = REQUEST_FEE;
+    request.created_slot = created_slot;
+    request
+  }
+
+  #[test]
+  fn test_process_initialize_oracle() {
+    let program_id = Pubkey::new_unique();
+    let oracle_id = Pubkey::new_unique();
+    let mut oracle_account = initialize_oracle_account(&program_id, &oracle_id);
+
+    let deserialized_oracle_account = OracleAccount::unpack(&oracle_account.data).unwrap();
+    assert_eq!(deserialized_oracle_account.header, expected_header());
+
+    let ix = initialize_oracle(&program_id, &oracle_id, &ADMIN, &NODE_AUTHORITY, 0, 0).unwrap();
+    assert_eq!(
+      do_process_instruction(ix, vec![&mut oracle_account, &mut Account::default()]),
+      Err(ProgramError::AccountAlreadyInitialized)
+    );
+
+    let mut oracle_account = Account::new(0, OracleAccount::LEN, &program_id);
+    let mut ix = initialize_oracle(&program_id, &oracle_id, &ADMIN, &NODE_AUTHORITY, 0, 0).unwrap();
+    ix.accounts[1].is_signer = false;
+    assert_eq!(
+      do_process_instruction(ix, vec![&mut oracle_account, &mut Account::default()]),
+      Err(ProgramError::MissingRequiredSignature)
+    );
+  }
+
+  #[test]
+  fn test_process_create_oracle() {
+    setup_syscall_stubs();
+    let program_id = Pubkey::new_unique();
+    let seed = [9u8; ORACLE_SEED_LEN];
+    let space = OracleAccount::get_packed_len(20);
+    let rent_lamports = Rent::default().minimum_balance(space);
+    let mut oracle_account = Account::new(0, space, &program_id);
+    let mut admin_account = Account::new(rent_lamports, 0, &system_program::id());
+    let mut rent_account = create_account(&Rent::default(), 1);
+
+    // the Oracle must live at the admin's program address for the seed
+    let mut ix = create_oracle(
+      &program_id, &ADMIN, seed, &NODE_AUTHORITY, REQUEST_FEE, REQUEST_EXPIRY_SLOTS, &Pubkey::default(), None, 20,
+    ).unwrap();
+    ix.accounts[0].pubkey = Pubkey::new_unique();
+    assert_eq!(
+      do_process_instruction(ix, vec![&mut oracle_account, &mut admin_account, &mut Account::default(), &mut rent_account]),
+      Err(ProgramError::InvalidSeeds)
+    );
+    let ix = create_oracle(
+      &program_id, &ADMIN, seed, &NODE_AUTHORITY, REQUEST_FEE, REQUEST_EXPIRY_SLOTS, &Pubkey::default(), None, 0,
+    ).unwrap();
+    assert_eq!(
+      do_process_instruction(ix, vec![&mut oracle_account, &mut admin_account, &mut Account::default(), &mut rent_account]),
+      Err(ProgramError::InvalidArgument)
+    );
+
+    let ix = create_oracle(
+      &program_id, &ADMIN, seed, &NODE_AUTHORITY, REQUEST_FEE, REQUEST_EXPIRY_SLOTS, &Pubkey::default(), None, 20,
+    ).unwrap();
+    assert_eq!(ix.accounts[0].pubkey, find_oracle_address(&program_id, &ADMIN, &seed).0);
+    do_process_instruction(ix, vec![&mut oracle_account, &mut admin_account, &mut Account::default(), &mut rent_account]).unwrap();
+    assert_eq!(oracle_account.lamports, rent_lamports);
+    assert_eq!(admin_account.lamports, 0);
+    let oracle_data = OracleAccount::unpack(&oracle_account.data).unwrap();
+    assert_eq!(oracle_data.header, OracleHeader { capacity: 20, ..expected_header() });
+    assert_eq!(oracle_data.request_queue.requests.len(), 20);
   }
 
   #[test]
   fn test_process_create_request() {
+    setup_syscall_stubs();
     let program_id = Pubkey::new_unique();
     let oracle_id = Pubkey::new_unique();
-    // account data buffer with the size of a request
+    let payer_id = Pubkey::new_unique();
+    let mut oracle_account = initialize_oracle_account(&program_id, &oracle_id);
+    let mut payer_account = Account::new(REQUEST_FEE, 0, &system_program::id());
     let request = build_request();
-    let mut oracle_account = Account::new(0, OracleAccount::LEN, &program_id);
 
-    let ix = create_request(&program_id, &oracle_id, request).unwrap();
-
-    do_process_instruction(ix, vec![&mut oracle_account]).unwrap();
+    let ix = create_request(&program_id, &oracle_id, &payer_id, request).unwrap();
+    do_process_instruction(ix, vec![
+      &mut oracle_account,
+      &mut payer_account,
+      &mut Account::default(),
+      &mut clock_account(42),
+    ]).unwrap();
     
     let deserialized_oracle_account = OracleAccount::unpack(&oracle_account.data).unwrap();
     
-    let request = build_request();
+    let request = build_escrowed_request(&payer_id, 0, 42);
     let request_queue = RequestQueue {
-      requests: Box::new([Some(request), None, None, None, None, None, None, None, None, None]),
+      requests: vec![Some(request), None, None, None, None, None, None, None, None, None],
     };
-    let oracle_account = OracleAccount {
+    let expected_oracle_account = OracleAccount {
+      header: OracleHeader { next_request_id: 1, ..expected_header() },
       request_queue,
     };
-    assert_eq!(deserialized_oracle_account, oracle_account);
+    assert_eq!(deserialized_oracle_account, expected_oracle_account);
+    // the fee is held in escrow by the Oracle account
+    assert_eq!(oracle_account.lamports, REQUEST_FEE);
+    assert_eq!(payer_account.lamports, 0);
+
+    // the payer can not afford a second Request
+    let request = build_request();
+    let ix = create_request(&program_id, &oracle_id, &payer_id, request).unwrap();
+    assert_eq!(
+      do_process_instruction(ix, vec![
+        &mut oracle_account,
+        &mut payer_account,
+        &mut Account::default(),
+        &mut clock_account(43),
+      ]),
+      Err(ProgramError::InsufficientFunds)
+    );
   }
 
   #[test]
   fn test_process_create_two_requests() {
+    setup_syscall_stubs();
     let program_id = Pubkey::new_unique();
     let oracle_id = Pubkey::new_unique();
-    // account data buffer with the size of a request
-    let request = build_request();
-    let mut account = Account::new(0, OracleAccount::LEN, &program_id);
+    let payer_id = Pubkey::new_unique();
+    let mut account = initialize_oracle_account(&program_id, &oracle_id);
+    let mut payer_account = Account::new(2 * REQUEST_FEE, 0, &system_program::id());
     
-    let ix = create_request(&program_id, &oracle_id, request).unwrap();
-    
-    do_process_instruction(ix, vec![&mut account]).unwrap();
+    let request = build_request();
+    let ix = create_request(&program_id, &oracle_id, &payer_id, request).unwrap();
+    do_process_instruction(ix, vec![
+      &mut account,
+      &mut payer_account,
+      &mut Account::default(),
+      &mut clock_account(1),
+    ]).unwrap();
     
     let deserialized_oracle_account = OracleAccount::unpack(&account.data).unwrap();
     
-    let request = build_request();
+    let request = build_escrowed_request(&payer_id, 0, 1);
     let request_queue = RequestQueue {
-      requests: Box::new([Some(request), None, None, None, None, None, None, None, None, None]),
+      requests: vec![Some(request), None, None, None, None, None, None, None, None, None],
     };
     let oracle_account_data = OracleAccount {
+      header: OracleHeader { next_request_id: 1, ..expected_header() },
       request_queue,
     };
     assert_eq!(deserialized_oracle_account, oracle_account_data);
     
     let request = build_request();
-    let ix = create_request(&program_id, &oracle_id, request).unwrap();
-    do_process_instruction(ix, vec![&mut account]).unwrap();
+    let ix = create_request(&program_id, &oracle_id, &payer_id, request).unwrap();
+    do_process_instruction(ix, vec![
+      &mut account,
+      &mut payer_account,
+      &mut Account::default(),
+      &mut clock_account(2),
+    ]).unwrap();
     let deserialized_oracle_account = OracleAccount::unpack(&account.data).unwrap();
-    let request1 = build_request();
-    let mut request2 = build_request();
-    request2.index = 1;
+    let request1 = build_escrowed_request(&payer_id, 0, 1);
+    let mut request2 = build_escrowed_request(&payer_id, 1, 2);
+    // ids follow arrival order
+    request2.id = 1;
     let request_queue = RequestQueue {
-      requests: Box::new([Some(request1), Some(request2), None, None, None, None, None, None, None, None]),
+      requests: vec![Some(request1), Some(request2), None, None, None, None, None, None, None, None],
     };
     let oracle_account_data = OracleAccount {
+      header: OracleHeader { next_request_id: 2, ..expected_header() },
       request_queue,
     };
 
-
     assert_eq!(deserialized_oracle_account, oracle_account_data);
+    assert_eq!(account.lamports, 2 * REQUEST_FEE);
+  }
+
+  #[test]
+  fn test_process_create_request_queue_full() {
+    setup_syscall_stubs();
+    let program_id = Pubkey::new_unique();
+    let oracle_id = Pubkey::new_unique();
+    let payer_id = Pubkey::new_unique();
+    let mut payer_account = Account::new(11 * REQUEST_FEE, 0, &system_program::id());
+
+    let mut uninitialized_account = Account::new(0, OracleAccount::LEN, &program_id);
+    let ix = create_request(&program_id, &oracle_id, &payer_id, build_request()).unwrap();
+    assert_eq!(
+      do_process_instruction(ix, vec![
+        &mut uninitialized_account,
+        &mut payer_account,
+        &mut Account::default(),
+        &mut clock_account(1),
+      ]),
+      Err(ProgramError::UninitializedAccount)
+    );
+
+    let mut account = initialize_oracle_account(&program_id, &oracle_id);
+    for _ in 0..REQUEST_QUEUE_SIZE {
+      let ix = create_request(&program_id, &oracle_id, &payer_id, build_request()).unwrap();
+      do_process_instruction(ix, vec![
+        &mut account,
+        &mut payer_account,
+        &mut Account::default(),
+        &mut clock_account(1),
+      ]).unwrap();
+    }
+    let ix = create_request(&program_id, &oracle_id, &payer_id, build_request()).unwrap();
+    assert_eq!(
+      do_process_instruction(ix, vec![
+        &mut account,
+        &mut payer_account,
+        &mut Account::default(),
+        &mut clock_account(1),
+      ]),
+      Err(OracleError::RequestQueueFull.into())
+    );
+    // no fee is taken for a rejected Request
+    assert_eq!(payer_account.lamports, REQUEST_FEE);
+  }
+
+  #[test]
+  fn test_process_create_requests() {
+    setup_syscall_stubs();
+    let program_id = Pubkey::new_unique();
+    let oracle_id = Pubkey::new_unique();
+    let payer_id = Pubkey::new_unique();
+    let mut account = initialize_oracle_account(&program_id, &oracle_id);
+    let mut payer_account = Account::new(REQUEST_QUEUE_SIZE as u64 * REQUEST_FEE, 0, &system_program::id());
+
+    let ix = create_requests(&program_id, &oracle_id, &payer_id, vec![build_request(); 3]).unwrap();
+    do_process_instruction(ix, vec![
+      &mut account,
+      &mut payer_account,
+      &mut Account::default(),
+      &mut clock_account(5),
+    ]).unwrap();
+    let oracle_data = OracleAccount::unpack(&account.data).unwrap();
+    assert_eq!(oracle_data.header, OracleHeader { next_request_id: 3, ..expected_header() });
+    for index in 0..3 {
+      let mut expected_request = build_escrowed_request(&payer_id, index, 5);
+      expected_request.id = index as u64;
+      assert_eq!(oracle_data.request_queue.requests[index as usize], Some(expected_request));
+    }
+    // the fees of the whole batch are escrowed at once
+    assert_eq!(account.lamports, 3 * REQUEST_FEE);
+
+    // 7 slots are left, so a batch of 8 creates nothing
+    let ix = create_requests(&program_id, &oracle_id, &payer_id, vec![build_request(); 8]).unwrap();
+    assert_eq!(
+      do_process_instruction(ix, vec![
+        &mut account,
+        &mut payer_account,
+        &mut Account::default(),
+        &mut clock_account(6),
+      ]),
+      Err(OracleError::RequestQueueFull.into())
+    );
+    assert_eq!(account.lamports, 3 * REQUEST_FEE);
+    assert_eq!(OracleAccount::unpack(&account.data).unwrap(), oracle_data);
+
+    // every Request of the batch counts towards the requester limit
+    let ix = set_requester_limit(&program_id, &oracle_id, &ADMIN, 4).unwrap();
+    do_process_instruction(ix, vec![&mut account, &mut Account::default()]).unwrap();
+    let ix = create_requests(&program_id, &oracle_id, &payer_id, vec![build_request(); 2]).unwrap();
+    assert_eq!(
+      do_process_instruction(ix, vec![
+        &mut account,
+        &mut payer_account,
+        &mut Account::default(),
+        &mut clock_account(6),
+      ]),
+      Err(OracleError::RequesterLimitReached.into())
+    );
+  }
+
+  #[test]
+  fn test_process_job_requests() {
+    setup_syscall_stubs();
+    let program_id = Pubkey::new_unique();
+    let oracle_id = Pubkey::new_unique();
+    let payer_id = Pubkey::new_unique();
+    let job_id = 7;
+    let (job_address, _) = find_job_address(&program_id, &oracle_id, job_id);
+    let mut account = initialize_oracle_account(&program_id, &oracle_id);
+    let mut job_account = Account::new(0, JobSpec::LEN, &program_id);
+    let mut admin_account = Account::new(Rent::default().minimum_balance(JobSpec::LEN), 0, &system_program::id());
+    let mut payer_account = Account::new(REQUEST_FEE, 0, &system_program::id());
+    let mut rent_account = create_account(&Rent::default(), 1);
+    let mut name = [0u8; JOB_NAME_LEN];
+    name[..12].copy_from_slice(b"FTX USD pair");
+    let mut tasks = build_request().tasks;
+    tasks[0] = Task::HttpGet(GetArgs::new(GetParams { get: *GenericArray::from_slice(b"https://ftx.us/api/markets/{m}/USD") }));
+    let params = [job_param(b"m"), [0u8; JOB_PARAM_LEN]];
+    let args = [job_param(b"BTC"), [0u8; JOB_PARAM_LEN]];
+
+    // job id 0 marks Requests carrying their own Tasks
+    let ix = register_job(&program_id, &oracle_id, &ADMIN, 0, name, tasks.clone(), params).unwrap();
+    assert_eq!(
+      do_process_instruction(ix, vec![
+        &mut account,
+        &mut job_account,
+        &mut admin_account,
+        &mut Account::default(),
+        &mut rent_account,
+      ]),
+      Err(ProgramError::InvalidArgument)
+    );
+    let ix = register_job(&program_id, &oracle_id, &NODE_AUTHORITY, job_id, name, tasks.clone(), params).unwrap();
+    assert_eq!(
+      do_process_instruction(ix, vec![
+        &mut account,
+        &mut job_account,
+        &mut admin_account,
+        &mut Account::default(),
+        &mut rent_account,
+      ]),
+      Err(OracleError::IncorrectAuthority.into())
+    );
+
+    // the template refers to an undeclared parameter
+    let ix = register_job(&program_id, &oracle_id, &ADMIN, job_id, name, tasks.clone(), [[0u8; JOB_PARAM_LEN]; MAX_JOB_PARAMS]).unwrap();
+    assert_eq!(
+      do_process_instruction(ix, vec![
+        &mut account,
+        &mut job_account,
+        &mut admin_account,
+        &mut Account::default(),
+        &mut rent_account,
+      ]),
+      Err(OracleError::InvalidJobTemplate.into())
+    );
+
+    let ix = register_job(&program_id, &oracle_id, &ADMIN, job_id, name, tasks.clone(), params).unwrap();
+    assert_eq!(ix.accounts[1].pubkey, job_address);
+    do_process_instruction(ix, vec![
+      &mut account,
+      &mut job_account,
+      &mut admin_account,
+      &mut Account::default(),
+      &mut rent_account,
+    ]).unwrap();
+    assert_eq!(JobSpec::unpack(&job_account.data).unwrap(), JobSpec {
+      is_initialized: true,
+      oracle: oracle_id,
+      job_id,
+      name,
+      tasks: tasks.clone(),
+      params,
+    });
+
+    // the job account must belong to the Oracle and match the job id
+    let call_back_program = Pubkey::new(&[3u8; PUBLIC_KEY_LEN]);
+    let ix = create_job_request(&program_id, &oracle_id, &payer_id, job_id + 1, &call_back_program, REQUEST_FEE, 0, 0, 0, args).unwrap();
+    assert_eq!(
+      do_process_instruction(ix, vec![
+        &mut account,
+        &mut payer_account,
+        &mut Account::default(),
+        &mut clock_account(5),
+        &mut job_account,
+      ]),
+      Err(ProgramError::InvalidArgument)
+    );
+
+    // an argument may not add segments to the url
+    let bad_args = [job_param(b"BTC/X"), [0u8; JOB_PARAM_LEN]];
+    let ix = create_job_request(&program_id, &oracle_id, &payer_id, job_id, &call_back_program, REQUEST_FEE, 0, 0, 0, bad_args).unwrap();
+    assert_eq!(
+      do_process_instruction(ix, vec![
+        &mut account,
+        &mut payer_account,
+        &mut Account::default(),
+        &mut clock_account(5),
+        &mut job_account,
+      ]),
+      Err(OracleError::InvalidJobArgument.into())
+    );
+
+    let ix = create_job_request(&program_id, &oracle_id, &payer_id, job_id, &call_back_program, REQUEST_FEE, 0, 0, 0, args).unwrap();
+    do_process_instruction(ix, vec![
+      &mut account,
+      &mut payer_account,
+      &mut Account::default(),
+      &mut clock_account(5),
+      &mut job_account,
+    ]).unwrap();
+    // the Request runs the template with the argument substituted
+    let mut expected_request = build_escrowed_request(&payer_id, 0, 5);
+    expected_request.job_id = job_id;
+    let oracle_data = OracleAccount::unpack(&account.data).unwrap();
+    assert_eq!(oracle_data.request_queue.requests[0], Some(expected_request));
+    assert_eq!(account.lamports, REQUEST_FEE);
+  }
+
+  #[test]
+  fn test_process_resize_oracle() {
+    setup_syscall_stubs();
+    let program_id = Pubkey::new_unique();
+    let oracle_id = Pubkey::new_unique();
+    let payer_id = Pubkey::new_unique();
+    let mut payer_account = Account::new(10 * REQUEST_FEE, 0, &system_program::id());
+
+    // the capacity is derived from the account length
+    let mut account = Account::new(0, OracleAccount::get_packed_len(4), &program_id);
+    let ix = initialize_oracle(&program_id, &oracle_id, &ADMIN, &NODE_AUTHORITY, REQUEST_FEE, REQUEST_EXPIRY_SLOTS).unwrap();
+    do_process_instruction(ix, vec![&mut account, &mut Account::default()]).unwrap();
+    assert_eq!(OracleAccount::unpack(&account.data).unwrap().header.capacity, 4);
+    let mut too_small_account = Account::new(0, OracleHeader::LEN, &program_id);
+    let ix = initialize_oracle(&program_id, &oracle_id, &ADMIN, &NODE_AUTHORITY, REQUEST_FEE, REQUEST_EXPIRY_SLOTS).unwrap();
+    assert_eq!(
+      do_process_instruction(ix, vec![&mut too_small_account, &mut Account::default()]),
+      Err(ProgramError::InvalidAccountData)
+    );
+
+    let ix = resize_oracle(&program_id, &oracle_id, &ADMIN, 1).unwrap();
+    do_process_instruction(ix, vec![&mut account, &mut Account::default()]).unwrap();
+    let ix = create_request(&program_id, &oracle_id, &payer_id, build_request()).unwrap();
+    do_process_instruction(ix, vec![&mut account, &mut payer_account, &mut Account::default(), &mut clock_account(1)]).unwrap();
+    let ix = create_request(&program_id, &oracle_id, &payer_id, build_request()).unwrap();
+    assert_eq!(
+      do_process_instruction(ix, vec![&mut account, &mut payer_account, &mut Account::default(), &mut clock_account(1)]),
+      Err(OracleError::RequestQueueFull.into())
+    );
+    let oracle_data = OracleAccount::unpack(&account.data).unwrap();
+    assert_eq!(oracle_data.request_queue.requests.len(), 1);
+    assert!(oracle_data.request_queue.requests[0].is_some());
+
+    // the capacity can't outgrow the account or drop a pending Request
+    let ix = resize_oracle(&program_id, &oracle_id, &ADMIN, 5).unwrap();
+    assert_eq!(do_process_instruction(ix, vec![&mut account, &mut Account::default()]), Err(ProgramError::InvalidArgument));
+    let ix = resize_oracle(&program_id, &oracle_id, &ADMIN, 0).unwrap();
+    assert_eq!(do_process_instruction(ix, vec![&mut account, &mut Account::default()]), Err(ProgramError::InvalidArgument));
+    let ix = resize_oracle(&program_id, &oracle_id, &ADMIN, 4).unwrap();
+    do_process_instruction(ix, vec![&mut account, &mut Account::default()]).unwrap();
+    let ix = create_request(&program_id, &oracle_id, &payer_id, build_request()).unwrap();
+    do_process_instruction(ix, vec![&mut account, &mut payer_account, &mut Account::default(), &mut clock_account(1)]).unwrap();
+    assert_eq!(OracleAccount::unpack(&account.data).unwrap().pending_requests_of(&payer_id), 2);
+  }
+
+  #[test]
+  fn test_process_create_request_fee_schedule() {
+    setup_syscall_stubs();
+    let program_id = Pubkey::new_unique();
+    let oracle_id = Pubkey::new_unique();
+    let payer_id = Pubkey::new_unique();
+    let mut oracle_account = initialize_oracle_account(&program_id, &oracle_id);
+    let mut payer_account = Account::new(10 * REQUEST_FEE, 0, &system_program::id());
+
+    let mut fee_schedule = FeeSchedule {
+      task_fees: [1_000, 10, 1, 2_000],
+      ..FeeSchedule::default()
+    };
+    fee_schedule.host_fees[0] = HostFee::new(b"ftx.us", 3_000).unwrap();
+    let ix = set_fee_schedule(&program_id, &oracle_id, &Pubkey::new_unique(), fee_schedule.clone()).unwrap();
+    assert_eq!(
+      do_process_instruction(ix, vec![&mut oracle_account, &mut Account::default()]),
+      Err(OracleError::IncorrectAuthority.into())
+    );
+    let ix = set_fee_schedule(&program_id, &oracle_id, &ADMIN, fee_schedule.clone()).unwrap();
+    do_process_instruction(ix, vec![&mut oracle_account, &mut Account::default()]).unwrap();
+    let header = OracleAccount::unpack(&oracle_account.data).unwrap().header;
+    assert_eq!(header.fee_schedule, fee_schedule);
+
+    // the base fee alone no longer covers the tasks
+    let ix = create_request(&program_id, &oracle_id, &payer_id, build_request()).unwrap();
+    assert_eq!(
+      do_process_instruction(ix, vec![
+        &mut oracle_account,
+        &mut payer_account,
+        &mut Account::default(),
+        &mut clock_account(1),
+      ]),
+      Err(OracleError::InsufficientFee.into())
+    );
+
+    // the ftx.us host price replaces the HttpGet price
+    let price = REQUEST_FEE + 3_000 + 10 + 1;
+    let mut request = build_request();
+    request.fee = price;
+    let ix = create_request(&program_id, &oracle_id, &payer_id, request).unwrap();
+    do_process_instruction(ix, vec![
+      &mut oracle_account,
+      &mut payer_account,
+      &mut Account::default(),
+      &mut clock_account(1),
+    ]).unwrap();
+    let request = OracleAccount::unpack(&oracle_account.data).unwrap().request_queue.requests[0].clone().unwrap();
+    assert_eq!(request.fee, price);
+    assert_eq!(oracle_account.lamports, price);
+  }
+
+  #[test]
+  fn test_process_admin_config() {
+    setup_syscall_stubs();
+    let program_id = Pubkey::new_unique();
+    let oracle_id = Pubkey::new_unique();
+    let payer_id = Pubkey::new_unique();
+    let new_admin = Pubkey::new_unique();
+    let new_node_authority = Pubkey::new_unique();
+    let mut oracle_account = initialize_oracle_account(&program_id, &oracle_id);
+    let mut payer_account = Account::new(10 * REQUEST_FEE, 0, &system_program::id());
+    let mut node_account = active_node_account(&program_id, &oracle_id, &new_node_authority);
+
+    // no HTTP Task can hold a longer url
+    let ix = set_config(&program_id, &oracle_id, &ADMIN, REQUEST_FEE, REQUEST_EXPIRY_SLOTS, Task::MAX_URL_LEN as u8 + 1, ALL_TASK_KINDS, 1).unwrap();
+    assert_eq!(
+      do_process_instruction(ix, vec![&mut oracle_account, &mut Account::default()]),
+      Err(ProgramError::InvalidArgument)
+    );
+    // Responses are not aggregated yet, so a single answer settles a Request
+    let ix = set_config(&program_id, &oracle_id, &ADMIN, REQUEST_FEE, REQUEST_EXPIRY_SLOTS, 34, ALL_TASK_KINDS, 2).unwrap();
+    assert_eq!(
+      do_process_instruction(ix, vec![&mut oracle_account, &mut Account::default()]),
+      Err(ProgramError::InvalidArgument)
+    );
+    // the url of the sample request is 34 bytes and its last task is a Uint32
+    let ix = set_config(&program_id, &oracle_id, &ADMIN, 2 * REQUEST_FEE, 10, 33, ALL_TASK_KINDS, 1).unwrap();
+    do_process_instruction(ix, vec![&mut oracle_account, &mut Account::default()]).unwrap();
+    let header = OracleAccount::unpack(&oracle_account.data).unwrap().header;
+    assert_eq!(header, OracleHeader {
+      request_fee: 2 * REQUEST_FEE,
+      request_expiry_slots: 10,
+      max_url_len: 33,
+      ..expected_header()
+    });
+    let mut request = build_request();
+    request.fee = 2 * REQUEST_FEE;
+    let ix = create_request(&program_id, &oracle_id, &payer_id, request.clone()).unwrap();
+    assert_eq!(
+      do_process_instruction(ix, vec![&mut oracle_account, &mut payer_account, &mut Account::default(), &mut clock_account(1)]),
+      Err(OracleError::TaskNotAllowed.into())
+    );
+    let ix = set_config(&program_id, &oracle_id, &ADMIN, 2 * REQUEST_FEE, 10, 34, 0b011, 1).unwrap();
+    do_process_instruction(ix, vec![&mut oracle_account, &mut Account::default()]).unwrap();
+    let ix = create_request(&program_id, &oracle_id, &payer_id, request.clone()).unwrap();
+    assert_eq!(
+      do_process_instruction(ix, vec![&mut oracle_account, &mut payer_account, &mut Account::default(), &mut clock_account(1)]),
+      Err(OracleError::TaskNotAllowed.into())
+    );
+    let ix = set_config(&program_id, &oracle_id, &ADMIN, 2 * REQUEST_FEE, 10, 34, ALL_TASK_KINDS, 1).unwrap();
+    do_process_instruction(ix, vec![&mut oracle_account, &mut Account::default()]).unwrap();
+    let request_callback = request.call_back_program;
+    let ix = create_request(&program_id, &oracle_id, &payer_id, request).unwrap();
+    do_process_instruction(ix, vec![&mut oracle_account, &mut payer_account, &mut Account::default(), &mut clock_account(1)]).unwrap();
+
+    let ix = set_node_authority(&program_id, &oracle_id, &ADMIN, &new_node_authority).unwrap();
+    do_process_instruction(ix, vec![&mut oracle_account, &mut Account::default()]).unwrap();
+    let ix = pause(&program_id, &oracle_id, &ADMIN).unwrap();
+    do_process_instruction(ix, vec![&mut oracle_account, &mut Account::default()]).unwrap();
+    let header = OracleAccount::unpack(&oracle_account.data).unwrap().header;
+    assert_eq!(header.node_authority, new_node_authority);
+    assert!(header.is_paused);
+
+    // a paused Oracle takes neither Requests nor Responses
+    let mut request = build_request();
+    request.fee = 2 * REQUEST_FEE;
+    let ix = create_request(&program_id, &oracle_id, &payer_id, request).unwrap();
+    assert_eq!(
+      do_process_instruction(ix, vec![&mut oracle_account, &mut payer_account, &mut Account::default(), &mut clock_account(1)]),
+      Err(OracleError::OraclePaused.into())
+    );
+    let response = Response {
+      data: 15439u32.to_le_bytes(),
+      request_queue_index: 0,
+    };
+    let ix = handle_response(&program_id, &oracle_id, &request_callback, &new_node_authority, response).unwrap();
+    assert_eq!(
+      do_process_instruction(ix, vec![&mut oracle_account, &mut Account::default(), &mut Account::default(), &mut clock_account(1), &mut node_account]),
+      Err(OracleError::OraclePaused.into())
+    );
+    let ix = unpause(&program_id, &oracle_id, &ADMIN).unwrap();
+    do_process_instruction(ix, vec![&mut oracle_account, &mut Account::default()]).unwrap();
+    assert!(!OracleAccount::unpack(&oracle_account.data).unwrap().header.is_paused);
+
+    // a requester may not hold more queue slots than the limit
+    let ix = set_requester_limit(&program_id, &oracle_id, &ADMIN, 1).unwrap();
+    do_process_instruction(ix, vec![&mut oracle_account, &mut Account::default()]).unwrap();
+    let mut request = build_request();
+    request.fee = 2 * REQUEST_FEE;
+    let ix = create_request(&program_id, &oracle_id, &payer_id, request.clone()).unwrap();
+    assert_eq!(
+      do_process_instruction(ix, vec![&mut oracle_account, &mut payer_account, &mut Account::default(), &mut clock_account(1)]),
+      Err(OracleError::RequesterLimitReached.into())
+    );
+    let other_payer_id = Pubkey::new_unique();
+    let mut other_payer_account = Account::new(10 * REQUEST_FEE, 0, &system_program::id());
+    let ix = create_request(&program_id, &oracle_id, &other_payer_id, request.clone()).unwrap();
+    do_process_instruction(ix, vec![&mut oracle_account, &mut other_payer_account, &mut Account::default(), &mut clock_account(1)]).unwrap();
+    let ix = set_requester_limit(&program_id, &oracle_id, &ADMIN, 0).unwrap();
+    do_process_instruction(ix, vec![&mut oracle_account, &mut Account::default()]).unwrap();
+    let ix = create_request(&program_id, &oracle_id, &payer_id, request).unwrap();
+    do_process_instruction(ix, vec![&mut oracle_account, &mut payer_account, &mut Account::default(), &mut clock_account(1)]).unwrap();
+
+    // Requests may only refer to secrets the Oracle advertises, sent to their host
+    let api_key = vec![Header::new(b"X-Api-Key", b"secret:coingecko_pro")];
+    let mut request = build_request();
+    request.fee = 2 * REQUEST_FEE;
+    request.tasks[0] = Task::HttpGet(GetArgs::new(GetParams {
+      get: *GenericArray::from_slice(b"https://pro-api.coingecko.com/ping"),
+    })).with_headers(api_key.clone()).unwrap();
+    let ix = create_request(&program_id, &oracle_id, &other_payer_id, request.clone()).unwrap();
+    assert_eq!(
+      do_process_instruction(ix, vec![&mut oracle_account, &mut other_payer_account, &mut Account::default(), &mut clock_account(1)]),
+      Err(OracleError::UnknownSecret.into())
+    );
+    let mut secrets = OracleSecrets::default();
+    secrets[0] = OracleSecret::new(b"coingecko pro", b"pro-api.coingecko.com").unwrap();
+    let ix = set_secrets(&program_id, &oracle_id, &ADMIN, secrets).unwrap();
+    assert_eq!(
+      do_process_instruction(ix, vec![&mut oracle_account, &mut Account::default()]),
+      Err(ProgramError::InvalidArgument)
+    );
+    secrets[0] = OracleSecret::new(b"coingecko_pro", b"pro-api.coingecko.com").unwrap();
+    let ix = set_secrets(&program_id, &oracle_id, &ADMIN, secrets).unwrap();
+    do_process_instruction(ix, vec![&mut oracle_account, &mut Account::default()]).unwrap();
+    assert_eq!(OracleAccount::unpack(&oracle_account.data).unwrap().header.supported_secrets, secrets);
+    // a supported secret can't be sent to another host
+    let mut stealing_request = build_request();
+    stealing_request.fee = 2 * REQUEST_FEE;
+    stealing_request.tasks[0] = stealing_request.tasks[0].clone().with_headers(api_key).unwrap();
+    let ix = create_request(&program_id, &oracle_id, &other_payer_id, stealing_request).unwrap();
+    assert_eq!(
+      do_process_instruction(ix, vec![&mut oracle_account, &mut other_payer_account, &mut Account::default(), &mut clock_account(1)]),
+      Err(OracleError::UnknownSecret.into())
+    );
+    let ix = create_request(&program_id, &oracle_id, &other_payer_id, request).unwrap();
+    do_process_instruction(ix, vec![&mut oracle_account, &mut other_payer_account, &mut Account::default(), &mut clock_account(1)]).unwrap();
+
+    // the admin only changes once the proposed admin accepts
+    let ix = accept_admin(&program_id, &oracle_id, &new_admin).unwrap();
+    assert_eq!(
+      do_process_instruction(ix, vec![&mut oracle_account, &mut Account::default()]),
+      Err(OracleError::IncorrectAuthority.into())
+    );
+    let ix = transfer_admin(&program_id, &oracle_id, &ADMIN, &new_admin).unwrap();
+    do_process_instruction(ix, vec![&mut oracle_account, &mut Account::default()]).unwrap();
+    let header = OracleAccount::unpack(&oracle_account.data).unwrap().header;
+    assert_eq!(header.admin, ADMIN);
+    assert_eq!(header.pending_admin, new_admin);
+    let ix = accept_admin(&program_id, &oracle_id, &Pubkey::new_unique()).unwrap();
+    assert_eq!(
+      do_process_instruction(ix, vec![&mut oracle_account, &mut Account::default()]),
+      Err(OracleError::IncorrectAuthority.into())
+    );
+    let ix = accept_admin(&program_id, &oracle_id, &new_admin).unwrap();
+    do_process_instruction(ix, vec![&mut oracle_account, &mut Account::default()]).unwrap();
+    let header = OracleAccount::unpack(&oracle_account.data).unwrap().header;
+    assert_eq!(header.admin, new_admin);
+    assert_eq!(header.pending_admin, Pubkey::default());
+
+    // the previous admin lost its rights
+    let ix = pause(&program_id, &oracle_id, &ADMIN).unwrap();
+    assert_eq!(
+      do_process_instruction(ix, vec![&mut oracle_account, &mut Account::default()]),
+      Err(OracleError::IncorrectAuthority.into())
+    );
   }
 
   #[test]
@@ -263,15 +2452,20 @@ mod tests {
     let system_program = Pubkey::default();
     let program_id = Pubkey::new_unique();
     let oracle_id = Pubkey::new_unique();
-    let mut account = Account::new(0, OracleAccount::LEN, &program_id);
+    let requester = Pubkey::new_unique();
+    let mut account = Account::new(2 * REQUEST_FEE, OracleAccount::LEN, &program_id);
     let mut client_program_account = Account::new(0, 0, &system_program);
-    let request1 = build_request();
-    let mut request2 = build_request();
-    request2.index = 1;
+    let mut node_authority_account = Account::default();
+    let mut node_account = active_node_account(&program_id, &oracle_id, &NODE_AUTHORITY);
+    let mut request1 = build_escrowed_request(&requester, 0, 1);
+    request1.call_back_program = CLIENT_PROGRAM_ID;
+    let mut request2 = build_escrowed_request(&requester, 1, 1);
+    request2.call_back_program = CLIENT_PROGRAM_ID;
     let request_queue = RequestQueue {
-      requests: Box::new([Some(request1), Some(request2), None, None, None, None, None, None, None, None]),
+      requests: vec![Some(request1), Some(request2), None, None, None, None, None, None, None, None],
     };
     let oracle_account_data = OracleAccount {
+      header: expected_header(),
       request_queue,
     };
     OracleAccount::pack(oracle_account_data, &mut account.data).unwrap();
@@ -281,27 +2475,1018 @@ mod tests {
       data: response_val.to_le_bytes(),
       request_queue_index: 1,
     };
-    let instruction = OracleInstruction::HandleResponse(response);
-    let mut data = [0u8; OracleInstruction::LEN];
-    OracleInstruction::pack(instruction, &mut data).unwrap();
-
-    let ix = Instruction {
-      program_id: program_id,
-      accounts: vec![AccountMeta::new(oracle_id, false), AccountMeta::new(CLIENT_PROGRAM_ID, false)],
-      data: data.to_vec(),
-    };
-    do_process_instruction(ix, vec![&mut account, &mut client_program_account]).unwrap();
+    let ix = handle_response(&program_id, &oracle_id, &CLIENT_PROGRAM_ID, &NODE_AUTHORITY, response).unwrap();
+    do_process_instruction(ix, vec![&mut account, &mut client_program_account, &mut node_authority_account, &mut clock_account(1), &mut node_account]).unwrap();
     let deserialized_oracle_account = OracleAccount::unpack(&account.data).unwrap();
 
-    let request = build_request();
+    let mut request = build_escrowed_request(&requester, 0, 1);
+    request.call_back_program = CLIENT_PROGRAM_ID;
+    // the answer is kept for Requests accepting a cached one
+    let mut header = expected_header();
+    header.answer_cache.insert(request.task_hash(), response_val.to_le_bytes(), 1);
     let request_queue = RequestQueue {
-      requests: Box::new([Some(request), None, None, None, None, None, None, None, None, None]),
+      requests: vec![Some(request), None, None, None, None, None, None, None, None, None],
     };
     let expected_oracle_account_data = OracleAccount {
+      header,
       request_queue,
     };
 
     assert_eq!(deserialized_oracle_account, expected_oracle_account_data);
+    // the escrowed fee is released to the node
+    assert_eq!(account.lamports, REQUEST_FEE);
+    assert_eq!(node_authority_account.lamports, REQUEST_FEE);
+
+    // the slot has already been answered
+    let response = Response {
+      data: response_val.to_le_bytes(),
+      request_queue_index: 1,
+    };
+    let ix = handle_response(&program_id, &oracle_id, &CLIENT_PROGRAM_ID, &NODE_AUTHORITY, response).unwrap();
+    assert_eq!(
+      do_process_instruction(ix, vec![&mut account, &mut client_program_account, &mut node_authority_account, &mut clock_account(1), &mut node_account]),
+      Err(OracleError::InvalidRequestIndex.into())
+    );
+
+    // only the node authority may respond
+    let response = Response {
+      data: response_val.to_le_bytes(),
+      request_queue_index: 0,
+    };
+    let ix = handle_response(&program_id, &oracle_id, &CLIENT_PROGRAM_ID, &Pubkey::new_unique(), response).unwrap();
+    assert_eq!(
+      do_process_instruction(ix, vec![&mut account, &mut client_program_account, &mut node_authority_account, &mut clock_account(1), &mut node_account]),
+      Err(OracleError::IncorrectAuthority.into())
+    );
+
+    // the callback must go to the program that made the Request
+    let response = Response {
+      data: response_val.to_le_bytes(),
+      request_queue_index: 0,
+    };
+    let ix = handle_response(&program_id, &oracle_id, &Pubkey::new_unique(), &NODE_AUTHORITY, response).unwrap();
+    assert_eq!(
+      do_process_instruction(ix, vec![&mut account, &mut client_program_account, &mut node_authority_account, &mut clock_account(1), &mut node_account]),
+      Err(OracleError::IncorrectCallbackProgram.into())
+    );
+
+    // with a minimum stake the answer must come from a registered node of the node authority
+    let set_min_stake = |account: &mut Account, min_stake| {
+      let mut oracle_data = OracleAccount::unpack(&account.data).unwrap();
+      oracle_data.header.min_stake = min_stake;
+      OracleAccount::pack(oracle_data, &mut account.data).unwrap();
+    };
+    set_min_stake(&mut account, 1);
+    let response = Response {
+      data: response_val.to_le_bytes(),
+      request_queue_index: 0,
+    };
+    let ix = handle_response(&program_id, &oracle_id, &CLIENT_PROGRAM_ID, &NODE_AUTHORITY, response).unwrap();
+    let mut other_node_account = active_node_account(&program_id, &oracle_id, &Pubkey::new_unique());
+    assert_eq!(
+      do_process_instruction(ix.clone(), vec![&mut account, &mut client_program_account, &mut node_authority_account, &mut clock_account(1), &mut other_node_account]),
+      Err(OracleError::IncorrectAuthority.into())
+    );
+    assert_eq!(
+      do_process_instruction(ix.clone(), vec![&mut account, &mut client_program_account, &mut node_authority_account, &mut clock_account(1), &mut Account::default()]),
+      Err(ProgramError::IncorrectProgramId)
+    );
+    let mut node = NodeAccount::unpack(&node_account.data).unwrap();
+    node.stake = 1;
+    node.deregistered_slot = 1;
+    NodeAccount::pack(node, &mut node_account.data).unwrap();
+    assert_eq!(
+      do_process_instruction(ix.clone(), vec![&mut account, &mut client_program_account, &mut node_authority_account, &mut clock_account(1), &mut node_account]),
+      Err(OracleError::InactiveNode.into())
+    );
+
+    // without one the node authority answers on its own
+    set_min_stake(&mut account, 0);
+    do_process_instruction(ix, vec![&mut account, &mut client_program_account, &mut node_authority_account, &mut clock_account(1), &mut Account::default()]).unwrap();
+    assert!(OracleAccount::unpack(&account.data).unwrap().request_queue.requests[0].is_none());
+  }
+
+  #[test]
+  fn test_process_handle_responses() {
+    setup_syscall_stubs();
+    let program_id = Pubkey::new_unique();
+    let oracle_id = Pubkey::new_unique();
+    let requester = Pubkey::new_unique();
+    let mut account = Account::new(3 * REQUEST_FEE, OracleAccount::LEN, &program_id);
+    let mut client_program_account = Account::default();
+    let mut other_client_program_account = Account::default();
+    let mut node_authority_account = Account::default();
+    let mut node_account = active_node_account(&program_id, &oracle_id, &NODE_AUTHORITY);
+    // any program the stubbed invoke accepts
+    let other_client_program_id = TTP_ORACLE_PROGRAM_ID;
+    let mut requests = vec![None; REQUEST_QUEUE_SIZE];
+    for index in 0..3 {
+      let mut request = build_escrowed_request(&requester, index, 1);
+      request.call_back_program = if index == 1 { other_client_program_id } else { CLIENT_PROGRAM_ID };
+      requests[index as usize] = Some(request);
+    }
+    let oracle_account_data = OracleAccount {
+      header: expected_header(),
+      request_queue: RequestQueue { requests },
+    };
+    OracleAccount::pack(oracle_account_data, &mut account.data).unwrap();
+    let batch = |indexes: &[u8]| -> Vec<(Pubkey, Response)> {
+      indexes.iter().map(|index| (
+        if *index == 1 { other_client_program_id } else { CLIENT_PROGRAM_ID },
+        Response { data: (*index as u32).to_le_bytes(), request_queue_index: *index },
+      )).collect()
+    };
+
+    // slot 3 is empty so an atomic batch answers nothing
+    let ix = handle_responses(&program_id, &oracle_id, &NODE_AUTHORITY, batch(&[0, 3]), true).unwrap();
+    assert_eq!(
+      do_process_instruction(ix, vec![
+        &mut account,
+        &mut node_authority_account,
+        &mut clock_account(2),
+        &mut node_account,
+        &mut client_program_account,
+        &mut Account::default(),
+      ]),
+      Err(OracleError::InvalidRequestIndex.into())
+    );
+
+    // otherwise the empty slot and the repeated index are skipped
+    let ix = handle_responses(&program_id, &oracle_id, &NODE_AUTHORITY, batch(&[0, 3, 1, 0]), false).unwrap();
+    do_process_instruction(ix, vec![
+      &mut account,
+      &mut node_authority_account,
+      &mut clock_account(2),
+      &mut node_account,
+      &mut client_program_account,
+      &mut Account::default(),
+      &mut other_client_program_account,
+      &mut Account::default(),
+    ]).unwrap();
+    let oracle_data = OracleAccount::unpack(&account.data).unwrap();
+    let mut expected_request = build_escrowed_request(&requester, 2, 1);
+    expected_request.call_back_program = CLIENT_PROGRAM_ID;
+    let mut expected_requests = vec![None; REQUEST_QUEUE_SIZE];
+    expected_requests[2] = Some(expected_request);
+    assert_eq!(oracle_data.request_queue.requests, expected_requests);
+    assert_eq!(account.lamports, REQUEST_FEE);
+    assert_eq!(node_authority_account.lamports, 2 * REQUEST_FEE);
+
+    // only the node authority may respond
+    let ix = handle_responses(&program_id, &oracle_id, &Pubkey::new_unique(), batch(&[2]), false).unwrap();
+    assert_eq!(
+      do_process_instruction(ix, vec![
+        &mut account,
+        &mut node_authority_account,
+        &mut clock_account(2),
+        &mut node_account,
+        &mut client_program_account,
+      ]),
+      Err(OracleError::IncorrectAuthority.into())
+    );
+  }
+
+  #[test]
+  fn test_process_duplicate_and_cached_responses() {
+    setup_syscall_stubs();
+    let program_id = Pubkey::new_unique();
+    let oracle_id = Pubkey::new_unique();
+    let requester = Pubkey::new_unique();
+    let mut account = Account::new(4 * REQUEST_FEE, OracleAccount::LEN, &program_id);
+    let mut client_program_account = Account::default();
+    let mut node_authority_account = Account::default();
+    let mut node_account = active_node_account(&program_id, &oracle_id, &NODE_AUTHORITY);
+    // any program the stubbed invoke accepts
+    let other_client_program_id = TTP_ORACLE_PROGRAM_ID;
+    let mut requests = vec![None; REQUEST_QUEUE_SIZE];
+    for index in 0..4 {
+      let mut request = build_escrowed_request(&requester, index, 1);
+      request.call_back_program = CLIENT_PROGRAM_ID;
+      requests[index as usize] = Some(request);
+    }
+    // the same Tasks for another program
+    requests[1].as_mut().unwrap().call_back_program = other_client_program_id;
+    // other Tasks
+    requests[2].as_mut().unwrap().tasks[2] = Task::JsonParse(JsonParseArgs { path: *b"result.bid\0\0" });
+    // accepts answers up to 10 slots old
+    requests[3].as_mut().unwrap().max_answer_age_slots = 10;
+    let oracle_account_data = OracleAccount {
+      header: expected_header(),
+      request_queue: RequestQueue { requests: requests.clone() },
+    };
+    OracleAccount::pack(oracle_account_data, &mut account.data).unwrap();
+
+    // nothing is cached yet
+    let ix = serve_cached_response(&program_id, &oracle_id, &CLIENT_PROGRAM_ID, &NODE_AUTHORITY, 3).unwrap();
+    assert_eq!(
+      do_process_instruction(ix, vec![&mut account, &mut client_program_account, &mut node_authority_account, &mut clock_account(4), &mut node_account]),
+      Err(OracleError::NoCachedAnswer.into())
+    );
+
+    // the identical Request of the program passed along is answered too
+    let response = Response { data: 15439u32.to_le_bytes(), request_queue_index: 0 };
+    let ix = handle_response_with_duplicates(&program_id, &oracle_id, &CLIENT_PROGRAM_ID, &NODE_AUTHORITY, response, &[other_client_program_id]).unwrap();
+    do_process_instruction(ix, vec![
+      &mut account,
+      &mut client_program_account,
+      &mut node_authority_account,
+      &mut clock_account(2),
+      &mut node_account,
+      &mut Account::default(),
+    ]).unwrap();
+    let oracle_data = OracleAccount::unpack(&account.data).unwrap();
+    assert_eq!(oracle_data.request_queue.requests[0], None);
+    assert_eq!(oracle_data.request_queue.requests[1], None);
+    assert_eq!(oracle_data.request_queue.requests[2], requests[2]);
+    assert_eq!(oracle_data.request_queue.requests[3], requests[3]);
+    assert_eq!(node_authority_account.lamports, 2 * REQUEST_FEE);
+    let task_hash = requests[0].as_ref().unwrap().task_hash();
+    assert_eq!(oracle_data.header.answer_cache.lookup(&task_hash, 2), Some(15439u32.to_le_bytes()));
+
+    // the cached answer is too old at slot 13 and not for the Tasks of slot 2
+    let ix = serve_cached_response(&program_id, &oracle_id, &CLIENT_PROGRAM_ID, &NODE_AUTHORITY, 3).unwrap();
+    assert_eq!(
+      do_process_instruction(ix, vec![&mut account, &mut client_program_account, &mut node_authority_account, &mut clock_account(13), &mut node_account]),
+      Err(OracleError::NoCachedAnswer.into())
+    );
+    let ix = serve_cached_response(&program_id, &oracle_id, &CLIENT_PROGRAM_ID, &NODE_AUTHORITY, 2).unwrap();
+    assert_eq!(
+      do_process_instruction(ix, vec![&mut account, &mut client_program_account, &mut node_authority_account, &mut clock_account(4), &mut node_account]),
+      Err(OracleError::NoCachedAnswer.into())
+    );
+
+    let ix = serve_cached_response(&program_id, &oracle_id, &CLIENT_PROGRAM_ID, &NODE_AUTHORITY, 3).unwrap();
+    do_process_instruction(ix, vec![&mut account, &mut client_program_account, &mut node_authority_account, &mut clock_account(12), &mut node_account]).unwrap();
+    let oracle_data = OracleAccount::unpack(&account.data).unwrap();
+    assert_eq!(oracle_data.request_queue.requests[3], None);
+    assert_eq!(node_authority_account.lamports, 3 * REQUEST_FEE);
+  }
+
+  #[test]
+  fn test_process_tipped_request() {
+    setup_syscall_stubs();
+    let program_id = Pubkey::new_unique();
+    let oracle_id = Pubkey::new_unique();
+    let payer_id = Pubkey::new_unique();
+    let tip = 2_000;
+    let mut oracle_account = initialize_oracle_account(&program_id, &oracle_id);
+    let mut payer_account = Account::new(REQUEST_FEE + tip, 0, &system_program::id());
+    let mut client_program_account = Account::default();
+    let mut node_authority_account = Account::default();
+    let mut node_account = active_node_account(&program_id, &oracle_id, &NODE_AUTHORITY);
+
+    let mut request = build_request();
+    request.call_back_program = CLIENT_PROGRAM_ID;
+    request.tip = tip;
+    let ix = create_request(&program_id, &oracle_id, &payer_id, request).unwrap();
+    do_process_instruction(ix, vec![
+      &mut oracle_account,
+      &mut payer_account,
+      &mut Account::default(),
+      &mut clock_account(42),
+    ]).unwrap();
+    // the tip is escrowed with the fee
+    assert_eq!(oracle_account.lamports, REQUEST_FEE + tip);
+    assert_eq!(payer_account.lamports, 0);
+    let oracle_data = OracleAccount::unpack(&oracle_account.data).unwrap();
+    assert_eq!(oracle_data.request_queue.next_pending().map(|request| request.tip), Some(tip));
+
+    let response = Response {
+      data: 7u32.to_le_bytes(),
+      request_queue_index: 0,
+    };
+    let ix = handle_response(&program_id, &oracle_id, &CLIENT_PROGRAM_ID, &NODE_AUTHORITY, response).unwrap();
+    do_process_instruction(ix, vec![
+      &mut oracle_account,
+      &mut client_program_account,
+      &mut node_authority_account,
+      &mut clock_account(43),
+      &mut node_account,
+    ]).unwrap();
+    // the node is paid the fee and the tip
+    assert_eq!(oracle_account.lamports, 0);
+    assert_eq!(node_authority_account.lamports, REQUEST_FEE + tip);
+  }
+
+  #[test]
+  fn test_process_cancel_request() {
+    let program_id = Pubkey::new_unique();
+    let oracle_id = Pubkey::new_unique();
+    let requester = Pubkey::new_unique();
+    let mut account = Account::new(REQUEST_FEE, OracleAccount::LEN, &program_id);
+    let mut requester_account = Account::default();
+    let request = build_escrowed_request(&requester, 0, 100);
+    let request_queue = RequestQueue {
+      requests: vec![Some(request), None, None, None, None, None, None, None, None, None],
+    };
+    let oracle_account_data = OracleAccount {
+      header: expected_header(),
+      request_queue,
+    };
+    OracleAccount::pack(oracle_account_data, &mut account.data).unwrap();
+
+    // the node still has time to answer
+    let ix = cancel_request(&program_id, &oracle_id, &requester, 0).unwrap();
+    assert_eq!(
+      do_process_instruction(ix, vec![&mut account, &mut requester_account, &mut clock_account(100 + REQUEST_EXPIRY_SLOTS - 1)]),
+      Err(OracleError::RequestNotExpired.into())
+    );
+
+    // only the requester may cancel
+    let ix = cancel_request(&program_id, &oracle_id, &Pubkey::new_unique(), 0).unwrap();
+    assert_eq!(
+      do_process_instruction(ix, vec![&mut account, &mut requester_account, &mut clock_account(100 + REQUEST_EXPIRY_SLOTS)]),
+      Err(OracleError::IncorrectAuthority.into())
+    );
+
+    let ix = cancel_request(&program_id, &oracle_id, &requester, 0).unwrap();
+    do_process_instruction(ix, vec![&mut account, &mut requester_account, &mut clock_account(100 + REQUEST_EXPIRY_SLOTS)]).unwrap();
+    let deserialized_oracle_account = OracleAccount::unpack(&account.data).unwrap();
+    assert_eq!(deserialized_oracle_account, OracleAccount {
+      header: expected_header(),
+      request_queue: RequestQueue {
+        requests: vec![None, None, None, None, None, None, None, None, None, None],
+      },
+    });
+    assert_eq!(account.lamports, 0);
+    assert_eq!(requester_account.lamports, REQUEST_FEE);
+  }
+
+  #[test]
+  fn test_process_token_fee_request() {
+    setup_syscall_stubs();
+    let program_id = Pubkey::new_unique();
+    let oracle_id = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    let fee_vault_id = Pubkey::new_unique();
+    let payer_id = Pubkey::new_unique();
+    let payer_token_id = Pubkey::new_unique();
+    let node_token_id = Pubkey::new_unique();
+    let (vault_authority, _) = find_vault_authority(&program_id, &oracle_id);
+    let mut oracle_account = Account::new(0, OracleAccount::LEN, &program_id);
+    let mut fee_vault = token_account(&mint, &vault_authority, 0);
+    let mut payer_account = Account::default();
+    let mut payer_token_account = token_account(&mint, &payer_id, 2 * REQUEST_FEE);
+    let mut node_authority_account = Account::default();
+    let mut node_account = active_node_account(&program_id, &oracle_id, &NODE_AUTHORITY);
+    let mut node_token_account = token_account(&mint, &NODE_AUTHORITY, 0);
+
+    // the vault must be owned by the vault authority
+    let mut foreign_vault = token_account(&mint, &Pubkey::new_unique(), 0);
+    let ix = initialize_oracle_with_token_fee(
+      &program_id, &oracle_id, &ADMIN, &NODE_AUTHORITY, &mint, &fee_vault_id, REQUEST_FEE, REQUEST_EXPIRY_SLOTS,
+    ).unwrap();
+    assert_eq!(
+      do_process_instruction(ix.clone(), vec![&mut oracle_account, &mut Account::default(), &mut foreign_vault]),
+      Err(OracleError::IncorrectFeeVault.into())
+    );
+    do_process_instruction(ix, vec![&mut oracle_account, &mut Account::default(), &mut fee_vault]).unwrap();
+    let header = OracleAccount::unpack(&oracle_account.data).unwrap().header;
+    assert!(header.charges_token_fee());
+    assert_eq!(header.fee_vault, fee_vault_id);
+
+    for slot in 1..3 {
+      let mut request = build_request();
+      request.call_back_program = CLIENT_PROGRAM_ID;
+      let ix = create_request_with_token_fee(&program_id, &oracle_id, &payer_id, &payer_token_id, &fee_vault_id, request).unwrap();
+      do_process_instruction(ix, vec![
+        &mut oracle_account,
+        &mut payer_account,
+        &mut Account::default(),
+        &mut clock_account(slot),
+        &mut payer_token_account,
+        &mut fee_vault,
+        &mut Account::default(),
+      ]).unwrap();
+    }
+    // the fees are escrowed in the vault, not in lamports
+    assert_eq!(token_amount(&payer_token_account), 0);
+    assert_eq!(token_amount(&fee_vault), 2 * REQUEST_FEE);
+    assert_eq!(oracle_account.lamports, 0);
+
+    let response = Response {
+      data: 15439u32.to_le_bytes(),
+      request_queue_index: 0,
+    };
+    let ix = handle_response_with_token_fee(
+      &program_id, &oracle_id, &CLIENT_PROGRAM_ID, &NODE_AUTHORITY, &fee_vault_id, &node_token_id, response,
+    ).unwrap();
+    do_process_instruction(ix, vec![
+      &mut oracle_account,
+      &mut Account::default(),
+      &mut node_authority_account,
+      &mut clock_account(3),
+      &mut node_account,
+      &mut fee_vault,
+      &mut node_token_account,
+      &mut Account::default(),
+      &mut Account::default(),
+    ]).unwrap();
+    assert_eq!(token_amount(&fee_vault), REQUEST_FEE);
+    assert_eq!(token_amount(&node_token_account), REQUEST_FEE);
+    assert_eq!(node_authority_account.lamports, 0);
+
+    // the expired Request is refunded in tokens
+    let ix = cancel_request_with_token_fee(&program_id, &oracle_id, &payer_id, &payer_token_id, &fee_vault_id, 1).unwrap();
+    do_process_instruction(ix, vec![
+      &mut oracle_account,
+      &mut payer_account,
+      &mut clock_account(2 + REQUEST_EXPIRY_SLOTS),
+      &mut fee_vault,
+      &mut payer_token_account,
+      &mut Account::default(),
+      &mut Account::default(),
+    ]).unwrap();
+    assert_eq!(token_amount(&fee_vault), 0);
+    assert_eq!(token_amount(&payer_token_account), REQUEST_FEE);
+  }
+
+  #[test]
+  fn test_process_optimistic_response() {
+    setup_syscall_stubs();
+    let program_id = Pubkey::new_unique();
+    let oracle_id = Pubkey::new_unique();
+    let requester = Pubkey::new_unique();
+    let disputer = Pubkey::new_unique();
+    let dispute_bond = 1_000;
+    let stake = 1_000_000;
+    let mut account = Account::new(2 * REQUEST_FEE, OracleAccount::LEN, &program_id);
+    let mut client_program_account = Account::default();
+    let mut node_authority_account = Account::default();
+    let mut disputer_account = Account::new(dispute_bond, 0, &system_program::id());
+    let mut requester_account = Account::default();
+    let mut node_account = Account::new(stake, NodeAccount::LEN, &program_id);
+    NodeAccount::pack(NodeAccount {
+      is_initialized: true,
+      oracle: oracle_id,
+      authority: NODE_AUTHORITY,
+      stake,
+      deregistered_slot: 0,
+    }, &mut node_account.data).unwrap();
+    let mut requests = vec![None; REQUEST_QUEUE_SIZE];
+    for index in 0..2 {
+      let mut request = build_escrowed_request(&requester, index, 1);
+      request.call_back_program = CLIENT_PROGRAM_ID;
+      request.optimistic.dispute_window_slots = 10;
+      requests[index as usize] = Some(request);
+    }
+    OracleAccount::pack(OracleAccount {
+      header: OracleHeader { dispute_bond, ..expected_header() },
+      request_queue: RequestQueue { requests },
+    }, &mut account.data).unwrap();
 
+    // answers are only proposed, the fee stays in escrow
+    for request_queue_index in 0..2 {
+      let response = Response {
+        data: 15439u32.to_le_bytes(),
+        request_queue_index,
+      };
+      let ix = handle_response(&program_id, &oracle_id, &CLIENT_PROGRAM_ID, &NODE_AUTHORITY, response).unwrap();
+      do_process_instruction(ix, vec![&mut account, &mut client_program_account, &mut node_authority_account, &mut clock_account(5), &mut node_account]).unwrap();
+    }
+    let request = OracleAccount::unpack(&account.data).unwrap().request_queue.requests[0].clone().unwrap();
+    assert_eq!(request.optimistic.status, ResponseStatus::Proposed);
+    assert_eq!(request.optimistic.data, 15439u32.to_le_bytes());
+    assert_eq!(request.optimistic.proposed_slot, 5);
+    assert_eq!(account.lamports, 2 * REQUEST_FEE);
+    assert_eq!(node_authority_account.lamports, 0);
+
+    // a proposal can not be answered twice
+    let response = Response {
+      data: 1u32.to_le_bytes(),
+      request_queue_index: 0,
+    };
+    let ix = handle_response(&program_id, &oracle_id, &CLIENT_PROGRAM_ID, &NODE_AUTHORITY, response).unwrap();
+    assert_eq!(
+      do_process_instruction(ix, vec![&mut account, &mut client_program_account, &mut node_authority_account, &mut clock_account(6), &mut node_account]),
+      Err(OracleError::InvalidResponseStatus.into())
+    );
+
+    // no node may leave while a proposal can still be disputed
+    let other_authority = Pubkey::new_unique();
+    let mut other_node_account = active_node_account(&program_id, &oracle_id, &other_authority);
+    let ix = deregister_node(&program_id, &oracle_id, &other_authority).unwrap();
+    assert_eq!(
+      do_process_instruction(ix, vec![&mut account, &mut other_node_account, &mut Account::default(), &mut clock_account(6)]),
+      Err(OracleError::NodeBusy.into())
+    );
+
+    // the first answer is disputed and the dispute upheld
+    let ix = finalize_response(&program_id, &oracle_id, &CLIENT_PROGRAM_ID, &NODE_AUTHORITY, 0).unwrap();
+    assert_eq!(
+      do_process_instruction(ix, vec![&mut account, &mut client_program_account, &mut node_authority_account, &mut clock_account(14)]),
+      Err(OracleError::DisputeWindowOpen.into())
+    );
+    let ix = dispute(&program_id, &oracle_id, &disputer, 0).unwrap();
+    do_process_instruction(ix, vec![&mut account, &mut disputer_account, &mut Account::default(), &mut clock_account(14)]).unwrap();
+    assert_eq!(account.lamports, 2 * REQUEST_FEE + dispute_bond);
+    assert_eq!(disputer_account.lamports, 0);
+
+    let request = OracleAccount::unpack(&account.data).unwrap().request_queue.requests[0].clone().unwrap();
+    assert_eq!(request.optimistic.status, ResponseStatus::Disputed);
+    assert_eq!(request.optimistic.disputer, disputer);
+    let ix = resolve_dispute(&program_id, &oracle_id, &Pubkey::new_unique(), &NODE_AUTHORITY, &request, true, [9u8; 4]).unwrap();
+    assert_eq!(
+      do_process_instruction(ix, vec![
+        &mut account,
+        &mut client_program_account,
+        &mut Account::default(),
+        &mut node_authority_account,
+        &mut disputer_account,
+        &mut requester_account,
+        &mut node_account,
+      ]),
+      Err(OracleError::IncorrectAuthority.into())
+    );
+    let ix = resolve_dispute(&program_id, &oracle_id, &ADMIN, &NODE_AUTHORITY, &request, true, [9u8; 4]).unwrap();
+    do_process_instruction(ix, vec![
+      &mut account,
+      &mut client_program_account,
+      &mut Account::default(),
+      &mut node_authority_account,
+      &mut disputer_account,
+      &mut requester_account,
+      &mut node_account,
+    ]).unwrap();
+    // the bond comes back with the slashed stake and the requester is refunded
+    assert_eq!(disputer_account.lamports, 2 * dispute_bond);
+    assert_eq!(requester_account.lamports, REQUEST_FEE);
+    assert_eq!(node_authority_account.lamports, 0);
+    assert_eq!(NodeAccount::unpack(&node_account.data).unwrap().stake, stake - dispute_bond);
+    assert_eq!(account.lamports, REQUEST_FEE);
+
+    // the second answer goes undisputed and is paid once the window closes
+    let ix = dispute(&program_id, &oracle_id, &disputer, 1).unwrap();
+    assert_eq!(
+      do_process_instruction(ix, vec![&mut account, &mut disputer_account, &mut Account::default(), &mut clock_account(15)]),
+      Err(OracleError::DisputeWindowClosed.into())
+    );
+    let ix = finalize_response(&program_id, &oracle_id, &CLIENT_PROGRAM_ID, &NODE_AUTHORITY, 1).unwrap();
+    do_process_instruction(ix, vec![&mut account, &mut client_program_account, &mut node_authority_account, &mut clock_account(15)]).unwrap();
+    assert_eq!(node_authority_account.lamports, REQUEST_FEE);
+    assert_eq!(account.lamports, 0);
+    assert_eq!(
+      OracleAccount::unpack(&account.data).unwrap().request_queue.requests.iter().filter(|request| request.is_some()).count(),
+      0
+    );
+  }
+
+  #[test]
+  fn test_process_resolve_dispute_without_node() {
+    setup_syscall_stubs();
+    let program_id = Pubkey::new_unique();
+    let oracle_id = Pubkey::new_unique();
+    let requester = Pubkey::new_unique();
+    let disputer = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    let fee_vault_id = Pubkey::new_unique();
+    let requester_token_id = Pubkey::new_unique();
+    let dispute_bond = 1_000;
+    let (vault_authority, bump) = find_vault_authority(&program_id, &oracle_id);
+    let mut account = Account::new(dispute_bond, OracleAccount::LEN, &program_id);
+    let mut fee_vault = token_account(&mint, &vault_authority, REQUEST_FEE);
+    let mut disputer_account = Account::default();
+    let mut requester_account = Account::default();
+    let mut requester_token_account = token_account(&mint, &requester, 0);
+    let mut request = build_escrowed_request(&requester, 0, 1);
+    request.call_back_program = CLIENT_PROGRAM_ID;
+    request.optimistic = OptimisticResponse {
+      dispute_window_slots: 10,
+      status: ResponseStatus::Disputed,
+      data: 15439u32.to_le_bytes(),
+      proposed_slot: 2,
+      disputer,
+      dispute_bond,
+    };
+    let mut requests = vec![None; REQUEST_QUEUE_SIZE];
+    requests[0] = Some(request.clone());
+    OracleAccount::pack(OracleAccount {
+      header: OracleHeader {
+        fee_mint: mint,
+        fee_vault: fee_vault_id,
+        vault_authority_bump: bump,
+        dispute_bond,
+        ..expected_header()
+      },
+      request_queue: RequestQueue { requests },
+    }, &mut account.data).unwrap();
+
+    // the refund must go to a token account of the requester
+    let ix = resolve_dispute_with_token_fee(
+      &program_id, &oracle_id, &ADMIN, &NODE_AUTHORITY, &request, true, [9u8; 4], &fee_vault_id, &requester_token_id,
+    ).unwrap();
+    let mut foreign_token_account = token_account(&mint, &Pubkey::new_unique(), 0);
+    assert_eq!(
+      do_process_instruction(ix.clone(), vec![
+        &mut account,
+        &mut Account::default(),
+        &mut Account::default(),
+        &mut Account::default(),
+        &mut disputer_account,
+        &mut requester_account,
+        &mut Account::default(),
+        &mut fee_vault,
+        &mut foreign_token_account,
+        &mut Account::default(),
+        &mut Account::default(),
+      ]),
+      Err(OracleError::IncorrectAuthority.into())
+    );
+
+    // the node already closed its account, so the dispute is settled without slashing
+    do_process_instruction(ix, vec![
+      &mut account,
+      &mut Account::default(),
+      &mut Account::default(),
+      &mut Account::default(),
+      &mut disputer_account,
+      &mut requester_account,
+      &mut Account::default(),
+      &mut fee_vault,
+      &mut requester_token_account,
+      &mut Account::default(),
+      &mut Account::default(),
+    ]).unwrap();
+    assert_eq!(disputer_account.lamports, dispute_bond);
+    assert_eq!(token_amount(&requester_token_account), REQUEST_FEE);
+    assert_eq!(account.lamports, 0);
+  }
+
+  #[test]
+  fn test_process_settle_to_foreign_token_account() {
+    setup_syscall_stubs();
+    let program_id = Pubkey::new_unique();
+    let oracle_id = Pubkey::new_unique();
+    let requester = Pubkey::new_unique();
+    let disputer = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    let fee_vault_id = Pubkey::new_unique();
+    let node_token_id = Pubkey::new_unique();
+    let dispute_bond = 1_000;
+    let (vault_authority, bump) = find_vault_authority(&program_id, &oracle_id);
+    let mut account = Account::new(dispute_bond, OracleAccount::LEN, &program_id);
+    let mut fee_vault = token_account(&mint, &vault_authority, 2 * REQUEST_FEE);
+    let mut node_authority_account = Account::default();
+    let mut node_token_account = token_account(&mint, &NODE_AUTHORITY, 0);
+    let mut foreign_token_account = token_account(&mint, &Pubkey::new_unique(), 0);
+    let mut requests = vec![None; REQUEST_QUEUE_SIZE];
+    for (index, status) in [ResponseStatus::Proposed, ResponseStatus::Disputed].iter().enumerate() {
+      let mut request = build_escrowed_request(&requester, index as u8, 1);
+      request.call_back_program = CLIENT_PROGRAM_ID;
+      request.optimistic = OptimisticResponse {
+        dispute_window_slots: 10,
+        status: *status,
+        data: 15439u32.to_le_bytes(),
+        proposed_slot: 2,
+        disputer,
+        dispute_bond,
+      };
+      requests[index] = Some(request);
+    }
+    let disputed = requests[1].clone().unwrap();
+    OracleAccount::pack(OracleAccount {
+      header: OracleHeader {
+        fee_mint: mint,
+        fee_vault: fee_vault_id,
+        vault_authority_bump: bump,
+        dispute_bond,
+        ..expected_header()
+      },
+      request_queue: RequestQueue { requests },
+    }, &mut account.data).unwrap();
+
+    // anyone may finalize, but only the node authority's token account is paid
+    let ix = finalize_response_with_token_fee(
+      &program_id, &oracle_id, &CLIENT_PROGRAM_ID, &NODE_AUTHORITY, &fee_vault_id, &node_token_id, 0,
+    ).unwrap();
+    assert_eq!(
+      do_process_instruction(ix.clone(), vec![
+        &mut account.clone(),
+        &mut Account::default(),
+        &mut node_authority_account,
+        &mut clock_account(12),
+        &mut fee_vault,
+        &mut foreign_token_account,
+        &mut Account::default(),
+        &mut Account::default(),
+      ]),
+      Err(OracleError::IncorrectAuthority.into())
+    );
+    do_process_instruction(ix, vec![
+      &mut account,
+      &mut Account::default(),
+      &mut node_authority_account,
+      &mut clock_account(12),
+      &mut fee_vault,
+      &mut node_token_account,
+      &mut Account::default(),
+      &mut Account::default(),
+    ]).unwrap();
+    assert_eq!(token_amount(&node_token_account), REQUEST_FEE);
+    assert_eq!(token_amount(&foreign_token_account), 0);
+
+    // neither may a rejected dispute pay anyone else
+    let ix = resolve_dispute_with_token_fee(
+      &program_id, &oracle_id, &ADMIN, &NODE_AUTHORITY, &disputed, false, [0u8; 4], &fee_vault_id, &node_token_id,
+    ).unwrap();
+    assert_eq!(
+      do_process_instruction(ix.clone(), vec![
+        &mut account,
+        &mut Account::default(),
+        &mut Account::default(),
+        &mut node_authority_account,
+        &mut Account::default(),
+        &mut Account::default(),
+        &mut Account::default(),
+        &mut fee_vault,
+        &mut foreign_token_account,
+        &mut Account::default(),
+        &mut Account::default(),
+      ]),
+      Err(OracleError::IncorrectAuthority.into())
+    );
+    do_process_instruction(ix, vec![
+      &mut account,
+      &mut Account::default(),
+      &mut Account::default(),
+      &mut node_authority_account,
+      &mut Account::default(),
+      &mut Account::default(),
+      &mut Account::default(),
+      &mut fee_vault,
+      &mut node_token_account,
+      &mut Account::default(),
+      &mut Account::default(),
+    ]).unwrap();
+    assert_eq!(token_amount(&node_token_account), 2 * REQUEST_FEE);
+    assert_eq!(node_authority_account.lamports, dispute_bond);
+    assert_eq!(token_amount(&foreign_token_account), 0);
+  }
+
+  #[test]
+  fn test_process_close_oracle() {
+    let program_id = Pubkey::new_unique();
+    let oracle_id = Pubkey::new_unique();
+    let requester = Pubkey::new_unique();
+    let disputer = Pubkey::new_unique();
+    let destination_id = Pubkey::new_unique();
+    let dispute_bond = 1_000;
+    let rent_lamports = 1_000_000;
+    let mut account = Account::new(rent_lamports + 2 * REQUEST_FEE + dispute_bond, OracleAccount::LEN, &program_id);
+    let mut requester_account = Account::default();
+    let mut disputer_account = Account::default();
+    let mut destination_account = Account::default();
+    let request = build_escrowed_request(&requester, 0, 1);
+    let mut disputed_request = build_escrowed_request(&requester, 1, 1);
+    disputed_request.optimistic = OptimisticResponse {
+      dispute_window_slots: 10,
+      status: ResponseStatus::Disputed,
+      disputer,
+      dispute_bond,
+      ..OptimisticResponse::default()
+    };
+    let pending_requests = [request.clone(), disputed_request.clone()];
+    OracleAccount::pack(OracleAccount {
+      header: OracleHeader { dispute_bond, ..expected_header() },
+      request_queue: RequestQueue {
+        requests: vec![Some(request), Some(disputed_request), None, None, None, None, None, None, None, None],
+      },
+    }, &mut account.data).unwrap();
+
+    // pending Requests are only dropped when forced
+    let ix = close_oracle(&program_id, &oracle_id, &ADMIN, &destination_id, false, &[]).unwrap();
+    assert_eq!(
+      do_process_instruction(ix, vec![&mut account, &mut Account::default(), &mut destination_account]),
+      Err(OracleError::RequestsPending.into())
+    );
+    let ix = close_oracle(&program_id, &oracle_id, &Pubkey::new_unique(), &destination_id, true, &pending_requests).unwrap();
+    assert_eq!(
+      do_process_instruction(ix, vec![
+        &mut account,
+        &mut Account::default(),
+        &mut destination_account,
+        &mut requester_account,
+        &mut Account::default(),
+        &mut disputer_account,
+      ]),
+      Err(OracleError::IncorrectAuthority.into())
+    );
+
+    let ix = close_oracle(&program_id, &oracle_id, &ADMIN, &destination_id, true, &pending_requests).unwrap();
+    assert_eq!(ix.accounts[4].pubkey, requester);
+    assert_eq!(ix.accounts[5].pubkey, disputer);
+    let mut second_requester_account = Account::default();
+    do_process_instruction(ix, vec![
+      &mut account,
+      &mut Account::default(),
+      &mut destination_account,
+      &mut requester_account,
+      &mut second_requester_account,
+      &mut disputer_account,
+    ]).unwrap();
+    assert_eq!(requester_account.lamports + second_requester_account.lamports, 2 * REQUEST_FEE);
+    assert_eq!(disputer_account.lamports, dispute_bond);
+    assert_eq!(destination_account.lamports, rent_lamports);
+    assert_eq!(account.lamports, 0);
+    assert_eq!(account.data, vec![0u8; OracleAccount::LEN]);
+
+    // a node still registered with the closed Oracle gets its stake back right away
+    let stake = 1_000;
+    let mut node_account = active_node_account(&program_id, &oracle_id, &NODE_AUTHORITY);
+    node_account.lamports = stake;
+    let mut node_authority_account = Account::default();
+    let ix = unstake(&program_id, &oracle_id, &NODE_AUTHORITY).unwrap();
+    do_process_instruction(ix.clone(), vec![&mut account, &mut node_account, &mut node_authority_account, &mut clock_account(2)]).unwrap();
+    assert_eq!(node_authority_account.lamports, stake);
+    assert_eq!(node_account.data, vec![0u8; NodeAccount::LEN]);
+
+    // also once the runtime reclaimed the Oracle account
+    let mut node_account = active_node_account(&program_id, &oracle_id, &NODE_AUTHORITY);
+    node_account.lamports = stake;
+    do_process_instruction(ix, vec![&mut Account::default(), &mut node_account, &mut node_authority_account, &mut clock_account(2)]).unwrap();
+    assert_eq!(node_authority_account.lamports, 2 * stake);
+  }
+
+  #[test]
+  fn test_process_node_staking() {
+    setup_syscall_stubs();
+    let program_id = Pubkey::new_unique();
+    let oracle_id = Pubkey::new_unique();
+    let stake = 1_000_000;
+    let rent_lamports = Rent::default().minimum_balance(NodeAccount::LEN);
+    let (node_id, _) = find_node_address(&program_id, &oracle_id, &NODE_AUTHORITY);
+    let mut oracle_account = initialize_oracle_account(&program_id, &oracle_id);
+    let mut node_account = Account::new(0, NodeAccount::LEN, &program_id);
+    let mut node_authority_account = Account::new(rent_lamports + stake, 0, &system_program::id());
+    let mut rent_account = create_account(&Rent::default(), 1);
+
+    let ix = set_stake_config(&program_id, &oracle_id, &ADMIN, stake, 100, 0).unwrap();
+    do_process_instruction(ix, vec![&mut oracle_account, &mut Account::default()]).unwrap();
+
+    let ix = register_node(&program_id, &oracle_id, &NODE_AUTHORITY, stake - 1).unwrap();
+    assert_eq!(
+      do_process_instruction(ix, vec![
+        &mut oracle_account,
+        &mut node_account,
+        &mut node_authority_account,
+        &mut Account::default(),
+        &mut rent_account,
+      ]),
+      Err(OracleError::InsufficientStake.into())
+    );
+    let ix = register_node(&program_id, &oracle_id, &NODE_AUTHORITY, stake).unwrap();
+    do_process_instruction(ix, vec![
+      &mut oracle_account,
+      &mut node_account,
+      &mut node_authority_account,
+      &mut Account::default(),
+      &mut rent_account,
+    ]).unwrap();
+    let node = NodeAccount::unpack(&node_account.data).unwrap();
+    assert_eq!(node, NodeAccount {
+      is_initialized: true,
+      oracle: oracle_id,
+      authority: NODE_AUTHORITY,
+      stake,
+      deregistered_slot: 0,
+    });
+    assert_eq!(node_account.lamports, rent_lamports + stake);
+    assert_eq!(node_authority_account.lamports, 0);
+
+    // only the admin may slash
+    let destination_id = Pubkey::new_unique();
+    let mut destination_account = Account::default();
+    let ix = slash_node(&program_id, &oracle_id, &node_id, &Pubkey::new_unique(), &destination_id, 400_000).unwrap();
+    assert_eq!(
+      do_process_instruction(ix, vec![&mut oracle_account, &mut node_account, &mut Account::default(), &mut destination_account]),
+      Err(OracleError::IncorrectAuthority.into())
+    );
+    let ix = slash_node(&program_id, &oracle_id, &node_id, &ADMIN, &destination_id, 400_000).unwrap();
+    do_process_instruction(ix, vec![&mut oracle_account, &mut node_account, &mut Account::default(), &mut destination_account]).unwrap();
+    let node = NodeAccount::unpack(&node_account.data).unwrap();
+    assert_eq!(node.stake, 600_000);
+    assert!(!node.is_active(stake));
+    assert_eq!(destination_account.lamports, 400_000);
+
+    // the stake stays locked until the node deregisters and waits out the cooldown
+    let ix = unstake(&program_id, &oracle_id, &NODE_AUTHORITY).unwrap();
+    assert_eq!(
+      do_process_instruction(ix, vec![&mut oracle_account, &mut node_account, &mut node_authority_account, &mut clock_account(10)]),
+      Err(OracleError::StakeCooldownNotElapsed.into())
+    );
+    // nor may the node leave while it answers for the Oracle
+    let ix = deregister_node(&program_id, &oracle_id, &NODE_AUTHORITY).unwrap();
+    assert_eq!(
+      do_process_instruction(ix.clone(), vec![&mut oracle_account, &mut node_account, &mut node_authority_account, &mut clock_account(10)]),
+      Err(OracleError::NodeBusy.into())
+    );
+    let set_ix = set_node_authority(&program_id, &oracle_id, &ADMIN, &Pubkey::new_unique()).unwrap();
+    do_process_instruction(set_ix, vec![&mut oracle_account, &mut Account::default()]).unwrap();
+    do_process_instruction(ix, vec![&mut oracle_account, &mut node_account, &mut node_authority_account, &mut clock_account(10)]).unwrap();
+    let ix = unstake(&program_id, &oracle_id, &NODE_AUTHORITY).unwrap();
+    assert_eq!(
+      do_process_instruction(ix, vec![&mut oracle_account, &mut node_account, &mut node_authority_account, &mut clock_account(109)]),
+      Err(OracleError::StakeCooldownNotElapsed.into())
+    );
+    let ix = unstake(&program_id, &oracle_id, &NODE_AUTHORITY).unwrap();
+    do_process_instruction(ix, vec![&mut oracle_account, &mut node_account, &mut node_authority_account, &mut clock_account(110)]).unwrap();
+    assert_eq!(node_account.lamports, 0);
+    assert_eq!(node_account.data, vec![0u8; NodeAccount::LEN]);
+    assert_eq!(node_authority_account.lamports, rent_lamports + 600_000);
+  }
+
+  fn clock_account(slot: u64) -> Account {
+    create_account(&Clock { slot, ..Clock::default() }, 1)
   }
-}
\ No newline at end of file
+
+  fn active_node_account(program_id: &Pubkey, oracle_id: &Pubkey, authority: &Pubkey) -> Account {
+    let mut account = Account::new(0, NodeAccount::LEN, program_id);
+    NodeAccount::pack(NodeAccount {
+      is_initialized: true,
+      oracle: *oracle_id,
+      authority: *authority,
+      stake: 0,
+      deregistered_slot: 0,
+    }, &mut account.data).unwrap();
+    account
+  }
+
+  #[test]
+  fn test_process_update_feed() {
+    let program_id = Pubkey::new_unique();
+    let feed_id = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let mut feed_account = Account::new(0, Feed::LEN, &program_id);
+    let mut authority_account = Account::default();
+    let request = build_request();
+
+    let ix = initialize_feed(&program_id, &feed_id, &authority, request.tasks, 100, 50, 1_000).unwrap();
+    do_process_instruction(ix, vec![&mut feed_account, &mut authority_account]).unwrap();
+    let feed = Feed::unpack(&feed_account.data).unwrap();
+    assert_eq!(feed.authority, authority);
+    assert_eq!(feed.round, 0);
+
+    // the first value is always accepted
+    let ix = update_feed(&program_id, &feed_id, &authority, 10_000).unwrap();
+    do_process_instruction(ix, vec![&mut feed_account, &mut authority_account, &mut clock_account(10)]).unwrap();
+    let feed = Feed::unpack(&feed_account.data).unwrap();
+    assert_eq!(feed.value, 10_000);
+    assert_eq!(feed.round, 1);
+    assert_eq!(feed.last_update_slot, 10);
+
+    // a 1% deviation before the heartbeat is accepted
+    let ix = update_feed(&program_id, &feed_id, &authority, 10_101).unwrap();
+    do_process_instruction(ix, vec![&mut feed_account, &mut authority_account, &mut clock_account(20)]).unwrap();
+    let feed = Feed::unpack(&feed_account.data).unwrap();
+    assert_eq!(feed.value, 10_101);
+    assert_eq!(feed.round, 2);
+
+    // a small deviation before the heartbeat is rejected
+    let ix = update_feed(&program_id, &feed_id, &authority, 10_102).unwrap();
+    assert_eq!(
+      do_process_instruction(ix, vec![&mut feed_account, &mut authority_account, &mut clock_account(30)]),
+      Err(OracleError::FeedUpdateNotTriggered.into())
+    );
+
+    // the same value is accepted once the heartbeat is due
+    let ix = update_feed(&program_id, &feed_id, &authority, 10_101).unwrap();
+    do_process_instruction(ix, vec![&mut feed_account, &mut authority_account, &mut clock_account(70)]).unwrap();
+    let feed = Feed::unpack(&feed_account.data).unwrap();
+    assert_eq!(feed.round, 3);
+    assert_eq!(feed.last_update_slot, 70);
+
+    // only the feed authority may update
+    let other_authority = Pubkey::new_unique();
+    let ix = update_feed(&program_id, &feed_id, &other_authority, 20_000).unwrap();
+    assert_eq!(
+      do_process_instruction(ix, vec![&mut feed_account, &mut authority_account, &mut clock_account(80)]),
+      Err(OracleError::IncorrectAuthority.into())
+    );
+
+    // a move of more than 10% trips the breaker without storing the value
+    let ix = update_feed(&program_id, &feed_id, &authority, 20_000).unwrap();
+    do_process_instruction(ix, vec![&mut feed_account, &mut authority_account, &mut clock_account(80)]).unwrap();
+    let feed = Feed::unpack(&feed_account.data).unwrap();
+    assert!(feed.is_tripped);
+    assert_eq!(feed.value, 10_101);
+    assert_eq!(feed.round, 3);
+    let ix = update_feed(&program_id, &feed_id, &authority, 10_101).unwrap();
+    assert_eq!(
+      do_process_instruction(ix, vec![&mut feed_account, &mut authority_account, &mut clock_account(200)]),
+      Err(OracleError::FeedTripped.into())
+    );
+
+    let ix = reset_feed(&program_id, &feed_id, &other_authority, 20_000).unwrap();
+    assert_eq!(
+      do_process_instruction(ix, vec![&mut feed_account, &mut authority_account, &mut clock_account(210)]),
+      Err(OracleError::IncorrectAuthority.into())
+    );
+    let ix = reset_feed(&program_id, &feed_id, &authority, 20_000).unwrap();
+    do_process_instruction(ix, vec![&mut feed_account, &mut authority_account, &mut clock_account(210)]).unwrap();
+    let feed = Feed::unpack(&feed_account.data).unwrap();
+    assert!(!feed.is_tripped);
+    assert_eq!(feed.value, 20_000);
+    assert_eq!(feed.round, 4);
+    assert_eq!(feed.last_update_slot, 210);
+  }
+}
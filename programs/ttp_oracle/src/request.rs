@@ -1,9 +1,15 @@
-use crate::{ PUBLIC_KEY_LEN };
+use crate::{
+  PUBLIC_KEY_LEN,
+  response::{ ResponseData, RESPONSE_DATA_LEN },
+};
 use solana_program::{
+  clock::Slot,
+  hash::{ hash, HASH_BYTES },
   pubkey::Pubkey,
   program_error::ProgramError,
   program_pack::{ IsInitialized, Pack, Sealed },
 };
+use std::cmp::Reverse;
 use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
 use generic_array::{ 
   ArrayLength, 
@@ -14,8 +20,40 @@ use generic_array::{
 pub type RequestIndex = u8;
 
 pub const TASK_ARRAY_SIZE: usize = 3;
+// Number of Task variants
+pub const TASK_KIND_COUNT: usize = 4;
+// Bytes a packed Task holds after its tag
+pub const TASK_DATA_LEN: usize = 96;
+// Most headers an HttpGet or HttpPost Task sends
+pub const MAX_TASK_HEADERS: usize = 4;
+// Header values starting with this refer to a secret held by the node operator
+pub const SECRET_PREFIX: &[u8] = b"secret:";
+// Only urls with this scheme have a host Tasks are priced and sent secrets by
+const HTTPS_SCHEME: &[u8] = b"https://";
+// Headers a Task may not set, the node or its HTTP client owns them
+const DENIED_HEADERS: [&[u8]; 6] = [
+  b"host",
+  b"content-length",
+  b"transfer-encoding",
+  b"connection",
+  b"cookie",
+  b"proxy-authorization",
+];
+// Bitmask allowing every `Task::kind`
+pub const ALL_TASK_KINDS: u8 = (1 << TASK_KIND_COUNT) - 1;
+// Capacity of an Oracle account created without picking one
 pub const REQUEST_QUEUE_SIZE: usize = 10;
+// Most Request slots an Oracle can address with a RequestIndex
+pub const MAX_REQUEST_QUEUE_SIZE: usize = RequestIndex::MAX as usize + 1;
+// Bytes of the bitmap marking which Request slots are occupied
+pub const OCCUPANCY_BITMAP_LEN: usize = MAX_REQUEST_QUEUE_SIZE / 8;
 pub const REQUEST_INDEX_SIZE: usize = 1;
+pub const REQUEST_FEE_SIZE: usize = 8;
+pub const REQUEST_SLOT_SIZE: usize = 8;
+pub const REQUEST_ID_SIZE: usize = 8;
+pub const REQUEST_TIP_SIZE: usize = 8;
+pub const REQUEST_MAX_AGE_SIZE: usize = 8;
+pub const REQUEST_JOB_ID_SIZE: usize = 8;
 
 
 #[derive(Clone, Debug, PartialEq)]
@@ -38,21 +76,135 @@ impl Pack for GetParams<U34> {
 }
 
 
+/// An HTTP header a fetch Task sends along with its request
+#[derive(Clone, Debug, PartialEq)]
+pub struct Header {
+  pub name: Vec<u8>,
+  pub value: Vec<u8>,
+}
+
+impl Header {
+  pub fn new(name: &[u8], value: &[u8]) -> Self {
+    Header { name: name.to_vec(), value: value.to_vec() }
+  }
+
+  /// Whether the value names an operator held secret, e.g. `secret:coingecko_pro`,
+  /// for the node to substitute
+  pub fn is_secret_reference(&self) -> bool {
+    self.secret_name().is_some()
+  }
+
+  /// Name of the operator held secret the value refers to
+  pub fn secret_name(&self) -> Option<&[u8]> {
+    if self.value.len() > SECRET_PREFIX.len() && self.value.starts_with(SECRET_PREFIX) {
+      Some(&self.value[SECRET_PREFIX.len()..])
+    } else {
+      None
+    }
+  }
+
+  /// Whether a Task may send the header. The name must be a plain token and
+  /// the value printable ASCII, so neither can smuggle in further headers.
+  /// Headers the node's HTTP client sets are denied, and `Authorization`
+  /// must refer to an operator held secret rather than carry a credential
+  /// in a public Request.
+  pub fn is_allowed(&self) -> bool {
+    let is_token = !self.name.is_empty()
+      && self.name.iter().all(|b| b.is_ascii_alphanumeric() || *b == b'-');
+    let is_printable = self.value.iter().all(|b| (b' '..=b'~').contains(b));
+    let is_denied = DENIED_HEADERS.iter().any(|denied| self.name.eq_ignore_ascii_case(denied))
+      || (self.name.eq_ignore_ascii_case(b"authorization") && !self.is_secret_reference());
+    is_token && is_printable && !is_denied
+  }
+}
+
+/// Bytes `headers` take in a packed Task, a count then each name and value
+/// prefixed by its length
+fn headers_len(headers: &[Header]) -> usize {
+  1 + headers.iter().map(|header| 2 + header.name.len() + header.value.len()).sum::<usize>()
+}
+
+fn pack_headers(headers: &[Header], dst: &mut [u8]) {
+  dst[0] = headers.len() as u8;
+  let mut offset = 1;
+  for header in headers {
+    for field in [&header.name, &header.value] {
+      dst[offset] = field.len() as u8;
+      dst[offset + 1..offset + 1 + field.len()].copy_from_slice(field);
+      offset += 1 + field.len();
+    }
+  }
+}
+
+fn unpack_headers(src: &[u8]) -> Result<Vec<Header>, ProgramError> {
+  let count = *src.first().ok_or(ProgramError::InvalidAccountData)? as usize;
+  if count > MAX_TASK_HEADERS {
+    return Err(ProgramError::InvalidAccountData);
+  }
+  let mut offset = 1;
+  let mut next_field = || -> Result<Vec<u8>, ProgramError> {
+    let len = *src.get(offset).ok_or(ProgramError::InvalidAccountData)? as usize;
+    let field = src.get(offset + 1..offset + 1 + len).ok_or(ProgramError::InvalidAccountData)?;
+    offset += 1 + len;
+    Ok(field.to_vec())
+  };
+  (0..count).map(|_| Ok(Header { name: next_field()?, value: next_field()? })).collect()
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct GetArgs {
-  pub params: GetParams<U34>
+  pub params: GetParams<U34>,
+  // Sent along with the request, packed after the url. Private so headers
+  // are only set by `Task::with_headers`, which checks they fit.
+  headers: Vec<Header>,
+}
+impl GetArgs {
+  /// Arguments requesting the url in `params` without any headers
+  pub fn new(params: GetParams<U34>) -> Self {
+    GetArgs { params, headers: vec![] }
+  }
+
+  /// Length of the url without its zero padding
+  pub fn url_len(&self) -> usize {
+    let url = self.params.get.as_slice();
+    url.iter().position(|b| *b == 0).unwrap_or(url.len())
+  }
+
+  /// The host part of the url, see `url_host`
+  pub fn host(&self) -> Option<&[u8]> {
+    url_host(self.params.get.as_slice())
+  }
+}
+
+/// The host part of an `https://` url, up to the first `/`, `?`, `#` or 0
+/// byte, urls of HttpGet Tasks being zero padded. None for any other scheme
+/// or a host of anything but ASCII alphanumerics, `-` and `.`, which an HTTP
+/// client could read as a different host than the Oracle does.
+fn url_host(url: &[u8]) -> Option<&[u8]> {
+  if !url.starts_with(HTTPS_SCHEME) {
+    return None;
+  }
+  let rest = &url[HTTPS_SCHEME.len()..];
+  let len = rest.iter().position(|b| matches!(b, b'/' | b'?' | b'#' | 0)).unwrap_or(rest.len());
+  let host = &rest[..len];
+  if host.is_empty() || !host.iter().all(|b| b.is_ascii_alphanumeric() || *b == b'-' || *b == b'.') {
+    return None;
+  }
+  Some(host)
 }
 impl Sealed for GetArgs {}
 impl Pack for GetArgs {
-  const LEN: usize  = 34;
+  const LEN: usize  = TASK_DATA_LEN;
   fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
-    let params = GetParams::unpack_from_slice(src)?;
+    let params = GetParams::unpack_from_slice(&src[..GetParams::LEN])?;
     Ok(GetArgs {
-        params: params
+        params,
+        headers: unpack_headers(&src[GetParams::LEN..GetArgs::LEN])?,
     })
   }
    fn pack_into_slice(&self, dst: &mut [u8]) {
-        dst.copy_from_slice(self.params.get.as_slice());
+        dst[..GetParams::LEN].copy_from_slice(self.params.get.as_slice());
+        pack_headers(&self.headers, &mut dst[GetParams::LEN..]);
     }
 }
 
@@ -74,22 +226,162 @@ impl Pack for JsonParseArgs {
     }
 }
 
+/// Media type an HttpPost Task sends its body as
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ContentType {
+  // application/json, also used by GraphQL and JSON-RPC endpoints
+  Json,
+  // application/x-www-form-urlencoded
+  FormUrlEncoded,
+  // text/plain
+  PlainText,
+}
+
+impl ContentType {
+  fn from_u8(content_type: u8) -> Result<Self, ProgramError> {
+    match content_type {
+      0 => Ok(ContentType::Json),
+      1 => Ok(ContentType::FormUrlEncoded),
+      2 => Ok(ContentType::PlainText),
+      _ => Err(ProgramError::InvalidAccountData),
+    }
+  }
+}
+
+/// Arguments of an HttpPost Task. The fields are private so every HttpPost
+/// Task is built by `Task::http_post` and `Task::with_headers`, which check
+/// it fits a packed Task.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PostArgs {
+  url: Vec<u8>,
+  body: Vec<u8>,
+  content_type: ContentType,
+  headers: Vec<Header>,
+}
+
+impl PostArgs {
+  pub fn url(&self) -> &[u8] {
+    &self.url
+  }
+
+  pub fn body(&self) -> &[u8] {
+    &self.body
+  }
+
+  pub fn content_type(&self) -> ContentType {
+    self.content_type
+  }
+}
+
 #[repr(C, u16)]
 #[derive(Clone, Debug, PartialEq)]
 pub enum Task {
   HttpGet(GetArgs),
   JsonParse(JsonParseArgs),
   Uint32,
+  // Packed as the content type, then the url and the body each prefixed by
+  // its length in a byte, then the headers
+  HttpPost(PostArgs),
 }
 
 impl Task {
-  fn decode_task(kind: [u8; 2], data: [u8; 34]) -> Result<Self, ProgramError> {
+  /// Bytes of an HttpPost Task taken by the content type, the length
+  /// prefixes and the header count
+  pub const HTTP_POST_OVERHEAD: usize = 4;
+  /// Longest url an HTTP Task can hold, that of an HttpPost Task with an
+  /// empty body. HttpGet urls fit the 34 bytes of GetParams.
+  pub const MAX_URL_LEN: usize = TASK_DATA_LEN - Task::HTTP_POST_OVERHEAD;
+
+  /// An HttpPost Task, if its url and body fit a packed Task
+  pub fn http_post(url: &[u8], body: &[u8], content_type: ContentType) -> Result<Self, ProgramError> {
+    if url.len() + body.len() + Task::HTTP_POST_OVERHEAD > TASK_DATA_LEN {
+      return Err(ProgramError::InvalidArgument);
+    }
+    Ok(Task::HttpPost(PostArgs { url: url.to_vec(), body: body.to_vec(), content_type, headers: vec![] }))
+  }
+
+  /// The HttpGet or HttpPost Task sending `headers`, if they fit a packed Task
+  pub fn with_headers(self, headers: Vec<Header>) -> Result<Self, ProgramError> {
+    let task = match self {
+      Task::HttpGet(args) => Task::HttpGet(GetArgs { headers, ..args }),
+      Task::HttpPost(args) => Task::HttpPost(PostArgs { headers, ..args }),
+      _ => return Err(ProgramError::InvalidArgument),
+    };
+    if task.headers().len() > MAX_TASK_HEADERS || task.data_len() > TASK_DATA_LEN {
+      return Err(ProgramError::InvalidArgument);
+    }
+    Ok(task)
+  }
+
+  /// Headers an HTTP Task sends, none for other Tasks
+  pub fn headers(&self) -> &[Header] {
+    match self {
+      Task::HttpGet(args) => &args.headers,
+      Task::HttpPost(args) => &args.headers,
+      _ => &[],
+    }
+  }
+
+  /// Bytes the Task takes after its tag
+  fn data_len(&self) -> usize {
+    match self {
+      Task::HttpGet(args) => GetParams::LEN + headers_len(&args.headers),
+      Task::JsonParse(_) => JsonParseArgs::LEN,
+      Task::Uint32 => 0,
+      Task::HttpPost(args) =>
+        Task::HTTP_POST_OVERHEAD - 1 + args.url.len() + args.body.len() + headers_len(&args.headers),
+    }
+  }
+
+  /// Position of the variant, matches the tag it is packed with
+  pub fn kind(&self) -> usize {
+    match self {
+      Task::HttpGet(_) => 0,
+      Task::JsonParse(_) => 1,
+      Task::Uint32 => 2,
+      Task::HttpPost(_) => 3,
+    }
+  }
+
+  /// Length of the url an HTTP Task requests, without its padding
+  pub fn url_len(&self) -> Option<usize> {
+    match self {
+      Task::HttpGet(args) => Some(args.url_len()),
+      Task::HttpPost(args) => Some(args.url.len()),
+      _ => None,
+    }
+  }
+
+  /// The host an HTTP Task sends its request to
+  pub fn host(&self) -> Option<&[u8]> {
+    match self {
+      Task::HttpGet(args) => args.host(),
+      Task::HttpPost(args) => url_host(&args.url),
+      _ => None,
+    }
+  }
+
+  fn decode_task(kind: [u8; 2], data: [u8; TASK_DATA_LEN]) -> Result<Self, ProgramError> {
     match u16::from_le_bytes(kind) {
       0 => Ok(Task::HttpGet(
         GetArgs::unpack_from_slice(&data)?
       )),
       1 => Ok(Task::JsonParse(JsonParseArgs::unpack_from_slice(&data)?)),
       2 => Ok(Task::Uint32),
+      3 => {
+        let content_type = ContentType::from_u8(data[0])?;
+        let url_len = data[1] as usize;
+        let url = data.get(2..2 + url_len).ok_or(ProgramError::InvalidAccountData)?;
+        let body_start = 2 + url_len + 1;
+        let body_len = *data.get(body_start - 1).ok_or(ProgramError::InvalidAccountData)? as usize;
+        let body = data.get(body_start..body_start + body_len).ok_or(ProgramError::InvalidAccountData)?;
+        Ok(Task::HttpPost(PostArgs {
+          url: url.to_vec(),
+          body: body.to_vec(),
+          content_type,
+          headers: unpack_headers(data.get(body_start + body_len..).ok_or(ProgramError::InvalidAccountData)?)?,
+        }))
+      },
       _ => Err(ProgramError::InvalidAccountData),
     }
   }
@@ -109,6 +401,17 @@ impl Task {
       Task::Uint32 => {
         let tag: u16 = 2;
         kind.copy_from_slice(&tag.to_le_bytes()[0..2]);
+      },
+      Task::HttpPost(PostArgs { url, body, content_type, headers }) => {
+        let tag: u16 = 3;
+        kind.copy_from_slice(&tag.to_le_bytes()[0..2]);
+        let body_start = 2 + url.len() + 1;
+        task_data[0] = *content_type as u8;
+        task_data[1] = url.len() as u8;
+        task_data[2..2 + url.len()].copy_from_slice(url);
+        task_data[body_start - 1] = body.len() as u8;
+        task_data[body_start..body_start + body.len()].copy_from_slice(body);
+        pack_headers(headers, &mut task_data[body_start + body.len()..]);
       }
       // TODO propogate error here?
     }
@@ -116,22 +419,103 @@ impl Task {
 }
 impl Sealed for Task {}
 impl Pack for Task {
-  const LEN: usize  = 36;
+  const LEN: usize  = 2 + TASK_DATA_LEN;
   fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
-    let src = array_ref![src, 0, 36];
-    let (kind, task) = array_refs![src, 2, 34];
-    return Task::decode_task(*kind, *task);
+    let src = array_ref![src, 0, Task::LEN];
+    let (kind, task) = array_refs![src, 2, TASK_DATA_LEN];
+    Task::decode_task(*kind, *task)
   }
    fn pack_into_slice(&self, dst: &mut [u8]) {
-     let dst = array_mut_ref![dst, 0, 36];
+     let dst = array_mut_ref![dst, 0, Task::LEN];
      let (
         tag_dst,
         task_dst,
-      ) = mut_array_refs![dst, 2, 34];
+      ) = mut_array_refs![dst, 2, TASK_DATA_LEN];
       self.encode_task(tag_dst, task_dst)
     }
 }
 
+/// Progress of a Request's answer in optimistic mode
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ResponseStatus {
+  // The node has not answered yet
+  Unanswered,
+  // The node answered, the answer is delivered once the dispute window closes
+  Proposed,
+  // The answer was challenged and waits for the admin to resolve it
+  Disputed,
+}
+
+impl Default for ResponseStatus {
+  fn default() -> Self {
+    ResponseStatus::Unanswered
+  }
+}
+
+/// Optimistic mode state of a Request. With a dispute window of 0 slots
+/// the answer is delivered to the callback program as soon as the node
+/// responds, otherwise it is held as a proposal anyone may dispute.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct OptimisticResponse {
+  // Slots a proposed answer can be disputed for, set by the requester
+  pub dispute_window_slots: u64,
+  pub status: ResponseStatus,
+  // The proposed answer
+  pub data: ResponseData,
+  pub proposed_slot: Slot,
+  // The account that posted the dispute bond
+  pub disputer: Pubkey,
+  pub dispute_bond: u64,
+}
+
+impl OptimisticResponse {
+  pub fn is_optimistic(&self) -> bool {
+    self.dispute_window_slots > 0
+  }
+
+  /// Whether the proposed answer can still be disputed at `slot`
+  pub fn in_dispute_window(&self, slot: Slot) -> bool {
+    slot < self.proposed_slot.saturating_add(self.dispute_window_slots)
+  }
+}
+
+impl Sealed for OptimisticResponse {}
+impl Pack for OptimisticResponse {
+  const LEN: usize = 8 + 1 + RESPONSE_DATA_LEN + 8 + PUBLIC_KEY_LEN + 8;
+
+  fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+    let src = array_ref![src, 0, OptimisticResponse::LEN];
+    let (dispute_window_slots, status, data, proposed_slot, disputer, dispute_bond) =
+      array_refs![src, 8, 1, RESPONSE_DATA_LEN, 8, PUBLIC_KEY_LEN, 8];
+    let status = match status {
+      [0] => ResponseStatus::Unanswered,
+      [1] => ResponseStatus::Proposed,
+      [2] => ResponseStatus::Disputed,
+      _ => return Err(ProgramError::InvalidAccountData),
+    };
+    Ok(OptimisticResponse {
+      dispute_window_slots: u64::from_le_bytes(*dispute_window_slots),
+      status,
+      data: *data,
+      proposed_slot: u64::from_le_bytes(*proposed_slot),
+      disputer: Pubkey::new(disputer),
+      dispute_bond: u64::from_le_bytes(*dispute_bond),
+    })
+  }
+
+  fn pack_into_slice(&self, dst: &mut [u8]) {
+    let dst = array_mut_ref![dst, 0, OptimisticResponse::LEN];
+    let (dispute_window_slots, status, data, proposed_slot, disputer, dispute_bond) =
+      mut_array_refs![dst, 8, 1, RESPONSE_DATA_LEN, 8, PUBLIC_KEY_LEN, 8];
+    *dispute_window_slots = self.dispute_window_slots.to_le_bytes();
+    status[0] = self.status as u8;
+    *data = self.data;
+    *proposed_slot = self.proposed_slot.to_le_bytes();
+    *disputer = self.disputer.to_bytes();
+    *dispute_bond = self.dispute_bond.to_le_bytes();
+  }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct Request {
   // For phase 1 only 3 tasks are required
@@ -139,19 +523,42 @@ pub struct Request {
   pub tasks: [Task; TASK_ARRAY_SIZE], 
   pub call_back_program: Pubkey,
   pub index: RequestIndex,
+  // The account that paid the fee, refunded if the Request is cancelled
+  pub requester: Pubkey,
+  // Fee offered by the requester, held in escrow by the Oracle until the
+  // Request is answered
+  pub fee: u64,
+  pub created_slot: Slot,
+  // Sequence number set by the Oracle, increasing in arrival order
+  pub id: u64,
+  // Optional payment to the node on top of the fee, Requests with a higher
+  // tip are answered first
+  pub tip: u64,
+  // Oldest answer, in slots, the requester accepts from the Oracle's answer
+  // cache instead of a fresh one. 0 always asks for a fresh answer
+  pub max_answer_age_slots: u64,
+  // The JobSpec the Tasks were copied from, 0 when the requester gave them
+  pub job_id: u64,
+  pub optimistic: OptimisticResponse,
 }
 
 impl Request {
-  /// Check the first 8 bytes of Request buffer. If the first 8 bytes
-  /// are 0, then the Request is determined empty.
-  /// 
-  /// This function must check at least the first 3 bytes, since the
-  /// first two could be 0 as the initial Task enum tag.
-  fn is_empty_buffer(buf: &[u8; Request::LEN]) -> bool {
-    u64::from_le_bytes(*array_ref![buf, 0, 8]) == 0
+  /// Byte offset of `requester` within a packed Request
+  pub const REQUESTER_OFFSET: usize = Task::LEN * TASK_ARRAY_SIZE + PUBLIC_KEY_LEN + REQUEST_INDEX_SIZE;
+
+  /// Lamports or tokens held in escrow for the Request, its fee and tip
+  pub fn escrow(&self) -> u64 {
+    self.fee.saturating_add(self.tip)
   }
 
-  
+  /// Hash of the packed Tasks, equal for Requests running the same pipeline
+  pub fn task_hash(&self) -> [u8; HASH_BYTES] {
+    let mut tasks = [0u8; Task::LEN * TASK_ARRAY_SIZE];
+    for (task, dst) in self.tasks.iter().zip(tasks.chunks_exact_mut(Task::LEN)) {
+      task.pack_into_slice(dst);
+    }
+    hash(&tasks).to_bytes()
+  }
 }
 
 impl Sealed for Request {}
@@ -161,77 +568,133 @@ impl IsInitialized for Request {
   }
 }
 impl Pack for Request {
-  const LEN: usize  = Task::LEN * TASK_ARRAY_SIZE + PUBLIC_KEY_LEN + REQUEST_INDEX_SIZE;
+  const LEN: usize  = Task::LEN * TASK_ARRAY_SIZE + PUBLIC_KEY_LEN + REQUEST_INDEX_SIZE
+    + PUBLIC_KEY_LEN + REQUEST_FEE_SIZE + REQUEST_SLOT_SIZE + REQUEST_ID_SIZE
+    + REQUEST_TIP_SIZE + REQUEST_MAX_AGE_SIZE + REQUEST_JOB_ID_SIZE + OptimisticResponse::LEN;
   fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
     let src = array_ref![src, 0, Request::LEN];
-    let (task_1, task_2, task_3, program_id_bytes, index_bytes) = 
-      array_refs![src, Task::LEN, Task::LEN, Task::LEN, PUBLIC_KEY_LEN, REQUEST_INDEX_SIZE];
+    let (task_1, task_2, task_3, program_id_bytes, index_bytes, requester, fee, created_slot, id, tip, max_answer_age_slots, job_id, optimistic) = 
+      array_refs![src, Task::LEN, Task::LEN, Task::LEN, PUBLIC_KEY_LEN, REQUEST_INDEX_SIZE, PUBLIC_KEY_LEN, REQUEST_FEE_SIZE, REQUEST_SLOT_SIZE, REQUEST_ID_SIZE,
+      REQUEST_TIP_SIZE, REQUEST_MAX_AGE_SIZE, REQUEST_JOB_ID_SIZE, OptimisticResponse::LEN];
     let call_back_program = Pubkey::new(program_id_bytes);
-    return Ok(Request {
+    Ok(Request {
       tasks: [
         Task::unpack_from_slice(task_1)?,
         Task::unpack_from_slice(task_2)?,
         Task::unpack_from_slice(task_3)?
       ],
-      call_back_program: call_back_program,
-      index: u8::from_le_bytes(*index_bytes)
-    });
+      call_back_program,
+      index: u8::from_le_bytes(*index_bytes),
+      requester: Pubkey::new(requester),
+      fee: u64::from_le_bytes(*fee),
+      created_slot: u64::from_le_bytes(*created_slot),
+      id: u64::from_le_bytes(*id),
+      tip: u64::from_le_bytes(*tip),
+      max_answer_age_slots: u64::from_le_bytes(*max_answer_age_slots),
+      job_id: u64::from_le_bytes(*job_id),
+      optimistic: OptimisticResponse::unpack_from_slice(optimistic)?,
+    })
   }
 
   fn pack_into_slice(&self, dst: &mut [u8]) {
     let dst = array_mut_ref![dst, 0, Request::LEN];
-    let (task_1, task_2, task_3, call_back_program, index) =
-    mut_array_refs![dst, Task::LEN, Task::LEN, Task::LEN, PUBLIC_KEY_LEN, REQUEST_INDEX_SIZE];
+    let (task_1, task_2, task_3, call_back_program, index, requester, fee, created_slot, id, tip, max_answer_age_slots, job_id, optimistic) =
+    mut_array_refs![dst, Task::LEN, Task::LEN, Task::LEN, PUBLIC_KEY_LEN, REQUEST_INDEX_SIZE, PUBLIC_KEY_LEN, REQUEST_FEE_SIZE, REQUEST_SLOT_SIZE, REQUEST_ID_SIZE,
+      REQUEST_TIP_SIZE, REQUEST_MAX_AGE_SIZE, REQUEST_JOB_ID_SIZE, OptimisticResponse::LEN];
     self.tasks[0].pack_into_slice(task_1);
     self.tasks[1].pack_into_slice(task_2);
     self.tasks[2].pack_into_slice(task_3);
     *call_back_program = self.call_back_program.to_bytes();
     index.copy_from_slice(&[self.index]);
+    *requester = self.requester.to_bytes();
+    *fee = self.fee.to_le_bytes();
+    *created_slot = self.created_slot.to_le_bytes();
+    *id = self.id.to_le_bytes();
+    *tip = self.tip.to_le_bytes();
+    *max_answer_age_slots = self.max_answer_age_slots.to_le_bytes();
+    *job_id = self.job_id.to_le_bytes();
+    self.optimistic.pack_into_slice(optimistic);
   }
 }
 
+/// Whether the slot at `index` is marked occupied in `bitmap`
+pub fn is_slot_occupied(bitmap: &[u8; OCCUPANCY_BITMAP_LEN], index: usize) -> bool {
+  bitmap[index / 8] & (1 << (index % 8)) != 0
+}
+
+/// Mark the slot at `index` occupied or empty in `bitmap`
+pub fn set_slot_occupied(bitmap: &mut [u8; OCCUPANCY_BITMAP_LEN], index: usize, is_occupied: bool) {
+  if is_occupied {
+    bitmap[index / 8] |= 1 << (index % 8);
+  } else {
+    bitmap[index / 8] &= !(1 << (index % 8));
+  }
+}
+
+/// The Request slots of an Oracle, packed as a bitmap of the occupied slots
+/// followed by one `Request::LEN` slot per Request. Its capacity varies
+/// between Oracles so it is packed by hand instead of through `Pack`, which
+/// needs a fixed length.
 #[derive(Clone, Debug, PartialEq)]
 pub struct RequestQueue {
-  pub requests: Box<[Option<Request>; REQUEST_QUEUE_SIZE]>,
+  pub requests: Vec<Option<Request>>,
 }
 
-impl Sealed for RequestQueue {}
-impl IsInitialized for RequestQueue {
-  fn is_initialized(&self) -> bool {
-      true
+impl RequestQueue {
+  /// Bytes taken by a queue of `capacity` slots
+  pub const fn get_packed_len(capacity: usize) -> usize {
+    OCCUPANCY_BITMAP_LEN + Request::LEN * capacity
   }
-}
-impl Pack for RequestQueue {
-  const LEN: usize = Request::LEN * REQUEST_QUEUE_SIZE;
 
-  fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
-    let mut requests: Box<[Option<Request>; REQUEST_QUEUE_SIZE]> = Box::new([None, None, None, None, None, None, None, None, None, None]);
-    for i in 0..REQUEST_QUEUE_SIZE {
-      let offset = Request::LEN * i;
-      let request_buf = array_ref![src, offset, Request::LEN];
-      if !Request::is_empty_buffer(&request_buf) {
-        let request = Request::unpack(array_ref![src, offset, Request::LEN])?;
-        requests[i] = Some(request);
-      }
+  /// Unpack every whole Request slot in `src`, slots the bitmap marks empty
+  /// are not read
+  pub fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+    if src.len() < OCCUPANCY_BITMAP_LEN {
+      return Err(ProgramError::InvalidAccountData);
     }
+    let (bitmap, slots) = src.split_at(OCCUPANCY_BITMAP_LEN);
+    let bitmap = array_ref![bitmap, 0, OCCUPANCY_BITMAP_LEN];
+    let requests = slots.chunks_exact(Request::LEN)
+      .take(MAX_REQUEST_QUEUE_SIZE)
+      .enumerate()
+      .map(|(index, request_buf)| if is_slot_occupied(bitmap, index) {
+        Request::unpack(request_buf).map(Some)
+      } else {
+        Ok(None)
+      })
+      .collect::<Result<_, _>>()?;
 
     Ok(RequestQueue {
-      requests: requests
+      requests
     })
   }
 
-  fn pack_into_slice(&self, dst: &mut [u8]) {
-    for i in 0..REQUEST_QUEUE_SIZE {
-      if self.requests[i].is_none() {
-        continue;
-      }
-      let request = self.requests[i].clone().unwrap();
+  /// Unanswered Requests in the order nodes answer them, highest tip first
+  /// and oldest first among equal tips. Free slots are reused regardless of
+  /// arrival so slot order says nothing about age.
+  pub fn pending_by_priority(&self) -> Vec<&Request> {
+    let mut pending: Vec<&Request> = self.requests.iter()
+      .flatten()
+      .filter(|request| request.optimistic.status == ResponseStatus::Unanswered)
+      .collect();
+    pending.sort_by_key(|request| (Reverse(request.tip), request.id));
+    pending
+  }
 
+  /// The unanswered Request nodes should answer next
+  pub fn next_pending(&self) -> Option<&Request> {
+    self.pending_by_priority().into_iter().next()
+  }
 
-      let offset = Request::LEN * i;
-      let dst_request_buf = array_mut_ref![dst, offset, Request::LEN];
-      // TODO maybe add better ProgramError here or in the Request::pack?
-      Request::pack(request, dst_request_buf).unwrap();
+  pub fn pack_into_slice(&self, dst: &mut [u8]) {
+    let (bitmap, slots) = dst.split_at_mut(OCCUPANCY_BITMAP_LEN);
+    let bitmap = array_mut_ref![bitmap, 0, OCCUPANCY_BITMAP_LEN];
+    for (index, (request, dst_request_buf)) in self.requests.iter().zip(slots.chunks_exact_mut(Request::LEN)).enumerate() {
+      set_slot_occupied(bitmap, index, request.is_some());
+      if let Some(request) = request {
+        // TODO maybe add better ProgramError here or in the Request::pack?
+        Request::pack(request.clone(), dst_request_buf).unwrap();
+      }
     }
   }
 }
@@ -249,9 +712,7 @@ mod tests {
     let params = GetParams {
       get: *GenericArray::from_slice(url_bytes)
     };
-    let args = GetArgs {
-      params: params
-    };
+    let args = GetArgs::new(params);
     let get_task = Task::HttpGet(args);
     let json_parse_task = Task::JsonParse(json_args);
     let uint_128_task = Task::Uint32;
@@ -260,6 +721,14 @@ mod tests {
       tasks: [get_task, json_parse_task, uint_128_task],
       call_back_program: Pubkey::new(&[4u8; PUBLIC_KEY_LEN]),
       index: 0,
+      requester: Pubkey::default(),
+      fee: 0,
+      created_slot: 0,
+      id: 0,
+      tip: 0,
+      max_answer_age_slots: 0,
+      job_id: 0,
+      optimistic: OptimisticResponse::default(),
     }
   }
   
@@ -270,7 +739,7 @@ mod tests {
       get: *GenericArray::from_slice(url_bytes)
     };
 
-    let &mut mut serialized_params = &mut [0 as u8; 34];
+    let &mut mut serialized_params = &mut [0u8; 34];
     params.pack_into_slice(&mut serialized_params);
 
     // make sure the serialized GetParams is the same as the url_bytes
@@ -287,7 +756,7 @@ mod tests {
     let json_args = JsonParseArgs {
       path: *path_bytes
     };
-    let &mut mut serialized_json_parse_args = &mut [0 as u8; 12];
+    let &mut mut serialized_json_parse_args = &mut [0u8; 12];
     json_args.pack_into_slice(&mut serialized_json_parse_args);
 
     assert_eq!(&serialized_json_parse_args, path_bytes);
@@ -307,17 +776,15 @@ mod tests {
     let params = GetParams {
       get: *GenericArray::from_slice(url_bytes)
     };
-    let args = GetArgs {
-      params: params
-    };
+    let args = GetArgs::new(params);
     let get_task = Task::HttpGet(args);
     let json_parse_task = Task::JsonParse(json_args);
     
-    let httpget_tag = [0 as u8; 2];
+    let httpget_tag = [0u8; 2];
     let httpjson_tag: [u8; 2] = [1, 0];
-    let &mut mut serialized_get_task = &mut [0 as u8; 36];
+    let &mut mut serialized_get_task = &mut [0u8; Task::LEN];
     get_task.pack_into_slice(&mut serialized_get_task);
-    let &mut mut serialized_json_task = &mut [0 as u8; 36];
+    let &mut mut serialized_json_task = &mut [0u8; Task::LEN];
     json_parse_task.pack_into_slice(&mut serialized_json_task);
     assert_eq!(serialized_get_task[0..2], httpget_tag);
     assert_eq!(serialized_get_task[2..36], *url_bytes);
@@ -331,14 +798,168 @@ mod tests {
   }
 
   #[test]
-  fn test_request_is_empty() {
-    let empty_request_buffer = [0u8; Request::LEN];
-    assert!(Request::is_empty_buffer(&empty_request_buffer));
+  fn test_pack_unpack_http_post_task() {
+    let url = b"https://cloudflare-eth.com";
+    let body = br#"{"jsonrpc":"2.0","id":1,"method":"eth_blockNumber"}"#;
+    let post_task = Task::http_post(url, body, ContentType::Json).unwrap();
+    assert_eq!(post_task.url_len(), Some(url.len()));
+    assert_eq!(post_task.host(), Some(&b"cloudflare-eth.com"[..]));
+
+    let mut serialized_post_task = [0u8; Task::LEN];
+    post_task.pack_into_slice(&mut serialized_post_task);
+    assert_eq!(serialized_post_task[0..5], [3, 0, 0, url.len() as u8, b'h']);
+    assert_eq!(serialized_post_task[4 + url.len()], body.len() as u8);
+    assert_eq!(Task::unpack_from_slice(&serialized_post_task).unwrap(), post_task);
+
+    // the url and body must fit the Task
+    assert_eq!(
+      Task::http_post(url, &[b'a'; TASK_DATA_LEN - 29], ContentType::PlainText),
+      Err(ProgramError::InvalidArgument)
+    );
+    Task::http_post(url, &[b'a'; TASK_DATA_LEN - 30], ContentType::PlainText).unwrap();
+
+    // lengths running past the Task
+    serialized_post_task[4 + url.len()] = u8::MAX;
+    assert_eq!(Task::unpack_from_slice(&serialized_post_task), Err(ProgramError::InvalidAccountData));
+    serialized_post_task[3] = u8::MAX;
+    assert_eq!(Task::unpack_from_slice(&serialized_post_task), Err(ProgramError::InvalidAccountData));
+    serialized_post_task[2] = 3;
+    assert_eq!(Task::unpack_from_slice(&serialized_post_task), Err(ProgramError::InvalidAccountData));
+  }
+
+  #[test]
+  fn test_pack_unpack_task_headers() {
+    let get_task = create_sample_request().tasks[0].clone()
+      .with_headers(vec![Header::new(b"Accept", b"application/json"), Header::new(b"X-Api-Version", b"2")])
+      .unwrap();
+    let mut serialized_get_task = [0u8; Task::LEN];
+    get_task.pack_into_slice(&mut serialized_get_task);
+    // the headers follow the url
+    assert_eq!(serialized_get_task[36..40], [2, 6, b'A', b'c']);
+    assert_eq!(Task::unpack_from_slice(&serialized_get_task).unwrap(), get_task);
+
+    let post_task = Task::http_post(b"https://x.io/graphql", b"{}", ContentType::Json).unwrap()
+      .with_headers(vec![Header::new(b"Accept", b"application/json")])
+      .unwrap();
+    let mut serialized_post_task = [0u8; Task::LEN];
+    post_task.pack_into_slice(&mut serialized_post_task);
+    assert_eq!(Task::unpack_from_slice(&serialized_post_task).unwrap(), post_task);
+
+    // the headers must fit the Task
+    let header = Header::new(b"Accept", b"application/json");
+    assert_eq!(get_task.clone().with_headers(vec![header.clone(); 3]), Err(ProgramError::InvalidArgument));
+    assert_eq!(
+      post_task.with_headers(vec![Header::new(b"a", b"b"); MAX_TASK_HEADERS + 1]),
+      Err(ProgramError::InvalidArgument)
+    );
+    assert_eq!(Task::Uint32.with_headers(vec![header]), Err(ProgramError::InvalidArgument));
+
+    // more headers than a Task may carry
+    serialized_get_task[36] = MAX_TASK_HEADERS as u8 + 1;
+    assert_eq!(Task::unpack_from_slice(&serialized_get_task), Err(ProgramError::InvalidAccountData));
+  }
+
+  #[test]
+  fn test_header_is_allowed() {
+    assert!(Header::new(b"Accept", b"application/json").is_allowed());
+    assert!(Header::new(b"X-CG-Pro-Api-Key", b"secret:coingecko_pro").is_allowed());
+    assert!(Header::new(b"Authorization", b"secret:coingecko_pro").is_allowed());
+    assert!(!Header::new(b"Authorization", b"Bearer abc").is_allowed());
+    assert!(!Header::new(b"authorization", b"secret:").is_allowed());
+    assert!(!Header::new(b"Host", b"ftx.us").is_allowed());
+    assert!(!Header::new(b"CONTENT-LENGTH", b"10").is_allowed());
+    assert!(!Header::new(b"Accept", b"a\r\nHost: ftx.us").is_allowed());
+    assert!(!Header::new(b"Accept:", b"a").is_allowed());
+    assert!(!Header::new(b"", b"a").is_allowed());
+  }
+
+  #[test]
+  fn test_get_args_host() {
+    let url_bytes = b"https://ftx.us/api/markets/BTC/USD";
+    let args = GetArgs::new(GetParams { get: *GenericArray::from_slice(url_bytes) });
+    assert_eq!(args.host(), Some(&b"ftx.us"[..]));
+
+    let mut url_bytes = [0u8; 34];
+    url_bytes[..19].copy_from_slice(b"https://example.com");
+    let args = GetArgs::new(GetParams { get: *GenericArray::from_slice(&url_bytes) });
+    assert_eq!(args.host(), Some(&b"example.com"[..]));
+
+    // the host ends where HTTP clients end it, and urls they could read
+    // as a different host have none
+    let host = |url: &[u8]| url_host(url).map(|host| host.to_vec());
+    assert_eq!(host(b"https://example.com?q=1"), Some(b"example.com".to_vec()));
+    assert_eq!(host(b"https://example.com#top"), Some(b"example.com".to_vec()));
+    assert_eq!(host(b"http://example.com"), None);
+    assert_eq!(host(b"https:/evil.io/://pro-api.coingecko.com/x"), None);
+    assert_eq!(host(b"https:\\\\evil.io/://pro-api.coingecko.com/x"), None);
+    assert_eq!(host(b"https://pro-api.coingecko.com@evil.io/"), None);
+    assert_eq!(host(b"https://pro-api.coingecko.com\\@evil.io/"), None);
+    assert_eq!(host(b"https://pro-api.coingecko.com:443/"), None);
+    assert_eq!(host(b"https:///x"), None);
+  }
 
-    let mut request_buffer = [0u8; Request::LEN];
+  #[test]
+  fn test_request_queue_next_pending() {
+    let mut queue = RequestQueue { requests: vec![None, None, None] };
+    assert_eq!(queue.next_pending(), None);
+
+    // a later Request reusing a lower slot waits for the older one
+    let mut newer_request = create_sample_request();
+    newer_request.id = 7;
+    let mut older_request = create_sample_request();
+    older_request.id = 3;
+    queue.requests[0] = Some(newer_request.clone());
+    queue.requests[2] = Some(older_request.clone());
+    assert_eq!(queue.next_pending(), Some(&older_request));
+
+    // proposed answers are no longer pending
+    older_request.optimistic.status = ResponseStatus::Proposed;
+    queue.requests[2] = Some(older_request);
+    assert_eq!(queue.next_pending(), Some(&newer_request));
+  }
+
+  #[test]
+  fn test_request_task_hash() {
     let request = create_sample_request();
-    Request::pack(request, &mut request_buffer).unwrap();
-    assert_eq!(Request::is_empty_buffer(&request_buffer), false);
+    let mut same_tasks = create_sample_request();
+    same_tasks.fee = 1;
+    same_tasks.call_back_program = Pubkey::new(&[4u8; PUBLIC_KEY_LEN]);
+    assert_eq!(request.task_hash(), same_tasks.task_hash());
+
+    let mut other_tasks = create_sample_request();
+    other_tasks.tasks[1] = Task::JsonParse(JsonParseArgs { path: *b"result.bid\0\0" });
+    assert_ne!(request.task_hash(), other_tasks.task_hash());
+  }
+
+  #[test]
+  fn test_request_queue_pending_by_priority() {
+    let mut queue = RequestQueue { requests: vec![None; 4] };
+    for (slot, (id, tip)) in [(0, 0), (1, 50), (2, 0), (3, 50)].iter().enumerate() {
+      let mut request = create_sample_request();
+      request.id = *id;
+      request.tip = *tip;
+      queue.requests[slot] = Some(request);
+    }
+
+    let ids: Vec<u64> = queue.pending_by_priority().iter().map(|request| request.id).collect();
+    assert_eq!(ids, vec![1, 3, 0, 2]);
+    assert_eq!(queue.next_pending().map(|request| request.id), Some(1));
+  }
+
+  #[test]
+  fn test_occupancy_bitmap() {
+    let mut bitmap = [0u8; OCCUPANCY_BITMAP_LEN];
+    set_slot_occupied(&mut bitmap, 0, true);
+    set_slot_occupied(&mut bitmap, 9, true);
+    set_slot_occupied(&mut bitmap, MAX_REQUEST_QUEUE_SIZE - 1, true);
+    assert_eq!(bitmap[0..2], [0b1, 0b10]);
+    assert!(is_slot_occupied(&bitmap, 9));
+    assert!(!is_slot_occupied(&bitmap, 8));
+    assert!(is_slot_occupied(&bitmap, MAX_REQUEST_QUEUE_SIZE - 1));
+
+    set_slot_occupied(&mut bitmap, 9, false);
+    assert!(!is_slot_occupied(&bitmap, 9));
+    assert!(is_slot_occupied(&bitmap, 0));
   }
 
   #[test]
@@ -346,17 +967,17 @@ mod tests {
     let request = create_sample_request();
     let url_bytes = b"https://ftx.us/api/markets/BTC/USD";
     let path_bytes = b"result.price";
-    let httpget_tag = [0 as u8; 2];
+    let httpget_tag = [0u8; 2];
     let json_tag: [u8; 2] = [1, 0];
     let uint32_tag: [u8; 2] = [2, 0];
 
-    let &mut mut serialized_request = &mut [0 as u8; Request::LEN];
+    let &mut mut serialized_request = &mut [0u8; Request::LEN];
     request.pack_into_slice(&mut serialized_request);
     assert_eq!(serialized_request[0..2], httpget_tag);
     assert_eq!(serialized_request[2..36], *url_bytes);
-    assert_eq!(serialized_request[36..38], json_tag);
-    assert_eq!(serialized_request[38..50], *path_bytes);
-    assert_eq!(serialized_request[72..74], uint32_tag);
+    assert_eq!(serialized_request[Task::LEN..Task::LEN + 2], json_tag);
+    assert_eq!(serialized_request[Task::LEN + 2..Task::LEN + 14], *path_bytes);
+    assert_eq!(serialized_request[2 * Task::LEN..2 * Task::LEN + 2], uint32_tag);
 
     let deserialized_request: Request = Request::unpack_from_slice(&serialized_request).unwrap();
     assert_eq!(deserialized_request, request);
@@ -364,42 +985,62 @@ mod tests {
 
   #[test]
   fn test_pack_unpack_request_queue() {
-    let mut empty_request_queue_buffer = [0u8; RequestQueue::LEN];
-    let empty_request_queue = RequestQueue::unpack(&empty_request_queue_buffer).unwrap();
+    const QUEUE_LEN: usize = RequestQueue::get_packed_len(REQUEST_QUEUE_SIZE);
+    let mut empty_request_queue_buffer = [0u8; QUEUE_LEN];
+    let empty_request_queue = RequestQueue::unpack_from_slice(&empty_request_queue_buffer).unwrap();
     
     assert_eq!(empty_request_queue, RequestQueue {
-      requests: Box::new([None, None, None, None, None, None, None, None, None, None])
+      requests: vec![None, None, None, None, None, None, None, None, None, None]
     });
 
-    RequestQueue::pack(RequestQueue {
-      requests: Box::new([None, None, None, None, None, None, None, None, None, None])
-    }, &mut empty_request_queue_buffer).unwrap();
+    RequestQueue {
+      requests: vec![None, None, None, None, None, None, None, None, None, None]
+    }.pack_into_slice(&mut empty_request_queue_buffer);
 
-    assert_eq!(empty_request_queue_buffer, [0u8; RequestQueue::LEN]);
+    assert_eq!(empty_request_queue_buffer, [0u8; QUEUE_LEN]);
 
-    let mut expected_single_request_queue_buffer = [0u8; RequestQueue::LEN];
-    let request_queue_buffer = array_mut_ref![expected_single_request_queue_buffer, 0, RequestQueue::LEN];
-    let (first_request, _rest, last_request) = mut_array_refs![request_queue_buffer, Request::LEN, RequestQueue::LEN - 2 * Request::LEN, Request::LEN];
+    let mut expected_single_request_queue_buffer = [0u8; QUEUE_LEN];
+    let request_queue_buffer = array_mut_ref![expected_single_request_queue_buffer, 0, QUEUE_LEN];
+    let (bitmap, first_request, _rest, last_request) =
+      mut_array_refs![request_queue_buffer, OCCUPANCY_BITMAP_LEN, Request::LEN, 8 * Request::LEN, Request::LEN];
+    bitmap[0..2].copy_from_slice(&[0b1, 0b10]);
 
     let request = create_sample_request();
     Request::pack(request, first_request).unwrap();
     let request = create_sample_request();
     Request::pack(request, last_request).unwrap();
 
-    let single_queue = RequestQueue::unpack(&expected_single_request_queue_buffer).unwrap();
+    let single_queue = RequestQueue::unpack_from_slice(&expected_single_request_queue_buffer).unwrap();
     let request1 = create_sample_request();
     let request2 = create_sample_request();
     assert_eq!(single_queue, RequestQueue {
-      requests: Box::new([Some(request1), None, None, None, None, None, None, None, None, Some(request2)]),
+      requests: vec![Some(request1), None, None, None, None, None, None, None, None, Some(request2)],
     });
     
-    let mut single_queue_buffer = [0u8; RequestQueue::LEN];
+    let mut single_queue_buffer = [0u8; QUEUE_LEN];
     let request1 = create_sample_request();
     let request2 = create_sample_request();
-    RequestQueue::pack(RequestQueue {
-      requests: Box::new([Some(request1), None, None, None, None, None, None, None, None, Some(request2)]),
-    }, &mut single_queue_buffer).unwrap();
+    RequestQueue {
+      requests: vec![Some(request1), None, None, None, None, None, None, None, None, Some(request2)],
+    }.pack_into_slice(&mut single_queue_buffer);
 
     assert_eq!(single_queue_buffer, expected_single_request_queue_buffer);
+
+    // the capacity follows the length of the buffer
+    let request = create_sample_request();
+    let longer_queue = RequestQueue::unpack_from_slice(&[expected_single_request_queue_buffer, [0u8; QUEUE_LEN]].concat()).unwrap();
+    assert_eq!(longer_queue.requests.len(), 2 * REQUEST_QUEUE_SIZE);
+    assert_eq!(longer_queue.requests[0], Some(request));
+    assert_eq!(RequestQueue::unpack_from_slice(&single_queue_buffer[0..RequestQueue::get_packed_len(1) + 1]).unwrap().requests.len(), 1);
+
+    // a Request starting with zero bytes is still occupied
+    let mut request = create_sample_request();
+    request.tasks[0] = Task::HttpGet(GetArgs::new(GetParams { get: GenericArray::default() }));
+    let mut zeroed_request_queue_buffer = [0u8; QUEUE_LEN];
+    RequestQueue {
+      requests: vec![Some(request.clone()), None, None, None, None, None, None, None, None, None],
+    }.pack_into_slice(&mut zeroed_request_queue_buffer);
+    assert_eq!(zeroed_request_queue_buffer[OCCUPANCY_BITMAP_LEN..OCCUPANCY_BITMAP_LEN + 8], [0u8; 8]);
+    assert_eq!(RequestQueue::unpack_from_slice(&zeroed_request_queue_buffer).unwrap().requests[0], Some(request));
   }
-}
\ No newline at end of file
+}
@@ -10,7 +10,7 @@ use arrayref::{ array_ref, array_refs, array_mut_ref, mut_array_refs };
 pub const RESPONSE_DATA_LEN: usize = 4;
 pub const CALLBACK_DETERMINANT_LEN: usize = 1;
 
-type ResponseData = [u8; RESPONSE_DATA_LEN];
+pub type ResponseData = [u8; RESPONSE_DATA_LEN];
 
 #[derive(Debug, PartialEq)]
 pub struct Response {
@@ -59,7 +59,7 @@ mod tests {
       request_queue_index: 1,
     };
 
-    let &mut mut serialized_response = &mut [0 as u8; Response::LEN];
+    let &mut mut serialized_response = &mut [0u8; Response::LEN];
     Response::pack(response, &mut serialized_response).unwrap();
     let serialized_ref = array_ref![serialized_response, 0, Response::LEN]; 
     let (det, resp, index) = array_refs![serialized_ref, CALLBACK_DETERMINANT_LEN, RESPONSE_DATA_LEN, REQUEST_INDEX_SIZE];
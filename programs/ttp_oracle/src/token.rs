@@ -0,0 +1,97 @@
+use crate::PUBLIC_KEY_LEN;
+use solana_program::{
+  instruction::{ AccountMeta, Instruction },
+  program_error::ProgramError,
+  pubkey::Pubkey,
+};
+use arrayref::{ array_ref, array_refs };
+
+/// The SPL Token program
+pub mod spl_token {
+  solana_program::declare_id!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+}
+
+/// Size of an SPL Token account
+pub const TOKEN_ACCOUNT_LEN: usize = 165;
+/// Tag of the SPL Token Transfer instruction
+const TRANSFER_INSTRUCTION: u8 = 3;
+/// Seed for the program address that owns an Oracle's fee vault
+pub const VAULT_AUTHORITY_SEED: &[u8] = b"vault";
+
+/// The fields of an SPL Token account the oracle checks
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TokenAccount {
+  pub mint: Pubkey,
+  pub owner: Pubkey,
+  pub amount: u64,
+}
+
+impl TokenAccount {
+  pub fn unpack(src: &[u8]) -> Result<Self, ProgramError> {
+    if src.len() != TOKEN_ACCOUNT_LEN {
+      return Err(ProgramError::InvalidAccountData);
+    }
+    let src = array_ref![src, 0, PUBLIC_KEY_LEN + PUBLIC_KEY_LEN + 8];
+    let (mint, owner, amount) = array_refs![src, PUBLIC_KEY_LEN, PUBLIC_KEY_LEN, 8];
+    Ok(TokenAccount {
+      mint: Pubkey::new(mint),
+      owner: Pubkey::new(owner),
+      amount: u64::from_le_bytes(*amount),
+    })
+  }
+}
+
+/// Program address that owns the fee vault of `oracle_id` and signs payouts from it
+pub fn find_vault_authority(oracle_program_id: &Pubkey, oracle_id: &Pubkey) -> (Pubkey, u8) {
+  Pubkey::find_program_address(&[oracle_id.as_ref(), VAULT_AUTHORITY_SEED], oracle_program_id)
+}
+
+/// Generate an SPL Token Transfer of `amount` from `source` to `destination`.
+/// Encoded by hand as it is the only Token instruction the oracle sends
+pub fn transfer(
+  source: &Pubkey,
+  destination: &Pubkey,
+  authority: &Pubkey,
+  amount: u64,
+) -> Instruction {
+  let mut data = vec![TRANSFER_INSTRUCTION];
+  data.extend_from_slice(&amount.to_le_bytes());
+  Instruction {
+    program_id: spl_token::id(),
+    accounts: vec![
+      AccountMeta::new(*source, false),
+      AccountMeta::new(*destination, false),
+      AccountMeta::new_readonly(*authority, true),
+    ],
+    data,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_unpack_token_account() {
+    let mint = Pubkey::new_unique();
+    let owner = Pubkey::new_unique();
+    let mut data = [0u8; TOKEN_ACCOUNT_LEN];
+    data[0..32].copy_from_slice(mint.as_ref());
+    data[32..64].copy_from_slice(owner.as_ref());
+    data[64..72].copy_from_slice(&42u64.to_le_bytes());
+    assert_eq!(TokenAccount::unpack(&data).unwrap(), TokenAccount { mint, owner, amount: 42 });
+    assert_eq!(TokenAccount::unpack(&data[..64]), Err(ProgramError::InvalidAccountData));
+  }
+
+  #[test]
+  fn test_transfer() {
+    let source = Pubkey::new_unique();
+    let destination = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let ix = transfer(&source, &destination, &authority, 5_000);
+    assert_eq!(ix.program_id, spl_token::id());
+    assert_eq!(ix.data[0], 3);
+    assert_eq!(ix.data[1..9], 5_000u64.to_le_bytes());
+    assert!(ix.accounts[2].is_signer);
+  }
+}
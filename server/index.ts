@@ -10,8 +10,13 @@ import {
   ClusterEnv,
   createDataAccountForProgram,
   createHandleAccountChange,
+  createHandleFeed,
+  loadSecrets,
+  Secrets,
 } from './utils';
 
+const DEFAULT_FEED_POLL_INTERVAL = 10000;
+
 class MissingENVVarError extends Error {
   constructor(envVar: string) {
     super(`Must specify ${envVar} env var`);
@@ -34,26 +39,68 @@ const main = async () => {
 
   let oracleId: PublicKey;
   if (!process.env.ORACLE_ID) {
-    const oracleAccount = await createDataAccountForProgram(
+    oracleId = await createDataAccountForProgram(
       connection,
       payerAccount,
       programId,
     );
-    oracleId = oracleAccount.publicKey;
   } else {
     oracleId = new PublicKey(process.env.ORACLE_ID);
   }
 
+  // secrets Tasks refer to by name, held in a file encrypted with SECRETS_KEY
+  let secrets: Secrets = {};
+  if (process.env.SECRETS_PATH) {
+    if (!process.env.SECRETS_KEY) {
+      throw new MissingENVVarError('SECRETS_KEY');
+    }
+    secrets = await loadSecrets(
+      process.env.SECRETS_PATH,
+      process.env.SECRETS_KEY,
+    );
+  }
+
   const ws = new Connection(cluster.socket[environment]);
 
   console.log('Howdy, World!');
   console.log(`listening to Oracle: ${oracleId.toString()}`);
 
+  const nodeTokenAccount = process.env.NODE_FEE_TOKEN_ACCOUNT
+    ? new PublicKey(process.env.NODE_FEE_TOKEN_ACCOUNT)
+    : undefined;
+
   ws.onAccountChange(
     oracleId,
-    createHandleAccountChange(connection, payerAccount, programId, oracleId),
+    createHandleAccountChange(
+      connection,
+      payerAccount,
+      programId,
+      oracleId,
+      nodeTokenAccount,
+      secrets,
+    ),
   );
 
+  // Feeds are polled rather than subscribed to since the source data
+  // lives off-chain
+  const feedIds = (process.env.FEED_IDS ?? '')
+    .split(',')
+    .filter((feedId) => feedId.length > 0)
+    .map((feedId) => new PublicKey(feedId));
+  const feedPollInterval =
+    Number(process.env.FEED_POLL_INTERVAL) || DEFAULT_FEED_POLL_INTERVAL;
+  feedIds.forEach((feedId) => {
+    console.log(`updating Feed: ${feedId.toString()}`);
+    const handleFeed = createHandleFeed(
+      connection,
+      payerAccount,
+      programId,
+      feedId,
+      secrets,
+    );
+    setInterval(handleFeed, feedPollInterval);
+  });
+
   // start listening to account changes
   // on open check request queue for requests, subscribe to account changes
 
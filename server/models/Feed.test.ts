@@ -0,0 +1,42 @@
+import OracleFeed, { FEED_LAYOUT } from './Feed';
+
+const buildFeedBuffer = (): Buffer => {
+  const buffer = Buffer.alloc(FEED_LAYOUT.span);
+  FEED_LAYOUT.encode(
+    {
+      isInitialized: 1,
+      authorityBuffer: Array.from(Buffer.alloc(32, 5)),
+      tasks: [{ uint32: true }, { uint32: true }, { uint32: true }],
+      deviationThresholdBps: 50,
+      heartbeatSlots: 100,
+      value: 10000,
+      round: 1,
+      lastUpdateSlot: 1000,
+      maxChangeBps: 1000,
+      isTripped: 0,
+    },
+    buffer,
+  );
+  return buffer;
+};
+
+describe('Feed', () => {
+  it('should decode the feed', () => {
+    const feed = new OracleFeed(buildFeedBuffer());
+    expect(feed.isInitialized).toBe(true);
+    expect(feed.deviationThresholdBps).toEqual(50);
+    expect(feed.heartbeatSlots).toEqual(100);
+    expect(feed.value).toEqual(10000);
+    expect(feed.lastUpdateSlot).toEqual(1000);
+    expect(feed.maxChangeBps).toEqual(1000);
+    expect(feed.isTripped).toBe(false);
+  });
+
+  it('should only update on deviation or heartbeat', () => {
+    const feed = new OracleFeed(buildFeedBuffer());
+    expect(feed.shouldUpdate(10050, 1050)).toBe(false);
+    expect(feed.shouldUpdate(10051, 1050)).toBe(true);
+    expect(feed.shouldUpdate(9949, 1050)).toBe(true);
+    expect(feed.shouldUpdate(10000, 1100)).toBe(true);
+  });
+});
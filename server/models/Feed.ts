@@ -0,0 +1,60 @@
+import { PublicKey } from '@solana/web3.js';
+import { nu64, seq, struct, u8, u16, u32 } from 'buffer-layout';
+import { TASK_LAYOUT } from './Task';
+
+export const BPS_DENOMINATOR = 10000;
+
+export const FEED_LAYOUT = struct([
+  u8('isInitialized'),
+  seq(u8(), 32, 'authorityBuffer'),
+  seq(TASK_LAYOUT, 3, 'tasks'),
+  u16('deviationThresholdBps'),
+  nu64('heartbeatSlots'),
+  u32('value'),
+  nu64('round'),
+  nu64('lastUpdateSlot'),
+  u16('maxChangeBps'),
+  u8('isTripped'),
+]);
+
+export default class OracleFeed {
+  isInitialized: boolean;
+  authority: PublicKey;
+  tasks: Record<string, any>[];
+  deviationThresholdBps: number;
+  heartbeatSlots: number;
+  value: number;
+  round: number;
+  lastUpdateSlot: number;
+  maxChangeBps: number;
+  isTripped: boolean;
+
+  constructor(buffer: Buffer) {
+    const feed = FEED_LAYOUT.decode(buffer);
+    this.isInitialized = feed.isInitialized === 1;
+    this.authority = new PublicKey(Buffer.from(feed.authorityBuffer));
+    this.tasks = feed.tasks;
+    this.deviationThresholdBps = feed.deviationThresholdBps;
+    this.heartbeatSlots = feed.heartbeatSlots;
+    this.value = feed.value;
+    this.round = feed.round;
+    this.lastUpdateSlot = feed.lastUpdateSlot;
+    this.maxChangeBps = feed.maxChangeBps;
+    this.isTripped = feed.isTripped === 1;
+  }
+
+  /**
+   * Mirrors the check the Oracle Program performs on UpdateFeed so the
+   * node only pays for transactions that will be accepted
+   */
+  shouldUpdate(newValue: number, slot: number): boolean {
+    if (this.round === 0) {
+      return true;
+    }
+    if (slot - this.lastUpdateSlot >= this.heartbeatSlots) {
+      return true;
+    }
+    const diff = Math.abs(newValue - this.value);
+    return diff * BPS_DENOMINATOR > this.deviationThresholdBps * this.value;
+  }
+}
@@ -0,0 +1,66 @@
+import { PublicKey } from '@solana/web3.js';
+import { nu64, seq, struct, u8 } from 'buffer-layout';
+import { TASK_LAYOUT } from './Task';
+
+export const JOB_SEED = 'job';
+export const MAX_JOB_PARAMS = 2;
+export const JOB_PARAM_LEN = 16;
+
+export const JOB_SPEC_LAYOUT = struct([
+  u8('isInitialized'),
+  seq(u8(), 32, 'oracleBuffer'),
+  nu64('jobId'),
+  // zero padded UTF 8 name, e.g. "BTC/USD"
+  seq(u8(), 32, 'nameBuffer'),
+  // urls and json paths may hold `{param}` placeholders
+  seq(TASK_LAYOUT, 3, 'tasks'),
+  // zero padded names of the template parameters
+  seq(seq(u8(), JOB_PARAM_LEN), MAX_JOB_PARAMS, 'paramBuffers'),
+]);
+
+/**
+ * Program address of the JobSpec `jobId` registered on `oracleId`
+ */
+export const findJobAddress = async (
+  programId: PublicKey,
+  oracleId: PublicKey,
+  jobId: number,
+): Promise<PublicKey> => {
+  const jobIdBuffer = Buffer.alloc(8);
+  nu64().encode(jobId, jobIdBuffer);
+  const [jobAddress] = await PublicKey.findProgramAddress(
+    [oracleId.toBuffer(), jobIdBuffer, Buffer.from(JOB_SEED)],
+    programId,
+  );
+  return jobAddress;
+};
+
+const trimPadding = (buffer: number[]): string =>
+  Buffer.from(buffer).toString('utf8').replace(/\0+$/, '');
+
+export default class JobSpec {
+  isInitialized: boolean;
+  oracle: PublicKey;
+  jobId: number;
+  name: string;
+  tasks: Record<string, any>[];
+  params: string[];
+
+  constructor(buffer: Buffer) {
+    const job = JOB_SPEC_LAYOUT.decode(buffer);
+    this.isInitialized = job.isInitialized === 1;
+    this.oracle = new PublicKey(Buffer.from(job.oracleBuffer));
+    this.jobId = job.jobId;
+    this.name = trimPadding(job.nameBuffer);
+    this.tasks = job.tasks;
+    this.params = job.paramBuffers.map(trimPadding).filter((param) => param);
+  }
+
+  /**
+   * Whether the tasks are the same for every Request, otherwise the
+   * Request holds them with its arguments substituted
+   */
+  isTemplate(): boolean {
+    return this.params.length > 0;
+  }
+}
@@ -1,5 +1,6 @@
-import { ORACLE_ACCOUNT_LAYOUT } from './OracleAccount';
+import OracleAccount, { ORACLE_ACCOUNT_LAYOUT } from './OracleAccount';
 import {
+  decodedOracleHeader,
   decodedRequestQueue,
   mockOracleAccountBuffer,
 } from '../../testing/mockData';
@@ -9,6 +10,16 @@ describe('OracleAccount', () => {
     const oracle_account = ORACLE_ACCOUNT_LAYOUT.decode(
       mockOracleAccountBuffer,
     );
-    expect(oracle_account).toEqual({ requestQueue: decodedRequestQueue });
+    expect(oracle_account).toEqual({
+      header: decodedOracleHeader,
+      requestQueue: decodedRequestQueue,
+    });
+  });
+
+  it('should list the advertised secrets with their hosts', () => {
+    const oracleAccount = new OracleAccount(mockOracleAccountBuffer);
+    expect(oracleAccount.supportedSecrets).toEqual([
+      { name: 'coingecko_pro', host: 'pro-api.coingecko.com' },
+    ]);
   });
 });
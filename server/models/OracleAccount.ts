@@ -1,15 +1,138 @@
-import { struct } from 'buffer-layout';
-import OracleRequestQueue, { REQUEST_QUEUE_LAYOUT } from './RequestQueue';
+import { PublicKey } from '@solana/web3.js';
+import { nu64, seq, struct, u8, u16 } from 'buffer-layout';
+import OracleRequestQueue, {
+  requestQueueLayout,
+  REQUEST_QUEUE_LAYOUT,
+} from './RequestQueue';
+import { TASK_KIND_COUNT } from './Task';
 
+export const MAX_HOST_FEES = 4;
+
+const HOST_FEE_LAYOUT = struct([seq(u8(), 32, 'hostBuffer'), nu64('fee')]);
+
+export const FEE_SCHEDULE_LAYOUT = struct([
+  // indexed by the Task variant tag
+  seq(nu64(), TASK_KIND_COUNT, 'taskFees'),
+  seq(HOST_FEE_LAYOUT, MAX_HOST_FEES, 'hostFees'),
+]);
+
+export const ANSWER_CACHE_SIZE = 4;
+
+export const MAX_ORACLE_SECRETS = 4;
+export const SECRET_NAME_LEN = 16;
+
+// a secret the node holds and the only host Tasks may send it to
+const ORACLE_SECRET_LAYOUT = struct([
+  seq(u8(), SECRET_NAME_LEN, 'nameBuffer'),
+  seq(u8(), 32, 'hostBuffer'),
+]);
+
+export interface OracleSecret {
+  name: string;
+  host: string;
+}
+
+// a delivered answer, keyed by the hash of the Tasks that produced it
+const CACHED_ANSWER_LAYOUT = struct([
+  seq(u8(), 32, 'taskHash'),
+  seq(u8(), 4, 'data'),
+  nu64('slot'),
+]);
+
+export const ORACLE_HEADER_LAYOUT = struct([
+  u8('isInitialized'),
+  seq(u8(), 32, 'adminBuffer'),
+  seq(u8(), 32, 'nodeAuthorityBuffer'),
+  nu64('requestFee'),
+  nu64('requestExpirySlots'),
+  seq(u8(), 32, 'feeMintBuffer'),
+  seq(u8(), 32, 'feeVaultBuffer'),
+  u8('vaultAuthorityBump'),
+  FEE_SCHEDULE_LAYOUT.replicate('feeSchedule'),
+  nu64('minStake'),
+  nu64('unstakeCooldownSlots'),
+  // lamports a disputer puts up against a proposed answer
+  nu64('disputeBond'),
+  seq(u8(), 32, 'pendingAdminBuffer'),
+  u8('maxUrlLen'),
+  // bitmask of the Task variant tags Requests may use
+  u8('allowedTaskKinds'),
+  u8('quorum'),
+  u8('isPaused'),
+  // queue slots one requester may hold, 0 for no limit
+  u8('maxRequestsPerRequester'),
+  // number of Request slots in use, follows the header
+  u16('capacity'),
+  // id handed to the next Request, ids follow arrival order
+  nu64('nextRequestId'),
+  seq(CACHED_ANSWER_LAYOUT, ANSWER_CACHE_SIZE, 'answerCache'),
+  // secrets Tasks may refer to with their zero padded host, all zero if unused
+  seq(ORACLE_SECRET_LAYOUT, MAX_ORACLE_SECRETS, 'supportedSecrets'),
+]);
+
+export const VAULT_AUTHORITY_SEED = 'vault';
+export const NODE_SEED = 'node';
+
+/**
+ * Program address of the node account `authority` registered on `oracleId`
+ */
+export const findNodeAddress = async (
+  programId: PublicKey,
+  oracleId: PublicKey,
+  authority: PublicKey,
+): Promise<PublicKey> => {
+  const [nodeAddress] = await PublicKey.findProgramAddress(
+    [oracleId.toBuffer(), authority.toBuffer(), Buffer.from(NODE_SEED)],
+    programId,
+  );
+  return nodeAddress;
+};
+
+// layout of an Oracle with the default capacity
 export const ORACLE_ACCOUNT_LAYOUT = struct([
+  ORACLE_HEADER_LAYOUT.replicate('header'),
   REQUEST_QUEUE_LAYOUT.replicate('requestQueue'),
 ]);
 
 export default class OracleAccount {
+  admin: PublicKey;
+  nodeAuthority: PublicKey;
+  requestFee: number;
+  requestExpirySlots: number;
+  feeMint: PublicKey;
+  feeVault: PublicKey;
+  isPaused: boolean;
+  supportedSecrets: OracleSecret[];
   requestQueue: OracleRequestQueue;
 
   constructor(buffer: Buffer) {
-    const req = ORACLE_ACCOUNT_LAYOUT.decode(buffer);
+    const header = ORACLE_HEADER_LAYOUT.decode(buffer);
+    const req = struct([
+      ORACLE_HEADER_LAYOUT.replicate('header'),
+      requestQueueLayout(header.capacity).replicate('requestQueue'),
+    ]).decode(buffer);
+    this.admin = new PublicKey(Buffer.from(req.header.adminBuffer));
+    this.nodeAuthority = new PublicKey(
+      Buffer.from(req.header.nodeAuthorityBuffer),
+    );
+    this.requestFee = req.header.requestFee;
+    this.requestExpirySlots = req.header.requestExpirySlots;
+    this.feeMint = new PublicKey(Buffer.from(req.header.feeMintBuffer));
+    this.feeVault = new PublicKey(Buffer.from(req.header.feeVaultBuffer));
+    this.isPaused = req.header.isPaused === 1;
+    this.supportedSecrets = req.header.supportedSecrets
+      .map(({ nameBuffer, hostBuffer }: Record<string, number[]>) => ({
+        name: Buffer.from(nameBuffer).toString().replace(/\0/g, ''),
+        host: Buffer.from(hostBuffer).toString().replace(/\0/g, ''),
+      }))
+      .filter(({ name }: OracleSecret) => name.length > 0);
     this.requestQueue = req.requestQueue;
   }
+
+  /**
+   * Fees are paid in lamports unless the header names an SPL Token mint
+   */
+  chargesTokenFee(): boolean {
+    return !this.feeMint.equals(new PublicKey(0));
+  }
 }
@@ -2,9 +2,15 @@ import { REQUEST_LAYOUT } from './Request';
 import { Task, TaskVariantKeys } from './Task';
 import {
   mockCallerProgramIdBuffer,
-  mockUrlBuffer,
+  decodedOptimisticResponse,
+  mockCreatedSlot,
+  mockHttpGetTaskBuffer,
   mockPathBuffer,
   mockRequestBuffer,
+  mockRequestFee,
+  mockRequestId,
+  mockRequesterBuffer,
+  mockRequestTip,
 } from '../../testing/mockData';
 
 describe('Request', () => {
@@ -14,7 +20,7 @@ describe('Request', () => {
     expect(request).toEqual({
       tasks: [
         {
-          [TaskVariantKeys[Task.HTTP_GET]]: Array.from(mockUrlBuffer),
+          [TaskVariantKeys[Task.HTTP_GET]]: Array.from(mockHttpGetTaskBuffer),
         },
         {
           [TaskVariantKeys[Task.JSON_PARSE]]: Array.from(mockPathBuffer),
@@ -25,6 +31,14 @@ describe('Request', () => {
       ],
       callerProgramIdBuffer: Array.from(mockCallerProgramIdBuffer),
       index: 1,
+      requesterBuffer: Array.from(mockRequesterBuffer),
+      fee: mockRequestFee,
+      createdSlot: mockCreatedSlot,
+      id: mockRequestId,
+      tip: mockRequestTip,
+      maxAnswerAgeSlots: 0,
+      jobId: 0,
+      optimistic: decodedOptimisticResponse,
     });
   });
 });
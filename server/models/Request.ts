@@ -1,5 +1,5 @@
 import { PublicKey } from '@solana/web3.js';
-import { seq, struct, u8 } from 'buffer-layout';
+import { nu64, seq, struct, u8 } from 'buffer-layout';
 import { TASK_LAYOUT } from './Task';
 
 // TODO remove this in favor of the Request class
@@ -7,18 +7,77 @@ export interface Request {
   tasks: Record<string, any>[];
   callerProgramIdBuffer: Buffer;
   index: number;
+  requesterBuffer: Buffer;
+  fee: number;
+  createdSlot: number;
+  id: number;
+  tip: number;
+  maxAnswerAgeSlots: number;
+  jobId: number;
+  optimistic: Record<string, any>;
 }
 
+export enum ResponseStatus {
+  UNANSWERED = 0,
+  PROPOSED = 1,
+  DISPUTED = 2,
+}
+
+export const OPTIMISTIC_RESPONSE_LAYOUT = struct([
+  // 0 delivers the answer as soon as the node responds
+  nu64('disputeWindowSlots'),
+  u8('status'),
+  seq(u8(), 4, 'data'),
+  nu64('proposedSlot'),
+  seq(u8(), 32, 'disputerBuffer'),
+  nu64('disputeBond'),
+]);
+
 export const REQUEST_LAYOUT = struct([
   seq(TASK_LAYOUT, 3, 'tasks'),
   seq(u8(), 32, 'callerProgramIdBuffer'),
   u8('index'),
+  seq(u8(), 32, 'requesterBuffer'),
+  nu64('fee'),
+  nu64('createdSlot'),
+  // arrival order, lower ids are answered first
+  nu64('id'),
+  // optional payment to the node, higher tips are answered first
+  nu64('tip'),
+  // oldest cached answer, in slots, the requester accepts. 0 for none
+  nu64('maxAnswerAgeSlots'),
+  // registered JobSpec the tasks were copied from. 0 for none
+  nu64('jobId'),
+  OPTIMISTIC_RESPONSE_LAYOUT.replicate('optimistic'),
 ]);
 
+/**
+ * A Request in optimistic mode only takes one answer, later ones are
+ * settled on-chain through a dispute
+ */
+export const isUnanswered = (request: Request): boolean =>
+  request.optimistic.status === ResponseStatus.UNANSWERED;
+
+/**
+ * Orders Requests highest tip first, then by arrival, matching the on-chain
+ * `pending_by_priority`
+ */
+export const byPriority = (a: Request, b: Request): number =>
+  b.tip - a.tip || a.id - b.id;
+
 export default class OracleRequest {
   tasks = [];
   callerProgramId: PublicKey;
   index: number;
+  requester: PublicKey;
+  fee: number;
+  createdSlot: number;
+  id: number;
+  tip: number;
+  maxAnswerAgeSlots: number;
+  jobId: number;
+  disputeWindowSlots: number;
+  status: ResponseStatus;
 
   constructor(buffer: Buffer) {
     const req = REQUEST_LAYOUT.decode(buffer);
@@ -26,5 +85,14 @@ export default class OracleRequest {
     const callerBuf = Buffer.from(req.callerProgramIdBuffer);
     this.callerProgramId = new PublicKey(callerBuf);
     this.index = req.index;
+    this.requester = new PublicKey(Buffer.from(req.requesterBuffer));
+    this.fee = req.fee;
+    this.createdSlot = req.createdSlot;
+    this.id = req.id;
+    this.tip = req.tip;
+    this.maxAnswerAgeSlots = req.maxAnswerAgeSlots;
+    this.jobId = req.jobId;
+    this.disputeWindowSlots = req.optimistic.disputeWindowSlots;
+    this.status = req.optimistic.status;
   }
 }
@@ -1,16 +1,35 @@
-import { seq, struct } from 'buffer-layout';
+import { seq, struct, u8 } from 'buffer-layout';
 import { Request, REQUEST_LAYOUT } from './Request';
 
-export const MAX_REQUESTS = 10;
-export const REQUEST_QUEUE_LAYOUT = struct([
-  seq(REQUEST_LAYOUT, MAX_REQUESTS, 'requests'),
-]);
+// capacity of an Oracle created without picking one
+export const DEFAULT_REQUEST_QUEUE_SIZE = 10;
+// one bit per slot an Oracle can hold
+export const OCCUPANCY_BITMAP_LEN = 32;
+
+export const requestQueueLayout = (capacity: number) =>
+  struct([
+    seq(u8(), OCCUPANCY_BITMAP_LEN, 'occupancy'),
+    seq(REQUEST_LAYOUT, capacity, 'requests'),
+  ]);
+
+export const REQUEST_QUEUE_LAYOUT = requestQueueLayout(
+  DEFAULT_REQUEST_QUEUE_SIZE,
+);
+
+/**
+ * Empty slots are only marked in the occupancy bitmap, their bytes must
+ * not be relied on
+ */
+export const isSlotOccupied = (occupancy: number[], index: number): boolean =>
+  (occupancy[Math.floor(index / 8)] & (1 << index % 8)) !== 0;
 
 export default class OracleRequestQueue {
+  occupancy: number[];
   requests: Request[];
 
-  constructor(buffer: Buffer) {
-    const req = REQUEST_QUEUE_LAYOUT.decode(buffer);
+  constructor(buffer: Buffer, capacity = DEFAULT_REQUEST_QUEUE_SIZE) {
+    const req = requestQueueLayout(capacity).decode(buffer);
+    this.occupancy = req.occupancy;
     this.requests = req.requests;
   }
 }
@@ -1,18 +1,38 @@
-import { Task, TaskVariantKeys, TASK_LAYOUT, TASK_TAG_LAYOUT } from './Task';
+import {
+  decodeHttpGet,
+  decodeHttpPost,
+  encodeHttpGet,
+  encodeHttpPost,
+  GET_URL_LEN,
+  Task,
+  TaskVariantKeys,
+  TASK_LAYOUT,
+  TASK_TAG_LAYOUT,
+} from './Task';
 import { mockPath, mockURL } from '../../testing/mockData';
 
 describe('Task', () => {
   describe('HTTP_GET', () => {
-    const urlBuffer = Buffer.from(mockURL, 'utf8');
-    const tagBuffer = Buffer.alloc(TASK_TAG_LAYOUT.span);
-    const taskBuffer = Buffer.concat([tagBuffer, urlBuffer]);
+    const get = {
+      url: mockURL,
+      headers: { Accept: 'application/json', 'X-Api-Key': 'secret:ftx' },
+    };
 
     it('should decode task', () => {
+      const taskBuffer = Buffer.alloc(TASK_LAYOUT.span);
+      Buffer.from(mockURL, 'utf8').copy(taskBuffer, TASK_TAG_LAYOUT.span);
+
       const task = TASK_LAYOUT.decode(taskBuffer);
-      expect(task).toEqual({
-        [TaskVariantKeys[Task.HTTP_GET]]: Array.from(urlBuffer),
-      });
       expect(TASK_LAYOUT.getSourceVariant(task)).toEqual(Task.HTTP_GET);
+      const getBuffer = task[TaskVariantKeys[Task.HTTP_GET]];
+      expect(decodeHttpGet(getBuffer)).toEqual({ url: mockURL, headers: {} });
+    });
+
+    it('should encode and decode headers after the url', () => {
+      const getBuffer = encodeHttpGet(get);
+      expect(getBuffer.length).toEqual(TASK_LAYOUT.span - TASK_TAG_LAYOUT.span);
+      expect(getBuffer[GET_URL_LEN]).toEqual(2);
+      expect(decodeHttpGet(getBuffer)).toEqual(get);
     });
   });
 
@@ -32,6 +52,40 @@ describe('Task', () => {
     });
   });
 
+  describe('HTTP_POST', () => {
+    const post = {
+      url: 'https://cloudflare-eth.com',
+      body: '{"jsonrpc":"2.0","id":1,"method":"eth_blockNumber"}',
+      contentType: 'application/json',
+      headers: {},
+    };
+
+    it('should decode task', () => {
+      const tagBuffer = Buffer.alloc(TASK_TAG_LAYOUT.span);
+      tagBuffer.writeUInt16LE(3);
+      const taskBuffer = Buffer.concat([
+        tagBuffer,
+        Buffer.from([0, post.url.length]),
+        Buffer.from(post.url, 'utf8'),
+        Buffer.from([post.body.length]),
+        Buffer.from(post.body, 'utf8'),
+        // no headers
+        Buffer.alloc(TASK_LAYOUT.span),
+      ]).slice(0, TASK_LAYOUT.span);
+
+      const task = TASK_LAYOUT.decode(taskBuffer);
+      expect(TASK_LAYOUT.getSourceVariant(task)).toEqual(Task.HTTP_POST);
+      const postBuffer = task[TaskVariantKeys[Task.HTTP_POST]];
+      expect(decodeHttpPost(postBuffer)).toEqual(post);
+      expect(encodeHttpPost(post)).toEqual(postBuffer);
+    });
+
+    it('should encode and decode headers after the body', () => {
+      const withHeaders = { ...post, headers: { 'X-Api-Key': 'secret:eth' } };
+      expect(decodeHttpPost(encodeHttpPost(withHeaders))).toEqual(withHeaders);
+    });
+  });
+
   describe('SOL_UINT_256', () => {
     it('should decode task', () => {
       const buffer = Buffer.alloc(TASK_TAG_LAYOUT.span);
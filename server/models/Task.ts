@@ -4,31 +4,168 @@ export enum Task {
   HTTP_GET = 0,
   JSON_PARSE = 1,
   UINT32 = 2,
+  HTTP_POST = 3,
 }
 
+export const TASK_KIND_COUNT = 4;
+// bytes a Task holds after its tag
+export const TASK_DATA_LEN = 96;
+// zero padded url of an HTTP_GET task, its headers follow
+export const GET_URL_LEN = 34;
+
+/**
+ * Media types an HTTP_POST task sends its body as, by the byte it is
+ * encoded with
+ */
+export const CONTENT_TYPES = [
+  'application/json',
+  'application/x-www-form-urlencoded',
+  'text/plain',
+];
+
 /**
  * Map each task to the key BufferLayout outputs to
  */
 export const TaskVariantKeys: Record<Task, string> = {
-  [Task.HTTP_GET]: 'urlBuffer',
+  [Task.HTTP_GET]: 'getBuffer',
   [Task.JSON_PARSE]: 'pathBuffer',
   [Task.UINT32]: 'uint32',
+  [Task.HTTP_POST]: 'postBuffer',
 };
 
 export const TASK_TAG_LAYOUT = u16('tag');
 
 // default layout must have the max length of the enum
-const defaultLayout = seq(u8(), 34, 'defaultLayout');
+const defaultLayout = seq(u8(), TASK_DATA_LEN, 'defaultLayout');
 export const TASK_LAYOUT = union(TASK_TAG_LAYOUT, defaultLayout);
 // Can't use utf8 as it kills composability
 // would be nice to find a way to have this auto convert to a utf8
 // string instead of a u8 array
-TASK_LAYOUT.addVariant(0, seq(u8(), 34), TaskVariantKeys[Task.HTTP_GET]);
+// the zero padded url, then the headers
+TASK_LAYOUT.addVariant(
+  0,
+  seq(u8(), TASK_DATA_LEN),
+  TaskVariantKeys[Task.HTTP_GET],
+);
 TASK_LAYOUT.addVariant(1, seq(u8(), 12), TaskVariantKeys[Task.JSON_PARSE]);
 TASK_LAYOUT.addVariant(2, TaskVariantKeys[Task.UINT32]);
+// the content type, then the url and the body each prefixed by its length,
+// then the headers
+TASK_LAYOUT.addVariant(
+  3,
+  seq(u8(), TASK_DATA_LEN),
+  TaskVariantKeys[Task.HTTP_POST],
+);
 
 const getTaskVariant = (src: any): Task => {
   return TASK_LAYOUT.defaultGetSourceVariant(src).variant as Task;
 };
 
 TASK_LAYOUT.configGetSourceVariant(getTaskVariant);
+
+export type Headers = Record<string, string>;
+
+export interface HttpGet {
+  url: string;
+  headers: Headers;
+}
+
+export interface HttpPost {
+  url: string;
+  body: string;
+  contentType: string;
+  headers: Headers;
+}
+
+/**
+ * Reads a header count followed by each name and value prefixed by its length
+ */
+const decodeHeaders = (buffer: Buffer): Headers => {
+  const headers: Headers = {};
+  let offset = 1;
+  const nextField = (): string => {
+    const len = buffer.readUInt8(offset);
+    const field = buffer.slice(offset + 1, offset + 1 + len).toString('utf8');
+    offset += 1 + len;
+    return field;
+  };
+  for (let i = 0; i < buffer.readUInt8(0); i += 1) {
+    const name = nextField();
+    headers[name] = nextField();
+  }
+  return headers;
+};
+
+const encodeHeaders = (headers: Headers): Buffer =>
+  Buffer.concat([
+    Buffer.from([Object.keys(headers).length]),
+    ...Object.entries(headers).map(([name, value]) => {
+      const nameBuffer = Buffer.from(name, 'utf8');
+      const valueBuffer = Buffer.from(value, 'utf8');
+      return Buffer.concat([
+        Buffer.from([nameBuffer.length]),
+        nameBuffer,
+        Buffer.from([valueBuffer.length]),
+        valueBuffer,
+      ]);
+    }),
+  ]);
+
+/**
+ * Splits the zero padded url of an HTTP_GET task from its headers
+ */
+export const decodeHttpGet = (getBuffer: number[]): HttpGet => {
+  const buffer = Buffer.from(getBuffer);
+  return {
+    url: buffer.slice(0, GET_URL_LEN).toString('utf8').replace(/\0/g, ''),
+    headers: decodeHeaders(buffer.slice(GET_URL_LEN)),
+  };
+};
+
+/**
+ * Encodes an HTTP_GET task's fields the way the Oracle Program packs them
+ */
+export const encodeHttpGet = ({ url, headers }: HttpGet): number[] => {
+  const buffer = Buffer.alloc(TASK_DATA_LEN);
+  Buffer.from(url, 'utf8').copy(buffer);
+  encodeHeaders(headers).copy(buffer, GET_URL_LEN);
+  return Array.from(buffer);
+};
+
+/**
+ * Splits the length prefixed fields of an HTTP_POST task
+ */
+export const decodeHttpPost = (postBuffer: number[]): HttpPost => {
+  const buffer = Buffer.from(postBuffer);
+  const urlLen = buffer.readUInt8(1);
+  const bodyStart = 2 + urlLen + 1;
+  const bodyLen = buffer.readUInt8(bodyStart - 1);
+  return {
+    url: buffer.slice(2, 2 + urlLen).toString('utf8'),
+    body: buffer.slice(bodyStart, bodyStart + bodyLen).toString('utf8'),
+    contentType: CONTENT_TYPES[buffer.readUInt8(0)],
+    headers: decodeHeaders(buffer.slice(bodyStart + bodyLen)),
+  };
+};
+
+/**
+ * Encodes an HTTP_POST task's fields the way the Oracle Program packs them
+ */
+export const encodeHttpPost = ({
+  url,
+  body,
+  contentType,
+  headers,
+}: HttpPost): number[] => {
+  const urlBuffer = Buffer.from(url, 'utf8');
+  const bodyBuffer = Buffer.from(body, 'utf8');
+  const buffer = Buffer.alloc(TASK_DATA_LEN);
+  Buffer.concat([
+    Buffer.from([CONTENT_TYPES.indexOf(contentType), urlBuffer.length]),
+    urlBuffer,
+    Buffer.from([bodyBuffer.length]),
+    bodyBuffer,
+    encodeHeaders(headers),
+  ]).copy(buffer);
+  return Array.from(buffer);
+};
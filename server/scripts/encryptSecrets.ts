@@ -0,0 +1,25 @@
+import { promises as fs } from 'fs';
+import { encryptSecrets } from '../utils';
+
+/**
+ * yarn encrypt-secrets [PLAINTEXT_JSON_PATH]
+ * Writes the secrets file the node reads from SECRETS_PATH. The plaintext
+ * maps each secret name advertised by the Oracle to its value and host.
+ */
+const main = async () => {
+  if (!process.env.SECRETS_PATH) {
+    throw new Error('No env varaible SECRETS_PATH found');
+  }
+  if (!process.env.SECRETS_KEY) {
+    throw new Error('No env varaible SECRETS_KEY found');
+  }
+
+  const plaintext = await fs.readFile(process.argv[2], 'utf8');
+  const contents = encryptSecrets(
+    JSON.parse(plaintext),
+    Buffer.from(process.env.SECRETS_KEY, 'hex'),
+  );
+  await fs.writeFile(process.env.SECRETS_PATH, contents, { mode: 0o600 });
+};
+
+main();
@@ -20,14 +20,14 @@ const main = async () => {
   const environment = (process.env.SOLANA_ENV as ClusterEnv) ?? ClusterEnv.dev;
   const connection = new Connection(cluster.rest[environment]);
 
-  const oracleDataAccount = await createDataAccountForProgram(
+  const oracleId = await createDataAccountForProgram(
     connection,
     payerAccount,
     oracleProgramId,
   );
 
   // Do NOT change this
-  console.log(oracleDataAccount.publicKey.toString());
+  console.log(oracleId.toString());
 };
 
 main();
@@ -5,16 +5,16 @@ describe('createDataAccountForProgram', () => {
     const payerAccount = solanaTestHelper.accounts[0];
     const firstProgramId = Object.values(solanaTestHelper.programs)[0];
 
-    const dataAccount = await createDataAccountForProgram(
+    const oracleId = await createDataAccountForProgram(
       solanaTestHelper.connection,
       payerAccount,
       firstProgramId,
     );
 
-    expect(dataAccount).toBeDefined();
+    expect(oracleId).toBeDefined();
 
     const accountInfo = await solanaTestHelper.connection.getAccountInfo(
-      dataAccount.publicKey,
+      oracleId,
     );
 
     expect(accountInfo!.owner.equals(firstProgramId)).toBe(true);
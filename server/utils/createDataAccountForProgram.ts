@@ -4,37 +4,107 @@ import {
   PublicKey,
   sendAndConfirmTransaction,
   SystemProgram,
+  SYSVAR_RENT_PUBKEY,
   Transaction,
+  TransactionInstruction,
 } from '@solana/web3.js';
+import { nu64, seq, struct, u8, u16 } from 'buffer-layout';
 
-import { ORACLE_ACCOUNT_LAYOUT } from '../models/OracleAccount';
+import { DEFAULT_REQUEST_QUEUE_SIZE } from '../models/RequestQueue';
 
+const CREATE_ORACLE_INSTRUCTION = 23;
+const DEFAULT_REQUEST_EXPIRY_SLOTS = 300;
+const ORACLE_SEED = Buffer.from('oracle', 'utf8');
+const ORACLE_SEED_LEN = 32;
+
+const CREATE_ORACLE_LAYOUT = struct([
+  u16('instruction'),
+  seq(u8(), ORACLE_SEED_LEN, 'seed'),
+  seq(u8(), 32, 'nodeAuthorityBuffer'),
+  nu64('requestFee'),
+  nu64('requestExpirySlots'),
+  seq(u8(), 32, 'feeMintBuffer'),
+  u16('capacity'),
+]);
+
+/**
+ * Address of the Oracle created by `admin` with `seed`
+ */
+export const findOracleAddress = async (
+  programId: PublicKey,
+  admin: PublicKey,
+  seed: Buffer,
+): Promise<PublicKey> => {
+  const [oracleId] = await PublicKey.findProgramAddress(
+    [admin.toBuffer(), seed, ORACLE_SEED],
+    programId,
+  );
+  return oracleId;
+};
+
+/**
+ * Create the Oracle Data Account at the program address of the payer and
+ * `seed`, with room for `capacity` Requests, and initialize its header. The payer becomes both the admin and
+ * the node authority of the Oracle.
+ */
 export const createDataAccountForProgram = async (
   connection: Connection,
   payerAccount: Account,
   programId: PublicKey,
-): Promise<Account> => {
-  const dataAccount = new Account();
-  const space = ORACLE_ACCOUNT_LAYOUT.span;
-  const lamps = await connection.getMinimumBalanceForRentExemption(space);
-  const createAccountTX = new Transaction().add(
-    SystemProgram.createAccount({
-      fromPubkey: payerAccount.publicKey,
-      newAccountPubkey: dataAccount.publicKey,
-      lamports: lamps,
-      space,
+  requestFee = 0,
+  requestExpirySlots = DEFAULT_REQUEST_EXPIRY_SLOTS,
+  // any 32 bytes, random by default
+  seed = new Account().publicKey.toBuffer(),
+  capacity = DEFAULT_REQUEST_QUEUE_SIZE,
+): Promise<PublicKey> => {
+  const oracleId = await findOracleAddress(
+    programId,
+    payerAccount.publicKey,
+    seed,
+  );
+  const createOracleData = Buffer.alloc(CREATE_ORACLE_LAYOUT.span);
+  CREATE_ORACLE_LAYOUT.encode(
+    {
+      instruction: CREATE_ORACLE_INSTRUCTION,
+      seed: Array.from(seed),
+      nodeAuthorityBuffer: Array.from(payerAccount.publicKey.toBuffer()),
+      requestFee,
+      requestExpirySlots,
+      // fees are charged in lamports
+      feeMintBuffer: Array.from(Buffer.alloc(32)),
+      capacity,
+    },
+    createOracleData,
+  );
+  const createOracleTX = new Transaction().add(
+    new TransactionInstruction({
+      keys: [
+        { pubkey: oracleId, isSigner: false, isWritable: true },
+        { pubkey: payerAccount.publicKey, isSigner: true, isWritable: true },
+        {
+          pubkey: SystemProgram.programId,
+          isSigner: false,
+          isWritable: false,
+        },
+        { pubkey: SYSVAR_RENT_PUBKEY, isSigner: false, isWritable: false },
+      ],
       programId,
+      data: createOracleData,
     }),
   );
-  const signers = [payerAccount, dataAccount];
   try {
-    await sendAndConfirmTransaction(connection, createAccountTX, signers, {
-      skipPreflight: true,
-      commitment: 'recent',
-    });
+    await sendAndConfirmTransaction(
+      connection,
+      createOracleTX,
+      [payerAccount],
+      {
+        skipPreflight: true,
+        commitment: 'recent',
+      },
+    );
   } catch (err) {
     throw new Error(`Failed to create new Oracle Data Account ${err}`);
   }
 
-  return dataAccount;
+  return oracleId;
 };
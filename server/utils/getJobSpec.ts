@@ -0,0 +1,38 @@
+import { Connection, PublicKey } from '@solana/web3.js';
+
+import JobSpec, { findJobAddress } from '../models/JobSpec';
+
+// JobSpecs can not be changed once registered, so they are fetched only once
+const jobSpecs = new Map<string, Promise<JobSpec>>();
+
+/**
+ * Fetches the JobSpec `jobId` of an Oracle, caching it for later Requests
+ * @param connection
+ * @param programId
+ * @param oracleId
+ * @param jobId
+ */
+export const getJobSpec = (
+  connection: Connection,
+  programId: PublicKey,
+  oracleId: PublicKey,
+  jobId: number,
+): Promise<JobSpec> => {
+  const key = `${oracleId.toString()}:${jobId}`;
+  if (!jobSpecs.has(key)) {
+    const jobSpec = findJobAddress(programId, oracleId, jobId)
+      .then((jobAddress) => connection.getAccountInfo(jobAddress))
+      .then((jobAccountInfo) => {
+        if (!jobAccountInfo) {
+          throw new Error(
+            `JobSpec ${jobId} of Oracle ${oracleId.toString()} does not exist`,
+          );
+        }
+        return new JobSpec(jobAccountInfo.data);
+      });
+    // a failed fetch is retried on the next Request
+    jobSpec.catch(() => jobSpecs.delete(key));
+    jobSpecs.set(key, jobSpec);
+  }
+  return jobSpecs.get(key);
+};
@@ -1,9 +1,17 @@
 import { Account, AccountInfo, Connection, PublicKey } from '@solana/web3.js';
 
-import { Request } from '../models/Request';
-import OracleAccount from '../models/OracleAccount';
+import { byPriority, isUnanswered, Request } from '../models/Request';
+import { isSlotOccupied } from '../models/RequestQueue';
+import OracleAccount, { VAULT_AUTHORITY_SEED } from '../models/OracleAccount';
+import { getJobSpec } from './getJobSpec';
 import { reduceTasks } from './reduceTasks';
-import { sendTransactionToHandleResponse } from './sendTransactionToHandleResponse';
+import { Secrets } from './secrets';
+import {
+  BatchedResponse,
+  MAX_BATCHED_RESPONSES,
+  sendTransactionToHandleResponses,
+  TokenFeeAccounts,
+} from './sendTransactionToHandleResponse';
 
 const requestsInFlight = {};
 
@@ -13,59 +21,133 @@ const requestsInFlight = {};
  * @param payerAccount
  * @param programId
  * @param oracleId
+ * @param nodeTokenAccount token account receiving fees of Oracles that charge in an SPL Token
+ * @param secrets values of the secrets the Oracle advertises
  */
 export const createHandleAccountChange = (
   connection: Connection,
   payerAccount: Account,
   programId: PublicKey,
   oracleId: PublicKey,
+  nodeTokenAccount?: PublicKey,
+  secrets: Secrets = {},
 ) => async (oracleAccountInfo: AccountInfo<Buffer>): Promise<void> => {
   const { data } = oracleAccountInfo;
-  const firstRequestByte = data[3];
-  // check the first non instruction byte, if it's 0, there is no request in the buffer
-  if (firstRequestByte === 0) {
-    // no url to request, short circuit
+  const oracleAccount = new OracleAccount(data);
+  // the Oracle Program rejects Responses while paused
+  if (oracleAccount.isPaused) {
     return;
   }
   const {
-    requestQueue: { requests },
-  } = new OracleAccount(data);
-  requests.forEach(
-    async (request: Request): Promise<void> => {
-      // check if the request is in flight
-      if (!requestsInFlight[request.index]) {
-        requestsInFlight[request.index] = request;
+    requestQueue: { occupancy, requests },
+  } = oracleAccount;
 
-        const callerProgramIdBuf = Buffer.from(request.callerProgramIdBuffer);
-        const callerProgramId = new PublicKey(callerProgramIdBuf);
+  let tokenFeeAccounts: TokenFeeAccounts | undefined;
+  if (oracleAccount.chargesTokenFee()) {
+    if (!nodeTokenAccount) {
+      throw new Error(
+        `Oracle ${oracleId.toString()} charges token fees, a node token account is required`,
+      );
+    }
+    const [vaultAuthority] = await PublicKey.findProgramAddress(
+      [oracleId.toBuffer(), Buffer.from(VAULT_AUTHORITY_SEED)],
+      programId,
+    );
+    tokenFeeAccounts = {
+      feeVault: oracleAccount.feeVault,
+      nodeTokenAccount,
+      vaultAuthority,
+    };
+  }
+
+  // check if the request is in flight
+  const pending = requests
+    .filter(
+      (request, index) =>
+        isSlotOccupied(occupancy, index) &&
+        isUnanswered(request) &&
+        !requestsInFlight[request.index],
+    )
+    .sort(byPriority)
+    .slice(0, MAX_BATCHED_RESPONSES);
+  if (pending.length === 0) {
+    return;
+  }
+  pending.forEach((request: Request) => {
+    requestsInFlight[request.index] = request;
+  });
 
-        console.log(
-          `Handling request ${
-            request.index
-          } for Caller Program ${callerProgramId.toString()}`,
-        );
-        const responseData = await reduceTasks(request.tasks);
-        const requestIndexBuffer = Buffer.alloc(1);
-        requestIndexBuffer.writeUInt8(request.index);
-        const response = Buffer.concat([
-          // Need 1 byte of padding for the Response determinant
-          Buffer.alloc(1),
-          responseData,
-          requestIndexBuffer,
-        ]);
+  // identical Tasks are run once so their requesters get the same answer
+  const answers = new Map<string, Promise<Buffer>>();
+  const answerTasks = (tasks: Record<string, any>[]): Promise<Buffer> => {
+    const key = JSON.stringify(tasks);
+    if (!answers.has(key)) {
+      answers.set(key, reduceTasks(tasks, secrets));
+    }
+    return answers.get(key);
+  };
+  // Requests for a registered job run the locally cached JobSpec, unless
+  // it is a template the Oracle Program filled in with the Request's arguments
+  const requestTasks = async (
+    request: Request,
+  ): Promise<Record<string, any>[]> => {
+    if (request.jobId === 0) {
+      return request.tasks;
+    }
+    const jobSpec = await getJobSpec(
+      connection,
+      programId,
+      oracleId,
+      request.jobId,
+    );
+    return jobSpec.isTemplate() ? request.tasks : jobSpec.tasks;
+  };
 
-        await sendTransactionToHandleResponse(
-          connection,
-          payerAccount,
-          programId,
-          oracleId,
-          callerProgramId,
-          response,
-        );
-        console.log('Response sent Oracle Program!');
-        // After the TX has been confirmed remove the index from inflight
-        delete requestsInFlight[request.index];
-      }
-    },
-  );
+  try {
+    const responses = await Promise.all(
+      pending.map(
+        async (request: Request): Promise<BatchedResponse> => {
+          const callerProgramIdBuf = Buffer.from(
+            request.callerProgramIdBuffer,
+          );
+          const callerProgramId = new PublicKey(callerProgramIdBuf);
+
+          console.log(
+            `Handling request ${
+              request.index
+            } for Caller Program ${callerProgramId.toString()}`,
+          );
+          const responseData = await answerTasks(
+            await requestTasks(request),
+          );
+          const requestIndexBuffer = Buffer.alloc(1);
+          requestIndexBuffer.writeUInt8(request.index);
+          const response = Buffer.concat([
+            // Need 1 byte of padding for the Response determinant
+            Buffer.alloc(1),
+            responseData,
+            requestIndexBuffer,
+          ]);
+          return { callerProgramId, response };
+        },
+      ),
+    );
+
+    // Requests answered or cancelled meanwhile are skipped by the program
+    await sendTransactionToHandleResponses(
+      connection,
+      payerAccount,
+      programId,
+      oracleId,
+      responses,
+      false,
+      tokenFeeAccounts,
+    );
+    console.log(`${responses.length} Responses sent Oracle Program!`);
+  } finally {
+    // After the TX has been confirmed remove the indexes from inflight
+    pending.forEach((request: Request) => {
+      delete requestsInFlight[request.index];
+    });
+  }
 };
@@ -0,0 +1,66 @@
+import { Account, Connection, PublicKey } from '@solana/web3.js';
+
+import OracleFeed from '../models/Feed';
+import { reduceTasks } from './reduceTasks';
+import { Secrets } from './secrets';
+import { sendTransactionToUpdateFeed } from './sendTransactionToUpdateFeed';
+
+/**
+ * Factory to create the function that refreshes a Feed. The returned
+ * function should be called on an interval; it only submits an UpdateFeed
+ * transaction when the new value would be accepted by the Oracle Program.
+ * @param connection
+ * @param payerAccount - must be the Feed's authority
+ * @param programId
+ * @param feedId
+ * @param secrets - values of the secrets the Feed's Tasks refer to
+ */
+export const createHandleFeed = (
+  connection: Connection,
+  payerAccount: Account,
+  programId: PublicKey,
+  feedId: PublicKey,
+  secrets: Secrets = {},
+) => {
+  let updateInFlight = false;
+  return async (): Promise<void> => {
+    if (updateInFlight) {
+      return;
+    }
+    updateInFlight = true;
+    try {
+      const feedAccountInfo = await connection.getAccountInfo(feedId);
+      if (!feedAccountInfo) {
+        console.log(`Feed ${feedId.toString()} does not exist`);
+        return;
+      }
+      const feed = new OracleFeed(feedAccountInfo.data);
+      if (!feed.isInitialized) {
+        return;
+      }
+      if (feed.isTripped) {
+        console.log(
+          `Feed ${feedId.toString()} circuit breaker tripped, waiting for a ResetFeed`,
+        );
+        return;
+      }
+      const valueBuffer = await reduceTasks(feed.tasks, secrets);
+      const value = valueBuffer.readUInt32LE(0);
+      const slot = await connection.getSlot();
+      if (!feed.shouldUpdate(value, slot)) {
+        return;
+      }
+
+      console.log(`Updating Feed ${feedId.toString()} to ${value}`);
+      await sendTransactionToUpdateFeed(
+        connection,
+        payerAccount,
+        programId,
+        feedId,
+        valueBuffer,
+      );
+    } finally {
+      updateInFlight = false;
+    }
+  };
+};
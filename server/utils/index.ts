@@ -1,4 +1,7 @@
 export * from './createDataAccountForProgram';
 export * from './cluster';
+export * from './getJobSpec';
 export * from './handleAccountChange';
+export * from './handleFeed';
 export * from './reduceTasks';
+export * from './secrets';
@@ -1,6 +1,12 @@
 import nock from 'nock';
 
 import { REQUEST_LAYOUT } from '../models/Request';
+import {
+  encodeHttpGet,
+  encodeHttpPost,
+  Task,
+  TaskVariantKeys,
+} from '../models/Task';
 import {
   mockRequestBuffer,
   mockUrlBase,
@@ -47,4 +53,64 @@ describe('reduceTasks', () => {
     expect(nockRequest.isDone()).toBe(true);
     expect(result).toEqual(buf);
   });
+
+  it('should send the body of an HTTP_POST task', async () => {
+    const body = '{"jsonrpc":"2.0","id":1,"method":"eth_blockNumber"}';
+    const nockRequest = nock('https://cloudflare-eth.com', {
+      reqheaders: { 'Content-Type': 'application/json' },
+    })
+      .post('/', body)
+      .reply(200, { jsonrpc: '2.0', id: 1, result: 11535000 });
+    const tasks = [
+      {
+        [TaskVariantKeys[Task.HTTP_POST]]: encodeHttpPost({
+          url: 'https://cloudflare-eth.com/',
+          body,
+          contentType: 'application/json',
+          headers: {},
+        }),
+      },
+      { [TaskVariantKeys[Task.JSON_PARSE]]: Array.from(Buffer.from('result')) },
+      { [TaskVariantKeys[Task.UINT32]]: true },
+    ];
+
+    const result = await reduceTasks(tasks);
+    const buf = Buffer.alloc(4);
+    buf.writeUInt32LE(11535000);
+    expect(nockRequest.isDone()).toBe(true);
+    expect(result).toEqual(buf);
+  });
+
+  it('should send the secrets headers refer to', async () => {
+    const nockRequest = nock('https://pro-api.coingecko.com', {
+      reqheaders: { 'x-cg-pro-api-key': 'mock-api-key' },
+    })
+      .get('/ping')
+      .reply(200, { gecko_says: 42 });
+    const tasks = [
+      {
+        [TaskVariantKeys[Task.HTTP_GET]]: encodeHttpGet({
+          url: 'https://pro-api.coingecko.com/ping',
+          headers: { 'x-cg-pro-api-key': 'secret:coingecko_pro' },
+        }),
+      },
+      {
+        [TaskVariantKeys[Task.JSON_PARSE]]: Array.from(
+          Buffer.from('gecko_says'),
+        ),
+      },
+      { [TaskVariantKeys[Task.UINT32]]: true },
+    ];
+
+    await expect(reduceTasks(tasks)).rejects.toThrow(
+      'Unknown secret coingecko_pro',
+    );
+    const result = await reduceTasks(tasks, {
+      coingecko_pro: { value: 'mock-api-key', host: 'pro-api.coingecko.com' },
+    });
+    const buf = Buffer.alloc(4);
+    buf.writeUInt32LE(42);
+    expect(nockRequest.isDone()).toBe(true);
+    expect(result).toEqual(buf);
+  });
 });
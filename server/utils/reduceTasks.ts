@@ -1,14 +1,24 @@
 import fetch from 'node-fetch';
 import _get from 'lodash/get';
 
-import { Task, TaskVariantKeys, TASK_LAYOUT } from '../models/Task';
+import {
+  decodeHttpGet,
+  decodeHttpPost,
+  Task,
+  TaskVariantKeys,
+  TASK_LAYOUT,
+} from '../models/Task';
+import { resolveSecrets, Secrets } from './secrets';
 
 /**
  * Should ultimately return a buffer of the response data
  * to be send to the Caller Program
+ * @param tasks
+ * @param secrets - values of the secrets fetch Tasks' headers refer to
  */
 export const reduceTasks = async (
   tasks: Record<string, any>[],
+  secrets: Secrets = {},
 ): Promise<Buffer> =>
   tasks.reduce(async (acc, task): Promise<string | number | Buffer> => {
     const variant: Task = TASK_LAYOUT.getSourceVariant(task);
@@ -19,9 +29,22 @@ export const reduceTasks = async (
 
     switch (variant) {
       case Task.HTTP_GET:
-        const url = Buffer.from(value).toString().replace(/\0/g, '');
-        console.log(`TASK::HTTP_GET making request to ${url}`);
-        return fetch(url);
+        const get = decodeHttpGet(value);
+        console.log(`TASK::HTTP_GET making request to ${get.url}`);
+        return fetch(get.url, {
+          headers: resolveSecrets(get.headers, secrets, get.url),
+        });
+      case Task.HTTP_POST:
+        const post = decodeHttpPost(value);
+        console.log(`TASK::HTTP_POST making request to ${post.url}`);
+        return fetch(post.url, {
+          method: 'POST',
+          body: post.body,
+          headers: {
+            ...resolveSecrets(post.headers, secrets, post.url),
+            'Content-Type': post.contentType,
+          },
+        });
       case Task.JSON_PARSE:
         const path = Buffer.from(value).toString().replace(/\0/g, '');
         const json = await resolvedAcc.json();
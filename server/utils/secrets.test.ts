@@ -0,0 +1,67 @@
+import { randomBytes } from 'crypto';
+
+import { decryptSecrets, encryptSecrets, resolveSecrets } from './secrets';
+
+describe('secrets', () => {
+  const key = randomBytes(32);
+  const secrets = {
+    coingecko_pro: { value: 'mock-api-key', host: 'pro-api.coingecko.com' },
+  };
+  const url = 'https://pro-api.coingecko.com/api/v3/ping';
+
+  it('should decrypt what it encrypted', () => {
+    const contents = encryptSecrets(secrets, key);
+    expect(contents).not.toContain('mock-api-key');
+    expect(decryptSecrets(contents, key)).toEqual(secrets);
+  });
+
+  it('should reject the wrong key or a tampered file', () => {
+    const contents = encryptSecrets(secrets, key);
+    expect(() => decryptSecrets(contents, randomBytes(32))).toThrow();
+
+    const encrypted = JSON.parse(contents);
+    const ciphertext = Buffer.from(encrypted.ciphertext, 'hex');
+    ciphertext[0] ^= 1;
+    encrypted.ciphertext = ciphertext.toString('hex');
+    expect(() => decryptSecrets(JSON.stringify(encrypted), key)).toThrow();
+  });
+
+  it('should resolve header values referring to a secret', () => {
+    const headers = {
+      Accept: 'application/json',
+      'x-cg-pro-api-key': 'secret:coingecko_pro',
+    };
+    expect(resolveSecrets(headers, secrets, url)).toEqual({
+      Accept: 'application/json',
+      'x-cg-pro-api-key': 'mock-api-key',
+    });
+    expect(() =>
+      resolveSecrets({ Authorization: 'secret:toString' }, secrets, url),
+    ).toThrow('Unknown secret toString');
+  });
+
+  it('should only send a secret to its host', () => {
+    const headers = { 'x-cg-pro-api-key': 'secret:coingecko_pro' };
+    expect(() =>
+      resolveSecrets(headers, secrets, 'https://attacker.example/steal'),
+    ).toThrow('Secret coingecko_pro may not be sent');
+    expect(() =>
+      resolveSecrets(
+        headers,
+        secrets,
+        'https://pro-api.coingecko.com.attacker.example/',
+      ),
+    ).toThrow('Secret coingecko_pro may not be sent');
+    [
+      'http://pro-api.coingecko.com/api/v3/ping',
+      'https:/evil.io/://pro-api.coingecko.com/x',
+      'https:\\\\evil.io/://pro-api.coingecko.com/x',
+      'https://pro-api.coingecko.com@evil.io/',
+      'https://pro-api.coingecko.com:8443/api/v3/ping',
+    ].forEach((target) =>
+      expect(() => resolveSecrets(headers, secrets, target)).toThrow(
+        'Secret coingecko_pro may not be sent',
+      ),
+    );
+  });
+});
@@ -0,0 +1,110 @@
+import { createCipheriv, createDecipheriv, randomBytes } from 'crypto';
+import { promises as fs } from 'fs';
+
+import { Headers } from '../models/Task';
+
+// header values starting with this name a secret held by the node
+export const SECRET_PREFIX = 'secret:';
+
+const CIPHER = 'aes-256-gcm';
+const IV_LEN = 12;
+
+// a secret's value and the only host it is sent to, matching the host the
+// Oracle advertises for it
+export interface Secret {
+  value: string;
+  host: string;
+}
+
+// secrets keyed by the names the Oracle advertises
+export type Secrets = Record<string, Secret>;
+
+interface EncryptedSecrets {
+  iv: string;
+  authTag: string;
+  ciphertext: string;
+}
+
+/**
+ * Encrypts `secrets` with a 32 byte `key` into the contents of a secrets file
+ * @param secrets
+ * @param key
+ */
+export const encryptSecrets = (secrets: Secrets, key: Buffer): string => {
+  const iv = randomBytes(IV_LEN);
+  const cipher = createCipheriv(CIPHER, key, iv);
+  const ciphertext = Buffer.concat([
+    cipher.update(JSON.stringify(secrets), 'utf8'),
+    cipher.final(),
+  ]);
+  const encrypted: EncryptedSecrets = {
+    iv: iv.toString('hex'),
+    authTag: cipher.getAuthTag().toString('hex'),
+    ciphertext: ciphertext.toString('hex'),
+  };
+  return JSON.stringify(encrypted);
+};
+
+/**
+ * Decrypts the contents of a secrets file, throws if it was not encrypted
+ * with `key` or was tampered with
+ * @param contents
+ * @param key
+ */
+export const decryptSecrets = (contents: string, key: Buffer): Secrets => {
+  const encrypted: EncryptedSecrets = JSON.parse(contents);
+  const decipher = createDecipheriv(
+    CIPHER,
+    key,
+    Buffer.from(encrypted.iv, 'hex'),
+  );
+  decipher.setAuthTag(Buffer.from(encrypted.authTag, 'hex'));
+  const plaintext = Buffer.concat([
+    decipher.update(Buffer.from(encrypted.ciphertext, 'hex')),
+    decipher.final(),
+  ]);
+  return JSON.parse(plaintext.toString('utf8'));
+};
+
+/**
+ * Reads the node's secrets file
+ * @param path
+ * @param key - hex encoded 32 byte key
+ */
+export const loadSecrets = async (
+  path: string,
+  key: string,
+): Promise<Secrets> => {
+  const contents = await fs.readFile(path, 'utf8');
+  return decryptSecrets(contents, Buffer.from(key, 'hex'));
+};
+
+/**
+ * Replaces header values referring to a secret with the secret's value.
+ * Throws on a secret the node does not hold, or one that may not be sent
+ * to the host of `url`, rather than sending the reference itself.
+ * @param headers
+ * @param secrets
+ * @param url - the url the headers are sent to
+ */
+export const resolveSecrets = (
+  headers: Headers,
+  secrets: Secrets,
+  url: string,
+): Headers =>
+  Object.entries(headers).reduce((resolved, [name, value]) => {
+    if (!value.startsWith(SECRET_PREFIX)) {
+      return { ...resolved, [name]: value };
+    }
+    const secretName = value.slice(SECRET_PREFIX.length);
+    if (!Object.prototype.hasOwnProperty.call(secrets, secretName)) {
+      throw new Error(`Unknown secret ${secretName}`);
+    }
+    const secret = secrets[secretName];
+    // the host as the HTTP client reads it, not as the url appears to read
+    const target = new URL(url);
+    if (target.protocol !== 'https:' || target.host !== secret.host) {
+      throw new Error(`Secret ${secretName} may not be sent to ${url}`);
+    }
+    return { ...resolved, [name]: secret.value };
+  }, {} as Headers);
@@ -3,10 +3,53 @@ import {
   Connection,
   PublicKey,
   sendAndConfirmTransaction,
+  SYSVAR_CLOCK_PUBKEY,
   Transaction,
   TransactionInstruction,
 } from '@solana/web3.js';
+import { findNodeAddress } from '../models/OracleAccount';
+
 const INSTRUCTION_SIZE = 2;
+const HANDLE_RESPONSES_TAG = 26;
+// Most Responses the Oracle Program takes in one HandleResponses instruction
+export const MAX_BATCHED_RESPONSES = 16;
+
+export const TOKEN_PROGRAM_ID = new PublicKey(
+  'TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA',
+);
+
+/**
+ * Accounts needed to be paid out of the fee vault of an Oracle that
+ * charges its fees in an SPL Token
+ */
+export interface TokenFeeAccounts {
+  feeVault: PublicKey;
+  nodeTokenAccount: PublicKey;
+  vaultAuthority: PublicKey;
+}
+
+/**
+ * A Response with the Caller Program receiving its callback
+ */
+export interface BatchedResponse {
+  callerProgramId: PublicKey;
+  response: Buffer;
+}
+
+const tokenFeeKeys = (tokenFeeAccounts: TokenFeeAccounts) => [
+  { pubkey: tokenFeeAccounts.feeVault, isSigner: false, isWritable: true },
+  {
+    pubkey: tokenFeeAccounts.nodeTokenAccount,
+    isSigner: false,
+    isWritable: true,
+  },
+  {
+    pubkey: tokenFeeAccounts.vaultAuthority,
+    isSigner: false,
+    isWritable: false,
+  },
+  { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+];
 
 export const sendTransactionToHandleResponse = async (
   connection: Connection,
@@ -15,15 +58,32 @@ export const sendTransactionToHandleResponse = async (
   oracleId: PublicKey,
   callerProgramId: PublicKey,
   response: Buffer,
+  tokenFeeAccounts?: TokenFeeAccounts,
 ): Promise<string> => {
   const handleResponseInstruction = Buffer.alloc(INSTRUCTION_SIZE);
   handleResponseInstruction.writeUInt8(1);
+  const nodeId = await findNodeAddress(
+    programId,
+    oracleId,
+    payerAccount.publicKey,
+  );
+
+  const keys = [
+    { pubkey: oracleId, isSigner: false, isWritable: true },
+    { pubkey: callerProgramId, isSigner: false, isWritable: false },
+    // the node authority collects the Request fee
+    { pubkey: payerAccount.publicKey, isSigner: true, isWritable: true },
+    // stamps the proposal of optimistic Requests
+    { pubkey: SYSVAR_CLOCK_PUBKEY, isSigner: false, isWritable: false },
+    // the node must be registered once the Oracle requires a minimum stake
+    { pubkey: nodeId, isSigner: false, isWritable: false },
+  ];
+  if (tokenFeeAccounts) {
+    keys.push(...tokenFeeKeys(tokenFeeAccounts));
+  }
 
   const createRequestTxInstruction = new TransactionInstruction({
-    keys: [
-      { pubkey: oracleId, isSigner: false, isWritable: true },
-      { pubkey: callerProgramId, isSigner: false, isWritable: false },
-    ],
+    keys,
     programId,
     data: Buffer.concat([handleResponseInstruction, response]),
   });
@@ -38,3 +98,71 @@ export const sendTransactionToHandleResponse = async (
     },
   );
 };
+
+/**
+ * Answer several Requests in one HandleResponses instruction. Unless
+ * `atomic` is set, Responses the Oracle Program rejects are skipped
+ * instead of failing the whole batch.
+ */
+export const sendTransactionToHandleResponses = async (
+  connection: Connection,
+  payerAccount: Account,
+  programId: PublicKey,
+  oracleId: PublicKey,
+  responses: BatchedResponse[],
+  atomic = false,
+  tokenFeeAccounts?: TokenFeeAccounts,
+): Promise<string> => {
+  if (responses.length > MAX_BATCHED_RESPONSES) {
+    throw new Error(
+      `At most ${MAX_BATCHED_RESPONSES} Responses fit in one instruction`,
+    );
+  }
+  const handleResponsesInstruction = Buffer.alloc(INSTRUCTION_SIZE + 2);
+  handleResponsesInstruction.writeUInt16LE(HANDLE_RESPONSES_TAG);
+  handleResponsesInstruction.writeUInt8(atomic ? 1 : 0, INSTRUCTION_SIZE);
+  handleResponsesInstruction.writeUInt8(
+    responses.length,
+    INSTRUCTION_SIZE + 1,
+  );
+  const nodeId = await findNodeAddress(
+    programId,
+    oracleId,
+    payerAccount.publicKey,
+  );
+
+  const keys = [
+    { pubkey: oracleId, isSigner: false, isWritable: true },
+    // the node authority collects the Request fees
+    { pubkey: payerAccount.publicKey, isSigner: true, isWritable: true },
+    { pubkey: SYSVAR_CLOCK_PUBKEY, isSigner: false, isWritable: false },
+    { pubkey: nodeId, isSigner: false, isWritable: false },
+    ...responses.map(({ callerProgramId }) => ({
+      pubkey: callerProgramId,
+      isSigner: false,
+      isWritable: false,
+    })),
+  ];
+  if (tokenFeeAccounts) {
+    keys.push(...tokenFeeKeys(tokenFeeAccounts));
+  }
+
+  const handleResponsesTxInstruction = new TransactionInstruction({
+    keys,
+    programId,
+    data: Buffer.concat([
+      handleResponsesInstruction,
+      ...responses.map(({ response }) => response),
+    ]),
+  });
+
+  return await sendAndConfirmTransaction(
+    connection,
+    new Transaction().add(handleResponsesTxInstruction),
+    [payerAccount],
+    {
+      skipPreflight: true,
+      commitment: 'recent',
+    },
+  );
+};
@@ -0,0 +1,42 @@
+import {
+  Account,
+  Connection,
+  PublicKey,
+  sendAndConfirmTransaction,
+  SYSVAR_CLOCK_PUBKEY,
+  Transaction,
+  TransactionInstruction,
+} from '@solana/web3.js';
+const INSTRUCTION_SIZE = 2;
+const UPDATE_FEED_INSTRUCTION = 3;
+
+export const sendTransactionToUpdateFeed = async (
+  connection: Connection,
+  payerAccount: Account,
+  programId: PublicKey,
+  feedId: PublicKey,
+  value: Buffer,
+): Promise<string> => {
+  const updateFeedInstruction = Buffer.alloc(INSTRUCTION_SIZE);
+  updateFeedInstruction.writeUInt16LE(UPDATE_FEED_INSTRUCTION);
+
+  const updateFeedTxInstruction = new TransactionInstruction({
+    keys: [
+      { pubkey: feedId, isSigner: false, isWritable: true },
+      { pubkey: payerAccount.publicKey, isSigner: true, isWritable: false },
+      { pubkey: SYSVAR_CLOCK_PUBKEY, isSigner: false, isWritable: false },
+    ],
+    programId,
+    data: Buffer.concat([updateFeedInstruction, value]),
+  });
+
+  return await sendAndConfirmTransaction(
+    connection,
+    new Transaction().add(updateFeedTxInstruction),
+    [payerAccount],
+    {
+      skipPreflight: true,
+      commitment: 'recent',
+    },
+  );
+};
@@ -1,12 +1,25 @@
 import {
   Task,
   TaskVariantKeys,
+  TASK_DATA_LEN,
   TASK_LAYOUT,
   TASK_TAG_LAYOUT,
 } from '../server/models/Task';
-import { REQUEST_LAYOUT } from '../server/models/Request';
+import {
+  OPTIMISTIC_RESPONSE_LAYOUT,
+  REQUEST_LAYOUT,
+} from '../server/models/Request';
 import { RESPONSE_LAYOUT } from '../server/models/Response';
-import { MAX_REQUESTS } from '../server/models/RequestQueue';
+import {
+  DEFAULT_REQUEST_QUEUE_SIZE,
+  OCCUPANCY_BITMAP_LEN,
+} from '../server/models/RequestQueue';
+import {
+  ANSWER_CACHE_SIZE,
+  MAX_ORACLE_SECRETS,
+  ORACLE_HEADER_LAYOUT,
+  SECRET_NAME_LEN,
+} from '../server/models/OracleAccount';
 
 export const mockUrlBase = 'https://ftx.us';
 export const mockUrlPath = '/api/markets/BTC/USD';
@@ -18,7 +31,9 @@ export const mockPath = 'result.price';
 const getTagBuffer = Buffer.alloc(TASK_TAG_LAYOUT.span);
 const jsonTagBuffer = Buffer.alloc(TASK_TAG_LAYOUT.span);
 const uint256Tagbuffer = Buffer.alloc(TASK_TAG_LAYOUT.span);
-const httpGetTaskBuffer = Buffer.alloc(TASK_LAYOUT.span - TASK_TAG_LAYOUT.span);
+export const mockHttpGetTaskBuffer = Buffer.alloc(
+  TASK_LAYOUT.span - TASK_TAG_LAYOUT.span,
+);
 const jsonParseTaskBuffer = Buffer.alloc(
   TASK_LAYOUT.span - TASK_TAG_LAYOUT.span,
 );
@@ -27,36 +42,124 @@ jsonTagBuffer.writeUInt16LE(1);
 uint256Tagbuffer.writeUInt16LE(2);
 export const mockUrlBuffer = Buffer.from(mockURL, 'utf8');
 export const mockPathBuffer = Buffer.from(mockPath, 'utf8');
-mockUrlBuffer.copy(httpGetTaskBuffer);
+// the url is followed by a header count of 0
+mockUrlBuffer.copy(mockHttpGetTaskBuffer);
 mockPathBuffer.copy(jsonParseTaskBuffer);
-export const mockCallerProgramIdBuffer = Buffer.alloc(32);
+export const mockCallerProgramIdBuffer = Buffer.alloc(32, 3);
+export const mockRequesterBuffer = Buffer.alloc(32, 7);
+export const mockRequestFee = 5000;
+export const mockCreatedSlot = 42;
+export const mockRequestId = 3;
+export const mockRequestTip = 250;
 
 const requestIndex = Buffer.alloc(1);
 requestIndex.writeUIntLE(1, 0, 1);
+const requestFee = Buffer.alloc(8);
+requestFee.writeUInt32LE(mockRequestFee);
+const createdSlot = Buffer.alloc(8);
+createdSlot.writeUInt32LE(mockCreatedSlot);
+const requestId = Buffer.alloc(8);
+requestId.writeUInt32LE(mockRequestId);
+const requestTip = Buffer.alloc(8);
+requestTip.writeUInt32LE(mockRequestTip);
+export const decodedOptimisticResponse = {
+  disputeWindowSlots: 0,
+  status: 0,
+  data: [0, 0, 0, 0],
+  proposedSlot: 0,
+  disputerBuffer: Array.from(Buffer.alloc(32)),
+  disputeBond: 0,
+};
 export const mockRequestBuffer = Buffer.concat([
   getTagBuffer,
-  httpGetTaskBuffer,
+  mockHttpGetTaskBuffer,
   jsonTagBuffer,
   jsonParseTaskBuffer,
   uint256Tagbuffer,
   uint256TaskBuffer,
   mockCallerProgramIdBuffer,
   requestIndex,
+  mockRequesterBuffer,
+  requestFee,
+  createdSlot,
+  requestId,
+  requestTip,
+  // the Request does not accept cached answers
+  Buffer.alloc(8),
+  // the Request carries its own tasks
+  Buffer.alloc(8),
+  // Request is not in optimistic mode
+  Buffer.alloc(OPTIMISTIC_RESPONSE_LAYOUT.span),
 ]);
 
 export const mockRequestQueueBuffer = Buffer.alloc(
-  REQUEST_LAYOUT.span * MAX_REQUESTS,
+  OCCUPANCY_BITMAP_LEN + REQUEST_LAYOUT.span * DEFAULT_REQUEST_QUEUE_SIZE,
+);
+// the first two slots are occupied
+mockRequestQueueBuffer.writeUInt8(0b11);
+mockRequestBuffer.copy(mockRequestQueueBuffer, OCCUPANCY_BITMAP_LEN);
+mockRequestBuffer.copy(
+  mockRequestQueueBuffer,
+  OCCUPANCY_BITMAP_LEN + REQUEST_LAYOUT.span,
 );
-mockRequestBuffer.copy(mockRequestQueueBuffer);
-mockRequestBuffer.copy(mockRequestQueueBuffer, REQUEST_LAYOUT.span);
 
-export const mockOracleAccountBuffer = Buffer.concat([mockRequestQueueBuffer]);
+export const decodedOracleHeader = {
+  isInitialized: 1,
+  adminBuffer: Array.from(Buffer.alloc(32, 5)),
+  nodeAuthorityBuffer: Array.from(Buffer.alloc(32, 6)),
+  requestFee: mockRequestFee,
+  requestExpirySlots: 300,
+  feeMintBuffer: Array.from(Buffer.alloc(32)),
+  feeVaultBuffer: Array.from(Buffer.alloc(32)),
+  vaultAuthorityBump: 0,
+  feeSchedule: {
+    taskFees: [1000, 10, 1, 2000],
+    hostFees: [
+      { hostBuffer: Array.from(Buffer.alloc(32)), fee: 0 },
+      { hostBuffer: Array.from(Buffer.alloc(32)), fee: 0 },
+      { hostBuffer: Array.from(Buffer.alloc(32)), fee: 0 },
+      { hostBuffer: Array.from(Buffer.alloc(32)), fee: 0 },
+    ],
+  },
+  minStake: 1000000,
+  unstakeCooldownSlots: 1000,
+  disputeBond: 100000,
+  pendingAdminBuffer: Array.from(Buffer.alloc(32)),
+  maxUrlLen: 34,
+  allowedTaskKinds: 0b111,
+  quorum: 1,
+  isPaused: 0,
+  maxRequestsPerRequester: 0,
+  capacity: DEFAULT_REQUEST_QUEUE_SIZE,
+  nextRequestId: 4,
+  answerCache: Array.from({ length: ANSWER_CACHE_SIZE }, () => ({
+    taskHash: Array.from(Buffer.alloc(32)),
+    data: [0, 0, 0, 0],
+    slot: 0,
+  })),
+  supportedSecrets: Array.from({ length: MAX_ORACLE_SECRETS }, (_, i) => {
+    const name = Buffer.alloc(SECRET_NAME_LEN);
+    const host = Buffer.alloc(32);
+    if (i === 0) {
+      name.write('coingecko_pro');
+      host.write('pro-api.coingecko.com');
+    }
+    return { nameBuffer: Array.from(name), hostBuffer: Array.from(host) };
+  }),
+};
+const mockOracleHeaderBuffer = Buffer.alloc(ORACLE_HEADER_LAYOUT.span);
+ORACLE_HEADER_LAYOUT.encode(decodedOracleHeader, mockOracleHeaderBuffer);
+
+export const mockOracleAccountBuffer = Buffer.concat([
+  mockOracleHeaderBuffer,
+  mockRequestQueueBuffer,
+]);
 
 const requests = [
   {
     tasks: [
       {
-        [TaskVariantKeys[Task.HTTP_GET]]: Array.from(mockUrlBuffer),
+        [TaskVariantKeys[Task.HTTP_GET]]: Array.from(mockHttpGetTaskBuffer),
       },
       {
         [TaskVariantKeys[Task.JSON_PARSE]]: Array.from(mockPathBuffer),
@@ -67,11 +170,19 @@ const requests = [
     ],
     callerProgramIdBuffer: Array.from(mockCallerProgramIdBuffer),
     index: 1,
+    requesterBuffer: Array.from(mockRequesterBuffer),
+    fee: mockRequestFee,
+    createdSlot: mockCreatedSlot,
+    id: mockRequestId,
+    tip: mockRequestTip,
+    maxAnswerAgeSlots: 0,
+    jobId: 0,
+    optimistic: decodedOptimisticResponse,
   },
   {
     tasks: [
       {
-        [TaskVariantKeys[Task.HTTP_GET]]: Array.from(mockUrlBuffer),
+        [TaskVariantKeys[Task.HTTP_GET]]: Array.from(mockHttpGetTaskBuffer),
       },
       {
         [TaskVariantKeys[Task.JSON_PARSE]]: Array.from(mockPathBuffer),
@@ -82,6 +193,14 @@ const requests = [
     ],
     callerProgramIdBuffer: Array.from(mockCallerProgramIdBuffer),
     index: 1,
+    requesterBuffer: Array.from(mockRequesterBuffer),
+    fee: mockRequestFee,
+    createdSlot: mockCreatedSlot,
+    id: mockRequestId,
+    tip: mockRequestTip,
+    maxAnswerAgeSlots: 0,
+    jobId: 0,
+    optimistic: decodedOptimisticResponse,
   },
 ];
 const blankRequest = {
@@ -119,129 +238,28 @@ const blankRequest = {
     0,
     0,
   ],
-  tasks: [
-    {
-      urlBuffer: [
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-      ],
-    },
-    {
-      urlBuffer: [
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-      ],
-    },
-    {
-      urlBuffer: [
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-        0,
-      ],
-    },
-  ],
+  // blank slots decode as HTTP_GET tasks with an empty url
+  tasks: Array.from({ length: 3 }, () => ({
+    [TaskVariantKeys[Task.HTTP_GET]]: Array.from(Buffer.alloc(TASK_DATA_LEN)),
+  })),
   index: 0,
+  requesterBuffer: Array.from(Buffer.alloc(32)),
+  fee: 0,
+  createdSlot: 0,
+  id: 0,
+  tip: 0,
+  maxAnswerAgeSlots: 0,
+  jobId: 0,
+  optimistic: decodedOptimisticResponse,
 };
-while (requests.length < MAX_REQUESTS) {
+while (requests.length < DEFAULT_REQUEST_QUEUE_SIZE) {
   requests.push(blankRequest);
 }
 
-export const decodedRequestQueue = { requests };
+const occupancy = Array.from(Buffer.alloc(OCCUPANCY_BITMAP_LEN));
+occupancy[0] = 0b11;
+
+export const decodedRequestQueue = { occupancy, requests };
 
 const responseDataBuffer = Buffer.alloc(4);
 responseDataBuffer.writeUInt32LE(16645);